@@ -0,0 +1,281 @@
+/*
+ * LiquidCAN protocol definitions.
+ *
+ * Generated from the Rust implementation (liquidcan_rust/src/c_header.rs). Do not edit by hand,
+ * regenerate with `cargo run --bin liquidcan_codegen -- c-header ../Cpp-Implementation/LiquidCan.h`.
+ */
+#ifndef LIQUIDCAN_H
+#define LIQUIDCAN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+#define LIQUIDCAN_STATIC_ASSERT(cond, msg) static_assert(cond, msg)
+#else
+#define LIQUIDCAN_STATIC_ASSERT(cond, msg) _Static_assert(cond, msg)
+#endif
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* ---- CAN identifier ---- */
+
+#define LIQUIDCAN_ID_RECEIVER_ID_SHIFT 0u
+#define LIQUIDCAN_ID_RECEIVER_ID_MASK 0x001Fu
+#define LIQUIDCAN_ID_SENDER_ID_SHIFT 5u
+#define LIQUIDCAN_ID_SENDER_ID_MASK 0x03E0u
+#define LIQUIDCAN_ID_PRIORITY_SHIFT 10u
+#define LIQUIDCAN_ID_PRIORITY_MASK 0x0400u
+
+static inline uint16_t liquidcan_id_make(uint8_t receiver_id, uint8_t sender_id, uint8_t priority) {
+    return (uint16_t)((((uint16_t)receiver_id << LIQUIDCAN_ID_RECEIVER_ID_SHIFT) & LIQUIDCAN_ID_RECEIVER_ID_MASK)
+        | (((uint16_t)sender_id << LIQUIDCAN_ID_SENDER_ID_SHIFT) & LIQUIDCAN_ID_SENDER_ID_MASK)
+        | (((uint16_t)priority << LIQUIDCAN_ID_PRIORITY_SHIFT) & LIQUIDCAN_ID_PRIORITY_MASK));
+}
+
+static inline uint8_t liquidcan_id_receiver_id(uint16_t can_id) {
+    return (uint8_t)((can_id & LIQUIDCAN_ID_RECEIVER_ID_MASK) >> LIQUIDCAN_ID_RECEIVER_ID_SHIFT);
+}
+
+static inline uint8_t liquidcan_id_sender_id(uint16_t can_id) {
+    return (uint8_t)((can_id & LIQUIDCAN_ID_SENDER_ID_MASK) >> LIQUIDCAN_ID_SENDER_ID_SHIFT);
+}
+
+static inline uint8_t liquidcan_id_priority(uint16_t can_id) {
+    return (uint8_t)((can_id & LIQUIDCAN_ID_PRIORITY_MASK) >> LIQUIDCAN_ID_PRIORITY_SHIFT);
+}
+
+typedef uint8_t can_message_priority_t;
+enum {
+    CAN_MESSAGE_PRIORITY_LOW = 0,
+    CAN_MESSAGE_PRIORITY_HIGH = 1,
+};
+LIQUIDCAN_STATIC_ASSERT(sizeof(can_message_priority_t) == 1, "can_message_priority_t has the wrong size");
+
+/* ---- Data enums ---- */
+
+typedef uint8_t can_data_type_t;
+enum {
+    CAN_DATA_TYPE_FLOAT32 = 0,
+    CAN_DATA_TYPE_INT32 = 1,
+    CAN_DATA_TYPE_INT16 = 2,
+    CAN_DATA_TYPE_INT8 = 3,
+    CAN_DATA_TYPE_UINT32 = 4,
+    CAN_DATA_TYPE_UINT16 = 5,
+    CAN_DATA_TYPE_UINT8 = 6,
+    CAN_DATA_TYPE_BOOLEAN = 7,
+};
+LIQUIDCAN_STATIC_ASSERT(sizeof(can_data_type_t) == 1, "can_data_type_t has the wrong size");
+
+typedef uint8_t parameter_set_status_t;
+enum {
+    PARAMETER_SET_STATUS_SUCCESS = 0,
+    PARAMETER_SET_STATUS_INVALID_PARAMETER_ID = 1,
+    PARAMETER_SET_STATUS_PARAMETER_LOCKED = 2,
+    PARAMETER_SET_STATUS_NODE_TO_NODE_MODIFICATION = 3,
+};
+LIQUIDCAN_STATIC_ASSERT(sizeof(parameter_set_status_t) == 1, "parameter_set_status_t has the wrong size");
+
+typedef uint8_t parameter_lock_status_t;
+enum {
+    PARAMETER_LOCK_STATUS_UNLOCKED = 0,
+    PARAMETER_LOCK_STATUS_LOCKED = 1,
+};
+LIQUIDCAN_STATIC_ASSERT(sizeof(parameter_lock_status_t) == 1, "parameter_lock_status_t has the wrong size");
+
+/* ---- Message types ---- */
+
+typedef uint8_t can_message_type_t;
+enum {
+    CAN_MESSAGE_TYPE_NODE_INFO_REQ = 0,
+    CAN_MESSAGE_TYPE_NODE_INFO_ANNOUNCEMENT = 1,
+    CAN_MESSAGE_TYPE_INFO_STATUS = 10,
+    CAN_MESSAGE_TYPE_WARNING_STATUS = 11,
+    CAN_MESSAGE_TYPE_ERROR_STATUS = 12,
+    CAN_MESSAGE_TYPE_TELEMETRY_VALUE_REGISTRATION = 20,
+    CAN_MESSAGE_TYPE_PARAMETER_REGISTRATION = 21,
+    CAN_MESSAGE_TYPE_TELEMETRY_GROUP_DEFINITION = 30,
+    CAN_MESSAGE_TYPE_TELEMETRY_GROUP_UPDATE = 31,
+    CAN_MESSAGE_TYPE_HEARTBEAT_REQ = 40,
+    CAN_MESSAGE_TYPE_HEARTBEAT_RES = 41,
+    CAN_MESSAGE_TYPE_PARAMETER_SET_REQ = 50,
+    CAN_MESSAGE_TYPE_PARAMETER_SET_CONFIRMATION = 51,
+    CAN_MESSAGE_TYPE_PARAMETER_SET_LOCK_REQ = 52,
+    CAN_MESSAGE_TYPE_PARAMETER_SET_LOCK_CONFIRMATION = 53,
+    CAN_MESSAGE_TYPE_FIELD_GET_REQ = 60,
+    CAN_MESSAGE_TYPE_FIELD_GET_RES = 61,
+    CAN_MESSAGE_TYPE_FIELD_ID_LOOKUP_REQ = 62,
+    CAN_MESSAGE_TYPE_FIELD_ID_LOOKUP_RES = 63,
+};
+LIQUIDCAN_STATIC_ASSERT(sizeof(can_message_type_t) == 1, "can_message_type_t has the wrong size");
+
+/* ---- Payloads ---- */
+
+#pragma pack(push, 1)
+
+/* NodeInfoResPayload */
+typedef struct {
+    uint8_t tel_count;
+    uint8_t par_count;
+    uint32_t firmware_hash;
+    uint32_t liquid_hash;
+    uint8_t device_name[53];
+} node_info_res_t;
+LIQUIDCAN_STATIC_ASSERT(sizeof(node_info_res_t) == 63, "node_info_res_t has the wrong size");
+LIQUIDCAN_STATIC_ASSERT(offsetof(node_info_res_t, tel_count) == 0, "node_info_res_t.tel_count is at the wrong offset");
+LIQUIDCAN_STATIC_ASSERT(offsetof(node_info_res_t, par_count) == 1, "node_info_res_t.par_count is at the wrong offset");
+LIQUIDCAN_STATIC_ASSERT(offsetof(node_info_res_t, firmware_hash) == 2, "node_info_res_t.firmware_hash is at the wrong offset");
+LIQUIDCAN_STATIC_ASSERT(offsetof(node_info_res_t, liquid_hash) == 6, "node_info_res_t.liquid_hash is at the wrong offset");
+LIQUIDCAN_STATIC_ASSERT(offsetof(node_info_res_t, device_name) == 10, "node_info_res_t.device_name is at the wrong offset");
+
+/* StatusPayload */
+typedef struct {
+    uint8_t msg[63];
+} status_t;
+LIQUIDCAN_STATIC_ASSERT(sizeof(status_t) == 63, "status_t has the wrong size");
+LIQUIDCAN_STATIC_ASSERT(offsetof(status_t, msg) == 0, "status_t.msg is at the wrong offset");
+
+/* FieldRegistrationPayload */
+typedef struct {
+    uint8_t field_id;
+    can_data_type_t field_type;
+    uint8_t field_name[61];
+} field_registration_t;
+LIQUIDCAN_STATIC_ASSERT(sizeof(field_registration_t) == 63, "field_registration_t has the wrong size");
+LIQUIDCAN_STATIC_ASSERT(offsetof(field_registration_t, field_id) == 0, "field_registration_t.field_id is at the wrong offset");
+LIQUIDCAN_STATIC_ASSERT(offsetof(field_registration_t, field_type) == 1, "field_registration_t.field_type is at the wrong offset");
+LIQUIDCAN_STATIC_ASSERT(offsetof(field_registration_t, field_name) == 2, "field_registration_t.field_name is at the wrong offset");
+
+/* TelemetryGroupDefinitionPayload */
+typedef struct {
+    uint8_t group_id;
+    uint8_t field_ids[62];
+} telemetry_group_definition_t;
+LIQUIDCAN_STATIC_ASSERT(sizeof(telemetry_group_definition_t) == 63, "telemetry_group_definition_t has the wrong size");
+LIQUIDCAN_STATIC_ASSERT(offsetof(telemetry_group_definition_t, group_id) == 0, "telemetry_group_definition_t.group_id is at the wrong offset");
+LIQUIDCAN_STATIC_ASSERT(offsetof(telemetry_group_definition_t, field_ids) == 1, "telemetry_group_definition_t.field_ids is at the wrong offset");
+
+/* TelemetryGroupUpdatePayload */
+typedef struct {
+    uint8_t group_id;
+    uint8_t values[62];
+} telemetry_group_update_t;
+LIQUIDCAN_STATIC_ASSERT(sizeof(telemetry_group_update_t) == 63, "telemetry_group_update_t has the wrong size");
+LIQUIDCAN_STATIC_ASSERT(offsetof(telemetry_group_update_t, group_id) == 0, "telemetry_group_update_t.group_id is at the wrong offset");
+LIQUIDCAN_STATIC_ASSERT(offsetof(telemetry_group_update_t, values) == 1, "telemetry_group_update_t.values is at the wrong offset");
+
+/* HeartbeatPayload */
+typedef struct {
+    uint32_t counter;
+} heartbeat_t;
+LIQUIDCAN_STATIC_ASSERT(sizeof(heartbeat_t) == 4, "heartbeat_t has the wrong size");
+LIQUIDCAN_STATIC_ASSERT(offsetof(heartbeat_t, counter) == 0, "heartbeat_t.counter is at the wrong offset");
+
+/* ParameterSetReqPayload */
+typedef struct {
+    uint8_t parameter_id;
+    uint8_t value[61];
+} parameter_set_req_t;
+LIQUIDCAN_STATIC_ASSERT(sizeof(parameter_set_req_t) == 62, "parameter_set_req_t has the wrong size");
+LIQUIDCAN_STATIC_ASSERT(offsetof(parameter_set_req_t, parameter_id) == 0, "parameter_set_req_t.parameter_id is at the wrong offset");
+LIQUIDCAN_STATIC_ASSERT(offsetof(parameter_set_req_t, value) == 1, "parameter_set_req_t.value is at the wrong offset");
+
+/* ParameterSetConfirmationPayload */
+typedef struct {
+    uint8_t parameter_id;
+    parameter_set_status_t status;
+    uint8_t value[61];
+} parameter_set_confirmation_t;
+LIQUIDCAN_STATIC_ASSERT(sizeof(parameter_set_confirmation_t) == 63, "parameter_set_confirmation_t has the wrong size");
+LIQUIDCAN_STATIC_ASSERT(offsetof(parameter_set_confirmation_t, parameter_id) == 0, "parameter_set_confirmation_t.parameter_id is at the wrong offset");
+LIQUIDCAN_STATIC_ASSERT(offsetof(parameter_set_confirmation_t, status) == 1, "parameter_set_confirmation_t.status is at the wrong offset");
+LIQUIDCAN_STATIC_ASSERT(offsetof(parameter_set_confirmation_t, value) == 2, "parameter_set_confirmation_t.value is at the wrong offset");
+
+/* ParameterSetLockPayload */
+typedef struct {
+    uint8_t parameter_id;
+    parameter_lock_status_t parameter_lock;
+} parameter_set_lock_t;
+LIQUIDCAN_STATIC_ASSERT(sizeof(parameter_set_lock_t) == 2, "parameter_set_lock_t has the wrong size");
+LIQUIDCAN_STATIC_ASSERT(offsetof(parameter_set_lock_t, parameter_id) == 0, "parameter_set_lock_t.parameter_id is at the wrong offset");
+LIQUIDCAN_STATIC_ASSERT(offsetof(parameter_set_lock_t, parameter_lock) == 1, "parameter_set_lock_t.parameter_lock is at the wrong offset");
+
+/* FieldGetReqPayload */
+typedef struct {
+    uint8_t field_id;
+} field_get_req_t;
+LIQUIDCAN_STATIC_ASSERT(sizeof(field_get_req_t) == 1, "field_get_req_t has the wrong size");
+LIQUIDCAN_STATIC_ASSERT(offsetof(field_get_req_t, field_id) == 0, "field_get_req_t.field_id is at the wrong offset");
+
+/* FieldGetResPayload */
+typedef struct {
+    uint8_t field_id;
+    uint8_t value[62];
+} field_get_res_t;
+LIQUIDCAN_STATIC_ASSERT(sizeof(field_get_res_t) == 63, "field_get_res_t has the wrong size");
+LIQUIDCAN_STATIC_ASSERT(offsetof(field_get_res_t, field_id) == 0, "field_get_res_t.field_id is at the wrong offset");
+LIQUIDCAN_STATIC_ASSERT(offsetof(field_get_res_t, value) == 1, "field_get_res_t.value is at the wrong offset");
+
+/* FieldIDLookupReqPayload */
+typedef struct {
+    uint8_t field_name[61];
+} field_id_lookup_req_t;
+LIQUIDCAN_STATIC_ASSERT(sizeof(field_id_lookup_req_t) == 61, "field_id_lookup_req_t has the wrong size");
+LIQUIDCAN_STATIC_ASSERT(offsetof(field_id_lookup_req_t, field_name) == 0, "field_id_lookup_req_t.field_name is at the wrong offset");
+
+/* FieldIDLookupResPayload */
+typedef struct {
+    uint8_t field_id;
+    can_data_type_t field_type;
+} field_id_lookup_res_t;
+LIQUIDCAN_STATIC_ASSERT(sizeof(field_id_lookup_res_t) == 2, "field_id_lookup_res_t has the wrong size");
+LIQUIDCAN_STATIC_ASSERT(offsetof(field_id_lookup_res_t, field_id) == 0, "field_id_lookup_res_t.field_id is at the wrong offset");
+LIQUIDCAN_STATIC_ASSERT(offsetof(field_id_lookup_res_t, field_type) == 1, "field_id_lookup_res_t.field_type is at the wrong offset");
+
+/* ---- Frames ---- */
+
+/* CanMessageFrame */
+typedef struct {
+    can_message_type_t message_type;
+    uint8_t data[63];
+} can_message_frame_t;
+LIQUIDCAN_STATIC_ASSERT(sizeof(can_message_frame_t) == 64, "can_message_frame_t has the wrong size");
+LIQUIDCAN_STATIC_ASSERT(offsetof(can_message_frame_t, message_type) == 0, "can_message_frame_t.message_type is at the wrong offset");
+LIQUIDCAN_STATIC_ASSERT(offsetof(can_message_frame_t, data) == 1, "can_message_frame_t.data is at the wrong offset");
+
+/* CanMessage */
+typedef struct {
+    can_message_type_t message_type;
+    union {
+        node_info_res_t node_info_announcement;
+        status_t info_status;
+        status_t warning_status;
+        status_t error_status;
+        field_registration_t telemetry_value_registration;
+        field_registration_t parameter_registration;
+        telemetry_group_definition_t telemetry_group_definition;
+        telemetry_group_update_t telemetry_group_update;
+        heartbeat_t heartbeat_req;
+        heartbeat_t heartbeat_res;
+        parameter_set_req_t parameter_set_req;
+        parameter_set_confirmation_t parameter_set_confirmation;
+        parameter_set_lock_t parameter_set_lock_req;
+        parameter_set_lock_t parameter_set_lock_confirmation;
+        field_get_req_t field_get_req;
+        field_get_res_t field_get_res;
+        field_id_lookup_req_t field_id_lookup_req;
+        field_id_lookup_res_t field_id_lookup_res;
+        uint8_t raw[63];
+    } payload;
+} can_message_t;
+LIQUIDCAN_STATIC_ASSERT(sizeof(can_message_t) == 64, "can_message_t has the wrong size");
+
+#pragma pack(pop)
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* LIQUIDCAN_H */
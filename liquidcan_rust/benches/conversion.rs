@@ -0,0 +1,199 @@
+//! Throughput of the conversions between messages and their wire format, for every message type.
+
+use criterion::{BatchSize, Criterion, Throughput, criterion_group, criterion_main};
+use liquidcan_rust::builders::{FieldRegistration, NodeInfo};
+use liquidcan_rust::field_value::FieldValue;
+use liquidcan_rust::payloads::*;
+use liquidcan_rust::status::Severity;
+use liquidcan_rust::{CanMessage, CanMessageFrame, CanMessageType};
+use std::hint::black_box;
+
+/// One message per message type, with typical values.
+fn messages() -> Vec<(CanMessageType, CanMessage)> {
+    let field = FieldRegistration::telemetry(0x81, CanDataType::Float32, "tank_pressure").unwrap();
+    let parameter = FieldRegistration::parameter(0x01, CanDataType::UInt16, "fill_target").unwrap();
+    let mut value = [0; 62];
+    value[..4].copy_from_slice(&101.3f32.to_le_bytes());
+    let lock = ParameterSetLockPayload {
+        parameter_id: 0x01,
+        parameter_lock: ParameterLockStatus::Locked,
+    };
+    CanMessageType::iter()
+        .map(|message_type| {
+            let msg = match message_type {
+                CanMessageType::NodeInfoReq => CanMessage::NodeInfoReq,
+                CanMessageType::NodeInfoAnnouncement => NodeInfo::builder()
+                    .name("ECU-Main")
+                    .tel_count(12)
+                    .par_count(3)
+                    .firmware_hash(0xDEADBEEF)
+                    .build()
+                    .unwrap()
+                    .to_message(),
+                CanMessageType::InfoStatus => Severity::Info.message("Tank pressure nominal"),
+                CanMessageType::WarningStatus => Severity::Warning.message("Tank pressure high"),
+                CanMessageType::ErrorStatus => Severity::Error.message("Valve 2 stuck"),
+                CanMessageType::TelemetryValueRegistration => field.to_message(),
+                CanMessageType::ParameterRegistration => parameter.to_message(),
+                CanMessageType::TelemetryGroupDefinition => CanMessage::TelemetryGroupDefinition {
+                    payload: TelemetryGroupDefinitionPayload::new(1, &[0x81, 0x82, 0x83]).unwrap(),
+                },
+                CanMessageType::TelemetryGroupUpdate => CanMessage::TelemetryGroupUpdate {
+                    payload: TelemetryGroupUpdatePayload {
+                        group_id: 1,
+                        values: value,
+                    },
+                },
+                CanMessageType::HeartbeatReq => CanMessage::HeartbeatReq {
+                    payload: HeartbeatPayload::new(1234),
+                },
+                CanMessageType::HeartbeatRes => CanMessage::HeartbeatRes {
+                    payload: HeartbeatPayload::new(1234),
+                },
+                CanMessageType::ParameterSetReq => CanMessage::ParameterSetReq {
+                    payload: ParameterSetReqPayload {
+                        parameter_id: 0x01,
+                        value: value[..61].try_into().unwrap(),
+                    },
+                },
+                CanMessageType::ParameterSetConfirmation => CanMessage::ParameterSetConfirmation {
+                    payload: ParameterSetConfirmationPayload {
+                        parameter_id: 0x01,
+                        status: ParameterSetStatus::Success,
+                        value: value[..61].try_into().unwrap(),
+                    },
+                },
+                CanMessageType::ParameterSetLockReq => CanMessage::ParameterSetLockReq {
+                    payload: lock.clone(),
+                },
+                CanMessageType::ParameterSetLockConfirmation => {
+                    CanMessage::ParameterSetLockConfirmation {
+                        payload: lock.clone(),
+                    }
+                }
+                CanMessageType::FieldGetReq => CanMessage::FieldGetReq {
+                    payload: FieldGetReqPayload { field_id: 0x81 },
+                },
+                CanMessageType::FieldGetRes => CanMessage::FieldGetRes {
+                    payload: FieldGetResPayload {
+                        field_id: 0x81,
+                        value,
+                    },
+                },
+                CanMessageType::FieldIDLookupReq => CanMessage::FieldIDLookupReq {
+                    payload: FieldIDLookupReqPayload::new("tank_pressure").unwrap(),
+                },
+                CanMessageType::FieldIDLookupRes => CanMessage::FieldIDLookupRes {
+                    payload: FieldIDLookupResPayload {
+                        field_id: 0x81,
+                        field_type: CanDataType::Float32,
+                    },
+                },
+            };
+            (message_type, msg)
+        })
+        .collect()
+}
+
+fn frame_conversion(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encode_frame");
+    group.throughput(Throughput::Elements(1));
+    for (message_type, msg) in messages() {
+        group.bench_function(message_type.name(), |b| {
+            b.iter_batched(
+                || msg.clone(),
+                |msg| CanMessageFrame::from(black_box(msg)),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("decode_frame");
+    group.throughput(Throughput::Elements(1));
+    for (message_type, msg) in messages() {
+        let frame = CanMessageFrame::from(msg);
+        group.bench_function(message_type.name(), |b| {
+            b.iter_batched(
+                || frame.clone(),
+                |frame| CanMessage::try_from(black_box(frame)).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn padded_bytes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("to_bytes");
+    group.throughput(Throughput::Elements(1));
+    for (message_type, msg) in messages() {
+        group.bench_function(message_type.name(), |b| {
+            let mut buf = [0u8; 64];
+            b.iter_batched(
+                || msg.clone(),
+                |msg| black_box(msg).to_bytes(&mut buf).len(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("from_bytes");
+    group.throughput(Throughput::Elements(1));
+    for (message_type, msg) in messages() {
+        let mut buf = [0u8; 64];
+        let bytes = msg.to_bytes(&mut buf).to_vec();
+        group.bench_function(message_type.name(), |b| {
+            b.iter(|| CanMessage::from_bytes(black_box(&bytes)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn telemetry_group_unpacking(c: &mut Criterion) {
+    let groups: [(&str, Vec<FieldValue>); 3] = [
+        ("float32_x15", vec![FieldValue::Float32(1.25); 15]),
+        ("uint8_x62", vec![FieldValue::UInt8(7); 62]),
+        (
+            "mixed",
+            vec![
+                FieldValue::Float32(101.3),
+                FieldValue::Int16(-40),
+                FieldValue::Boolean(true),
+                FieldValue::UInt32(123_456),
+                FieldValue::Int8(-1),
+                FieldValue::UInt16(5000),
+                FieldValue::Int32(-70_000),
+            ],
+        ),
+    ];
+
+    let mut group = c.benchmark_group("unpack_telemetry_group");
+    group.throughput(Throughput::Elements(1));
+    for (name, values) in &groups {
+        let payload = TelemetryGroupUpdatePayload::pack(1, values).unwrap();
+        let types: Vec<CanDataType> = values.iter().map(|v| v.data_type()).collect();
+        group.bench_function(*name, |b| {
+            b.iter(|| black_box(&payload).unpack(black_box(&types)).unwrap())
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("pack_telemetry_group");
+    group.throughput(Throughput::Elements(1));
+    for (name, values) in &groups {
+        group.bench_function(*name, |b| {
+            b.iter(|| TelemetryGroupUpdatePayload::pack(1, black_box(values)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    frame_conversion,
+    padded_bytes,
+    telemetry_group_unpacking
+);
+criterion_main!(benches);
@@ -0,0 +1,49 @@
+//! Compares borrowing a message with `CanMessageRef` to decoding it into an owned `CanMessage`.
+
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use liquidcan_rust::payloads::{CanDataType, FieldIDLookupResPayload, TelemetryGroupUpdatePayload};
+use liquidcan_rust::{CanMessage, CanMessageFrame, CanMessageRef};
+use std::hint::black_box;
+
+fn bench_decode(c: &mut Criterion, name: &str, msg: CanMessage) {
+    let frame = CanMessageFrame::from(msg);
+    let mut group = c.benchmark_group(name);
+    group.bench_function("TryFrom<CanMessageFrame>", |b| {
+        b.iter_batched(
+            || frame.clone(),
+            |frame| CanMessage::try_from(black_box(frame)).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("CanMessageRef::from_frame", |b| {
+        b.iter(|| CanMessageRef::from_frame(black_box(&frame)).unwrap())
+    });
+    group.finish();
+}
+
+fn message_ref(c: &mut Criterion) {
+    bench_decode(
+        c,
+        "telemetry_group_update",
+        CanMessage::TelemetryGroupUpdate {
+            payload: TelemetryGroupUpdatePayload {
+                group_id: 3,
+                values: [0x5A; 62],
+            },
+        },
+    );
+    // Contains an enum, so the view has to validate a byte.
+    bench_decode(
+        c,
+        "field_id_lookup_res",
+        CanMessage::FieldIDLookupRes {
+            payload: FieldIDLookupResPayload {
+                field_id: 0x81,
+                field_type: CanDataType::Float32,
+            },
+        },
+    );
+}
+
+criterion_group!(benches, message_ref);
+criterion_main!(benches);
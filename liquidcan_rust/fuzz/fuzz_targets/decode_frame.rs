@@ -0,0 +1,27 @@
+//! Feeds arbitrary bytes to the frame decoder.
+//!
+//! Run with `cargo +nightly fuzz run decode_frame` from `liquidcan_rust/`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use liquidcan_rust::{CanMessage, DecodeError};
+
+fuzz_target!(|data: &[u8]| {
+    let result = CanMessage::decode(data);
+    if data.len() > 64 {
+        assert_eq!(result, Err(DecodeError::TooLong { len: data.len() }));
+        return;
+    }
+
+    // Whatever decodes successfully must re-encode identically up to the end of the payload.
+    // The rest is padding, which decoding ignores and encoding zeroes.
+    if let Ok(msg) = result {
+        let mut buf = [0u8; 64];
+        let len = msg.clone().to_bytes(&mut buf).len();
+        let mut expected = data.to_vec();
+        expected.resize(64, 0);
+        expected[len..].fill(0);
+        assert_eq!(&buf[..], &expected[..]);
+    }
+});
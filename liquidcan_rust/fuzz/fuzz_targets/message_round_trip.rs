@@ -0,0 +1,17 @@
+//! Encodes arbitrary messages and checks that they decode to the same message.
+//!
+//! Run with `cargo +nightly fuzz run message_round_trip` from `liquidcan_rust/`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use liquidcan_rust::{CanMessage, CanMessageFrame};
+
+fuzz_target!(|msg: CanMessage| {
+    let frame: CanMessageFrame = msg.clone().into();
+    assert_eq!(CanMessage::try_from(frame).as_ref(), Ok(&msg));
+
+    let mut buf = [0u8; 64];
+    let bytes = msg.clone().to_bytes(&mut buf);
+    assert_eq!(CanMessage::decode(bytes).as_ref(), Ok(&msg));
+});
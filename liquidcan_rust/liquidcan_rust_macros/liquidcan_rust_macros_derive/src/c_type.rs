@@ -0,0 +1,108 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Expr, Fields, Type};
+
+pub fn impl_c_type_derive(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &ast.ident;
+    let rust_name = name.to_string();
+
+    let kind = match &ast.data {
+        Data::Struct(data) => struct_kind(&data.fields)?,
+        Data::Enum(data) if data.variants.iter().all(|v| v.fields.is_empty()) => {
+            let variants = data.variants.iter().map(|v| {
+                let ident = &v.ident;
+                let variant_name = ident.to_string();
+                quote! {
+                    ::liquidcan_rust::c_header::CEnumVariant {
+                        name: #variant_name,
+                        value: Self::#ident as u8,
+                    }
+                }
+            });
+            quote! { ::liquidcan_rust::c_header::CTypeKind::Enum(&[#(#variants),*]) }
+        }
+        Data::Enum(data) => message_kind(data)?,
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "CType cannot be derived for unions",
+            ));
+        }
+    };
+
+    Ok(quote! {
+        impl ::liquidcan_rust::c_header::CType for #name {
+            const C_TYPE: ::liquidcan_rust::c_header::CTypeInfo =
+                ::liquidcan_rust::c_header::CTypeInfo {
+                    rust_name: #rust_name,
+                    size: ::core::mem::size_of::<Self>(),
+                    kind: #kind,
+                };
+        }
+    })
+}
+
+fn struct_kind(fields: &Fields) -> syn::Result<TokenStream> {
+    let Fields::Named(fields) = fields else {
+        return Err(syn::Error::new_spanned(
+            fields,
+            "CType can only be derived for structs with named fields",
+        ));
+    };
+    let fields = fields.named.iter().map(|field| {
+        let ident = field.ident.as_ref().expect("named field");
+        let field_name = ident.to_string();
+        let (ty, array_len) = match &field.ty {
+            Type::Array(array) => {
+                let len = &array.len;
+                (&*array.elem, quote! { Some(#len) })
+            }
+            ty => (ty, quote! { None }),
+        };
+        quote! {
+            ::liquidcan_rust::c_header::CField {
+                name: #field_name,
+                ty: &<#ty as ::liquidcan_rust::c_header::CType>::C_TYPE,
+                array_len: #array_len,
+                offset: ::core::mem::offset_of!(Self, #ident),
+            }
+        }
+    });
+    Ok(quote! { ::liquidcan_rust::c_header::CTypeKind::Struct(&[#(#fields),*]) })
+}
+
+fn message_kind(data: &syn::DataEnum) -> syn::Result<TokenStream> {
+    let mut variants = Vec::new();
+    // Variants without an explicit discriminant continue counting from the previous one.
+    let mut next_discriminant: Expr = syn::parse_quote!(0);
+    for variant in &data.variants {
+        let variant_name = variant.ident.to_string();
+        let discriminant = match &variant.discriminant {
+            Some((_, expr)) => expr.clone(),
+            None => next_discriminant.clone(),
+        };
+        next_discriminant = syn::parse_quote!((#discriminant) + 1);
+
+        let payload = match variant.fields.len() {
+            0 => quote! { None },
+            1 => {
+                let ty = &variant.fields.iter().next().expect("one field").ty;
+                quote! { Some(&<#ty as ::liquidcan_rust::c_header::CType>::C_TYPE) }
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &variant.fields,
+                    "CType message variants may carry at most one payload field",
+                ));
+            }
+        };
+        variants.push(quote! {
+            ::liquidcan_rust::c_header::CMessageVariant {
+                name: #variant_name,
+                discriminant: (#discriminant) as u8,
+                payload: #payload,
+            }
+        });
+    }
+    Ok(quote! { ::liquidcan_rust::c_header::CTypeKind::Message(&[#(#variants),*]) })
+}
@@ -0,0 +1,119 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Data, DeriveInput, Meta, Path, Token};
+
+pub fn impl_enum_discriminate_derive(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &ast.ident;
+    let vis = &ast.vis;
+    let Data::Enum(data) = &ast.data else {
+        return Err(syn::Error::new_spanned(
+            name,
+            "EnumDiscriminate can only be derived for enums",
+        ));
+    };
+    if !has_repr_u8(&ast.attrs)? {
+        return Err(syn::Error::new_spanned(
+            name,
+            "EnumDiscriminate can only be derived for enums which have the u8 repr, add `#[repr(u8)]`",
+        ));
+    }
+    let derives = companion_derives(&ast.attrs)?;
+
+    let type_name = format_ident!("{}Type", name);
+    let type_doc = format!("The variants of [`{name}`] without their fields.");
+    let idents: Vec<_> = data.variants.iter().map(|v| &v.ident).collect();
+    let names: Vec<_> = idents.iter().map(|ident| ident.to_string()).collect();
+    let companion_variants = data.variants.iter().map(|v| {
+        let ident = &v.ident;
+        match &v.discriminant {
+            Some((_, expr)) => quote! { #ident = #expr },
+            None => quote! { #ident },
+        }
+    });
+
+    Ok(quote! {
+        #[doc = #type_doc]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, #(#derives),*)]
+        #[repr(u8)]
+        #vis enum #type_name {
+            #(#companion_variants,)*
+        }
+
+        impl #type_name {
+            /// All variants, in declaration order.
+            pub const ALL: &'static [Self] = &[#(Self::#idents),*];
+
+            pub fn iter() -> impl Iterator<Item = Self> {
+                Self::ALL.iter().copied()
+            }
+
+            pub const fn name(self) -> &'static str {
+                match self {
+                    #(Self::#idents => #names,)*
+                }
+            }
+        }
+
+        impl From<#type_name> for u8 {
+            fn from(value: #type_name) -> u8 {
+                value as u8
+            }
+        }
+
+        impl TryFrom<u8> for #type_name {
+            /// The value that doesn't belong to any variant.
+            type Error = u8;
+
+            fn try_from(value: u8) -> Result<Self, u8> {
+                #type_name::iter()
+                    .find(|variant| *variant as u8 == value)
+                    .ok_or(value)
+            }
+        }
+
+        impl #name {
+            pub const fn message_type(&self) -> #type_name {
+                match self {
+                    #(Self::#idents { .. } => #type_name::#idents,)*
+                }
+            }
+
+            pub const fn discriminant(&self) -> u8 {
+                self.message_type() as u8
+            }
+        }
+    })
+}
+
+fn has_repr_u8(attrs: &[Attribute]) -> syn::Result<bool> {
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        let reprs = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+        if reprs.iter().any(|repr| repr.path().is_ident("u8")) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Collects the paths from `#[enum_discriminate(derive(...))]`, which are derived for the
+/// companion enum in addition to the standard traits.
+fn companion_derives(attrs: &[Attribute]) -> syn::Result<Vec<Path>> {
+    let mut derives = Vec::new();
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("enum_discriminate"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("derive") {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                derives.extend(Punctuated::<Path, Token![,]>::parse_terminated(&content)?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported enum_discriminate argument, expected `derive(...)`"))
+            }
+        })?;
+    }
+    Ok(derives)
+}
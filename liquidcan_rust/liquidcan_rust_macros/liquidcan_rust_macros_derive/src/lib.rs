@@ -1,51 +1,97 @@
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::Attribute;
 
-#[proc_macro_derive(EnumDiscriminate)]
+mod c_type;
+mod enum_discriminate;
+mod liquid_fields;
+mod liquid_message;
+mod telemetry_group;
+
+/// Generates a `<Name>Type` companion enum with the fieldless variants of a `#[repr(u8)]` enum,
+/// together with `message_type()` and `discriminant()` accessors.
+///
+/// The companion enum derives the standard comparison traits and can be converted from and to
+/// `u8`. Additional derives for it are listed in `#[enum_discriminate(derive(...))]`.
+#[proc_macro_derive(EnumDiscriminate, attributes(enum_discriminate))]
 pub fn enum_discriminate_derive(input: TokenStream) -> TokenStream {
-    // Construct a representation of Rust code as a syntax tree
-    // that we can manipulate.
-    let ast = syn::parse(input).unwrap();
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+    enum_discriminate::impl_enum_discriminate_derive(&ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-    // Build the trait implementation.
-    impl_enum_discriminate_derive(&ast)
+/// Describes a wire type for the C header generator in `liquidcan_rust::c_header`.
+///
+/// Structs with named fields become packed C structs, fieldless enums become `uint8_t` typedefs with
+/// one constant per variant and enums whose variants carry a single payload become the message type
+/// enum plus a tagged union of all payloads.
+#[proc_macro_derive(CType)]
+pub fn c_type_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+    c_type::impl_c_type_derive(&ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
 
-fn has_repr_u8(attrs: &[Attribute]) -> bool {
-    let mut is_u8 = false;
-    for attr in attrs {
-        if attr.path().is_ident("repr") {
-            attr.parse_nested_meta(|meta| {
-                if meta.path.is_ident("u8") {
-                    is_u8 = true;
-                }
-                Ok(())
-            })
-            .unwrap()
-        }
-    }
-    is_u8
+/// Implements `liquidcan_rust::liquid_fields::LiquidFields` for a struct.
+///
+/// Fields marked `#[telemetry]` or `#[parameter]` become fields of the node, with IDs assigned
+/// in declaration order. Telemetry values can be put into a group with `#[telemetry(group = N)]`,
+/// and both take an optional `name = "..."` that replaces the field's name on the bus.
+#[proc_macro_derive(LiquidFields, attributes(telemetry, parameter))]
+pub fn liquid_fields_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+    liquid_fields::impl_liquid_fields_derive(&ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `liquidcan_rust::telemetry_group::TelemetryGroup` for a struct.
+///
+/// Every field is a value of the group, in declaration order, and must implement
+/// `liquidcan_rust::field_value::FieldType`.
+#[proc_macro_derive(TelemetryGroup)]
+pub fn telemetry_group_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+    telemetry_group::impl_telemetry_group_derive(&ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
 
-fn impl_enum_discriminate_derive(ast: &syn::DeriveInput) -> TokenStream {
-    let name = &ast.ident;
-    if !has_repr_u8(&ast.attrs) {
-        panic!("EnumDiscriminate can only be derived for enums which have the u8 repr");
-    }
-    let generated = quote! {
-        impl #name {
-            pub const fn discriminant(&self) -> u8 {
-                // SAFETY: Because we require the enum to be marked as `repr(u8)`, its layout is a `repr(C)` `union`
-                // between `repr(C)` structs, each of which has the `u8` discriminant as its first
-                // field, so we can read the discriminant without offsetting the pointer.
-                unsafe {
-                    let ptr = self as *const Self;
-                    let discriminant_ptr = ptr.cast::<u8>();
-                    *discriminant_ptr
-                }
-            }
+/// Turns an enum into a fixed-size wire message.
+///
+/// Generates a `zerocopy` compatible `<Name>Padded` enum in which every variant is zero-padded to
+/// `size` bytes (including the `u8` tag), conversions between both enums, `to_bytes`/`from_bytes`
+/// and metadata about the wire format: `VARIANTS`, `payload_len`, `pad_len`, `discriminant_name`
+/// and `from_discriminant_name`. Variants must have named fields or none at all.
+///
+/// ```ignore
+/// #[liquid_message(size = 5)]
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// #[repr(u8)]
+/// pub enum Command {
+///     Move { val: u32 } = 1,
+///     Stop = 2,
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn liquid_message(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut size = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("size") {
+            size = Some(meta.value()?.parse::<syn::Expr>()?);
+            Ok(())
+        } else {
+            Err(meta.error("unsupported liquid_message argument, expected `size = N`"))
         }
+    });
+    syn::parse_macro_input!(args with parser);
+    let item = syn::parse_macro_input!(input as syn::ItemEnum);
+    let Some(size) = size else {
+        return syn::Error::new_spanned(&item.ident, "liquid_message requires `size = N`")
+            .into_compile_error()
+            .into();
     };
-    generated.into()
+    liquid_message::impl_liquid_message(&size, &item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
@@ -0,0 +1,220 @@
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use std::collections::BTreeMap;
+use syn::ext::IdentExt;
+use syn::{Data, DeriveInput, Field, Fields, Ident, LitInt, LitStr, Type};
+
+/// Longest name that fits into a `FieldRegistrationPayload` together with its null terminator.
+const MAX_NAME_LEN: usize = 60;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Telemetry,
+    Parameter,
+}
+
+struct LiquidField<'a> {
+    ident: &'a Ident,
+    ty: &'a Type,
+    kind: Kind,
+    id: u8,
+    name: String,
+    group: Option<u8>,
+}
+
+pub fn impl_liquid_fields_derive(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &ast.ident;
+    let Data::Struct(data) = &ast.data else {
+        return Err(syn::Error::new_spanned(
+            name,
+            "LiquidFields can only be derived for structs",
+        ));
+    };
+    let Fields::Named(named) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &data.fields,
+            "LiquidFields can only be derived for structs with named fields",
+        ));
+    };
+
+    let mut fields = Vec::new();
+    let (mut next_telemetry, mut next_parameter) = (0x81u16, 0x01u16);
+    for field in &named.named {
+        let Some((kind, name, group)) = parse_attrs(field)? else {
+            continue;
+        };
+        let next = match kind {
+            Kind::Telemetry => &mut next_telemetry,
+            Kind::Parameter => &mut next_parameter,
+        };
+        let limit = match kind {
+            Kind::Telemetry => 0xFF,
+            Kind::Parameter => 0x7F,
+        };
+        if *next > limit {
+            return Err(syn::Error::new_spanned(
+                field,
+                "too many fields of this kind, there are only 127 IDs for each",
+            ));
+        }
+        fields.push(LiquidField {
+            ident: field.ident.as_ref().expect("named field"),
+            ty: &field.ty,
+            kind,
+            id: *next as u8,
+            name,
+            group,
+        });
+        *next += 1;
+    }
+    for (i, field) in fields.iter().enumerate() {
+        if fields[..i].iter().any(|other| other.name == field.name) {
+            return Err(syn::Error::new_spanned(
+                field.ident,
+                format!("there's already a field named `{}`", field.name),
+            ));
+        }
+    }
+
+    let defs = fields.iter().map(|f| {
+        let (id, name, ty) = (f.id, &f.name, f.ty);
+        let kind = match f.kind {
+            Kind::Telemetry => quote! { Telemetry },
+            Kind::Parameter => quote! { Parameter },
+        };
+        let group = match f.group {
+            Some(group) => quote! { Some(#group) },
+            None => quote! { None },
+        };
+        quote! {
+            ::liquidcan_rust::liquid_fields::FieldDef {
+                id: #id,
+                kind: ::liquidcan_rust::builders::FieldKind::#kind,
+                data_type: <#ty as ::liquidcan_rust::field_value::FieldType>::DATA_TYPE,
+                name: #name,
+                group: #group,
+            }
+        }
+    });
+    let get_arms = fields.iter().map(|f| {
+        let (id, ident) = (f.id, f.ident);
+        quote! {
+            #id => Some(::liquidcan_rust::field_value::FieldType::into_value(self.#ident)),
+        }
+    });
+    let set_arms = fields.iter().map(|f| {
+        let (id, ident) = (f.id, f.ident);
+        quote! {
+            #id => ::liquidcan_rust::liquid_fields::set(&mut self.#ident, field_id, value),
+        }
+    });
+
+    let mut groups: BTreeMap<u8, Vec<&LiquidField>> = BTreeMap::new();
+    for field in &fields {
+        if let Some(group) = field.group {
+            groups.entry(group).or_default().push(field);
+        }
+    }
+    let group_checks = groups.iter().map(|(group, fields)| {
+        let types = fields.iter().map(|f| f.ty);
+        let message =
+            format!("the values of telemetry group {group} of `{name}` need more than 62 bytes");
+        let span = fields.last().expect("groups aren't empty").ident.span();
+        quote_spanned! {span=>
+            const _: () = assert!(
+                #(<#types as ::liquidcan_rust::field_value::FieldType>::DATA_TYPE.size())+*
+                    <= ::liquidcan_rust::field_value::GROUP_VALUES_LEN,
+                #message
+            );
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::liquidcan_rust::liquid_fields::LiquidFields for #name #ty_generics #where_clause {
+            const FIELDS: &'static [::liquidcan_rust::liquid_fields::FieldDef] = &[#(#defs),*];
+
+            fn get_field(&self, field_id: u8) -> Option<::liquidcan_rust::field_value::FieldValue> {
+                match field_id {
+                    #(#get_arms)*
+                    _ => None,
+                }
+            }
+
+            fn set_field(
+                &mut self,
+                field_id: u8,
+                value: ::liquidcan_rust::field_value::FieldValue,
+            ) -> Result<(), ::liquidcan_rust::liquid_fields::SetFieldError> {
+                match field_id {
+                    #(#set_arms)*
+                    _ => Err(::liquidcan_rust::liquid_fields::SetFieldError::UnknownField(field_id)),
+                }
+            }
+        }
+
+        #(#group_checks)*
+    })
+}
+
+/// Parses `#[telemetry(group = N, name = "...")]` or `#[parameter(name = "...")]`, both with
+/// optional arguments. Returns `None` for fields without either attribute.
+fn parse_attrs(field: &Field) -> syn::Result<Option<(Kind, String, Option<u8>)>> {
+    let mut found = None;
+    for attr in &field.attrs {
+        let kind = if attr.path().is_ident("telemetry") {
+            Kind::Telemetry
+        } else if attr.path().is_ident("parameter") {
+            Kind::Parameter
+        } else {
+            continue;
+        };
+        if found.is_some() {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "a field can only be either one telemetry value or one parameter",
+            ));
+        }
+
+        let mut name = field
+            .ident
+            .as_ref()
+            .expect("named field")
+            .unraw()
+            .to_string();
+        let mut explicit_name = None;
+        let mut group = None;
+        if !matches!(attr.meta, syn::Meta::Path(_)) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    name = lit.value();
+                    explicit_name = Some(lit);
+                    Ok(())
+                } else if meta.path.is_ident("group") && kind == Kind::Telemetry {
+                    group = Some(meta.value()?.parse::<LitInt>()?.base10_parse::<u8>()?);
+                    Ok(())
+                } else if kind == Kind::Telemetry {
+                    Err(meta.error("unsupported telemetry argument, expected `group` or `name`"))
+                } else {
+                    Err(meta.error("unsupported parameter argument, expected `name`"))
+                }
+            })?;
+        }
+        // Names taken from identifiers can contain non-ASCII characters as well.
+        if !name.is_ascii() || name.contains('\0') || name.len() > MAX_NAME_LEN {
+            let message = format!(
+                "field names must be ASCII without NUL and at most {MAX_NAME_LEN} characters long"
+            );
+            return Err(match explicit_name {
+                Some(lit) => syn::Error::new_spanned(lit, message),
+                None => syn::Error::new_spanned(
+                    &field.ident,
+                    format!("{message}, use `name = \"...\"`"),
+                ),
+            });
+        }
+        found = Some((kind, name, group));
+    }
+    Ok(found)
+}
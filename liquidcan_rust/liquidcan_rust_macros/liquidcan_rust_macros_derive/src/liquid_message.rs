@@ -0,0 +1,310 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{Expr, Fields, Ident, ItemEnum, Type};
+
+struct Variant<'a> {
+    ident: &'a Ident,
+    discriminant: Option<&'a Expr>,
+    fields: Vec<(&'a Ident, &'a Type)>,
+    body: Ident,
+}
+
+pub fn impl_liquid_message(size: &Expr, item: &ItemEnum) -> syn::Result<TokenStream> {
+    if !item.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item.generics,
+            "liquid_message enums cannot be generic",
+        ));
+    }
+
+    let original = &item.ident;
+    let vis = &item.vis;
+    let padded = format_ident!("{}Padded", original);
+    let error = format_ident!("{}FromBytesError", original);
+
+    let variants = item
+        .variants
+        .iter()
+        .map(|variant| {
+            if let Some(attr) = variant.attrs.iter().find(|a| a.path().is_ident("pad")) {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "liquid_message computes the padding itself, remove `#[pad(..)]`",
+                ));
+            }
+            let fields = match &variant.fields {
+                Fields::Named(fields) => fields
+                    .named
+                    .iter()
+                    .map(|f| (f.ident.as_ref().expect("named field"), &f.ty))
+                    .collect(),
+                Fields::Unit => Vec::new(),
+                Fields::Unnamed(fields) => {
+                    return Err(syn::Error::new_spanned(
+                        fields,
+                        "liquid_message variants must have named fields or none at all",
+                    ));
+                }
+            };
+            Ok(Variant {
+                ident: &variant.ident,
+                discriminant: variant.discriminant.as_ref().map(|(_, expr)| expr),
+                fields,
+                body: format_ident!("{}Padded_{}_Body", original, variant.ident),
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    // Pads to the largest payload if a variant doesn't fit, so that its error isn't followed by
+    // layout errors from zerocopy.
+    let payload_lens: Vec<_> = variants.iter().map(payload_len).collect();
+    let capacity = quote! {
+        {
+            let mut capacity = (#size) - 1;
+            #( if #payload_lens > capacity { capacity = #payload_lens; } )*
+            capacity
+        }
+    };
+
+    let bodies = variants.iter().map(|v| {
+        let body = &v.body;
+        let payload_len = payload_len(v);
+        let (names, types): (Vec<_>, Vec<_>) = v.fields.iter().copied().unzip();
+        let ident = v.ident;
+        // Points the error at the variant that doesn't fit.
+        let fits = quote_spanned! {ident.span()=>
+            const _: () = ::core::assert!(
+                #payload_len < (#size),
+                ::core::concat!(
+                    "variant `",
+                    ::core::stringify!(#ident),
+                    "` does not fit into ",
+                    ::core::stringify!(#size),
+                    " bytes"
+                )
+            );
+        };
+        quote! {
+            // Wraps the variant's fields to force alignment to 1, preventing the compiler from
+            // inserting uninitialized padding bytes between the tag and the fields.
+            #[repr(C, packed)]
+            #[derive(
+                ::zerocopy_derive::IntoBytes,
+                ::zerocopy_derive::TryFromBytes,
+                ::zerocopy_derive::Immutable,
+                ::zerocopy_derive::KnownLayout
+            )]
+            #[allow(non_camel_case_types)]
+            #vis struct #body {
+                #( pub #names: #types, )*
+                pub _pad: [u8; #capacity - #payload_len],
+            }
+
+            #fits
+        }
+    });
+
+    let padded_variants = variants.iter().map(|v| {
+        let ident = v.ident;
+        let body = &v.body;
+        let discriminant = v.discriminant.map(|d| quote! { = #d });
+        quote! { #ident(#body) #discriminant }
+    });
+
+    let discriminant_variants = variants.iter().map(|v| {
+        let ident = v.ident;
+        let discriminant = v.discriminant.map(|d| quote! { = #d });
+        quote! { #ident #discriminant }
+    });
+
+    let variant_table = variants.iter().map(|v| {
+        let ident = v.ident;
+        let name = ident.to_string();
+        let payload_len = payload_len(v);
+        quote! { (Discriminant::#ident as u8, #name, #payload_len) }
+    });
+
+    let payload_len_arms = variants.iter().map(|v| {
+        let ident = v.ident;
+        let payload_len = payload_len(v);
+        quote! { #original::#ident { .. } => #payload_len }
+    });
+
+    let into_padded_arms = variants.iter().map(|v| {
+        let ident = v.ident;
+        let body = &v.body;
+        let names: Vec<_> = v.fields.iter().map(|(name, _)| name).collect();
+        quote! {
+            #original::#ident { #(#names),* } => #padded::#ident(#body {
+                #(#names,)*
+                _pad: ::zerocopy::FromZeros::new_zeroed(),
+            })
+        }
+    });
+
+    let from_padded_arms = variants.iter().map(|v| {
+        let ident = v.ident;
+        let names: Vec<_> = v.fields.iter().map(|(name, _)| name).collect();
+        quote! {
+            #[allow(unused_variables)]
+            #padded::#ident(body) => #original::#ident { #(#names: body.#names),* }
+        }
+    });
+
+    let size_check = quote_spanned! {size.span()=>
+        const _: () = ::core::assert!(
+            ::core::mem::size_of::<#padded>() == (#size),
+            ::core::concat!(
+                "`",
+                ::core::stringify!(#padded),
+                "` is not ",
+                ::core::stringify!(#size),
+                " bytes large"
+            )
+        );
+    };
+
+    let padded_doc =
+        format!("Wire format of [`{original}`] with every variant padded to the same size.");
+    let error_doc = format!("Why bytes couldn't be deserialized into [`{original}`].");
+
+    Ok(quote! {
+        #item
+
+        #(#bodies)*
+
+        #[doc = #padded_doc]
+        #[repr(u8)]
+        #[derive(
+            ::zerocopy_derive::IntoBytes,
+            ::zerocopy_derive::TryFromBytes,
+            ::zerocopy_derive::Immutable,
+            ::zerocopy_derive::KnownLayout
+        )]
+        #vis enum #padded {
+            #(#padded_variants,)*
+        }
+
+        impl #original {
+            /// Discriminant, name and payload length (without tag and padding) of every variant,
+            /// in declaration order.
+            pub const VARIANTS: &'static [(u8, &'static str, usize)] = {
+                // Mirrors the discriminants of the original enum, which can't be cast to `u8`
+                // if it has variants with fields.
+                #[repr(u8)]
+                #[allow(unused)]
+                enum Discriminant {
+                    #(#discriminant_variants,)*
+                }
+                &[#(#variant_table),*]
+            };
+
+            /// Returns the number of payload bytes of this variant, without tag and padding.
+            pub fn payload_len(&self) -> usize {
+                match self {
+                    #(#payload_len_arms,)*
+                }
+            }
+
+            /// Returns the number of padding bytes of the variant with the given discriminant, or
+            /// `None` if no variant has this discriminant.
+            pub fn pad_len(discriminant: u8) -> Option<usize> {
+                Self::VARIANTS
+                    .iter()
+                    .find(|(d, _, _)| *d == discriminant)
+                    .map(|(_, _, payload_len)| (#size) - 1 - payload_len)
+            }
+
+            /// Returns the name of the variant with the given discriminant.
+            pub fn discriminant_name(discriminant: u8) -> Option<&'static str> {
+                Self::VARIANTS
+                    .iter()
+                    .find(|(d, _, _)| *d == discriminant)
+                    .map(|(_, name, _)| *name)
+            }
+
+            /// Returns the discriminant of the variant with the given name.
+            pub fn from_discriminant_name(name: &str) -> Option<u8> {
+                Self::VARIANTS
+                    .iter()
+                    .find(|(_, n, _)| *n == name)
+                    .map(|(d, _, _)| *d)
+            }
+
+            /// Serializes the enum into `buf` and returns the used part, omitting the padding.
+            pub fn to_bytes(self, buf: &mut [u8; #size]) -> &[u8] {
+                let len = 1 + self.payload_len();
+                let padded = #padded::from(self);
+                buf.copy_from_slice(::zerocopy::IntoBytes::as_bytes(&padded));
+                &buf[..len]
+            }
+
+            /// Deserializes from a byte slice, padding with zeros if necessary.
+            pub fn from_bytes(bytes: &[u8]) -> ::core::result::Result<Self, #error> {
+                let mut buf = [0u8; #size];
+                buf.get_mut(..bytes.len())
+                    .ok_or(#error::TooLong { len: bytes.len() })?
+                    .copy_from_slice(bytes);
+
+                let padded = <#padded as ::zerocopy::TryFromBytes>::try_read_from_bytes(&buf)
+                    .map_err(|_| #error::Invalid)?;
+                Ok(padded.into())
+            }
+        }
+
+        #[doc = #error_doc]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #vis enum #error {
+            /// The input has more bytes than the largest variant.
+            TooLong { len: usize },
+            /// The input doesn't start with a known discriminant, or the payload has an invalid
+            /// value.
+            Invalid,
+        }
+
+        impl ::core::fmt::Display for #error {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match self {
+                    #error::TooLong { len } => ::core::write!(
+                        f,
+                        "{} bytes don't fit into {} bytes",
+                        len,
+                        #size
+                    ),
+                    #error::Invalid => ::core::write!(
+                        f,
+                        "bytes aren't a valid `{}`",
+                        ::core::stringify!(#original)
+                    ),
+                }
+            }
+        }
+
+        impl ::core::error::Error for #error {}
+
+        impl From<#original> for #padded {
+            fn from(orig: #original) -> Self {
+                match orig {
+                    #(#into_padded_arms,)*
+                }
+            }
+        }
+
+        impl From<#padded> for #original {
+            fn from(padded: #padded) -> Self {
+                match padded {
+                    #(#from_padded_arms,)*
+                }
+            }
+        }
+
+        #size_check
+    })
+}
+
+/// The number of bytes the variant's fields take up.
+fn payload_len(variant: &Variant) -> TokenStream {
+    let types = variant.fields.iter().map(|(_, ty)| ty);
+    quote! { (0 #(+ ::core::mem::size_of::<#types>())*) }
+}
@@ -0,0 +1,76 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Fields, Index};
+
+pub fn impl_telemetry_group_derive(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &ast.ident;
+    let Data::Struct(data) = &ast.data else {
+        return Err(syn::Error::new_spanned(
+            name,
+            "TelemetryGroup can only be derived for structs",
+        ));
+    };
+
+    let types: Vec<_> = data.fields.iter().map(|f| &f.ty).collect();
+    let members: Vec<_> = data
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, f)| match &f.ident {
+            Some(ident) => quote! { #ident },
+            None => {
+                let index = Index::from(i);
+                quote! { #index }
+            }
+        })
+        .collect();
+    let bindings: Vec<_> = (0..members.len())
+        .map(|i| format_ident!("value_{i}"))
+        .collect();
+    let construct = match &data.fields {
+        Fields::Named(_) => quote! {
+            Self { #(#members: ::liquidcan_rust::field_value::FieldType::from_value(*#bindings)?),* }
+        },
+        Fields::Unnamed(_) => quote! {
+            Self(#(::liquidcan_rust::field_value::FieldType::from_value(*#bindings)?),*)
+        },
+        Fields::Unit => quote! { Self },
+    };
+
+    let size_check = (!types.is_empty()).then(|| {
+        let message = format!("the values of `{name}` need more than 62 bytes");
+        let span = data.fields.iter().last().expect("not empty").span();
+        quote_spanned! {span=>
+            const _: () = assert!(
+                #(<#types as ::liquidcan_rust::field_value::FieldType>::DATA_TYPE.size())+*
+                    <= ::liquidcan_rust::field_value::GROUP_VALUES_LEN,
+                #message
+            );
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::liquidcan_rust::telemetry_group::TelemetryGroup for #name #ty_generics #where_clause {
+            const DATA_TYPES: &'static [::liquidcan_rust::payloads::CanDataType] = &[
+                #(<#types as ::liquidcan_rust::field_value::FieldType>::DATA_TYPE),*
+            ];
+
+            fn to_values(&self) -> ::liquidcan_rust::__private::Vec<::liquidcan_rust::field_value::FieldValue> {
+                ::liquidcan_rust::__private::vec![
+                    #(::liquidcan_rust::field_value::FieldType::into_value(self.#members)),*
+                ]
+            }
+
+            fn from_values(values: &[::liquidcan_rust::field_value::FieldValue]) -> Option<Self> {
+                let [#(#bindings),*] = values else {
+                    return None;
+                };
+                Some(#construct)
+            }
+        }
+
+        #size_check
+    })
+}
@@ -1,4 +1,6 @@
-#[doc(hidden)]
-pub use paste::paste;
+//! Procedural macros for defining LiquidCAN messages, re-exported from
+//! `liquidcan_rust_macros_derive`.
 
-mod padded_enum;
+pub use liquidcan_rust_macros_derive::{
+    CType, EnumDiscriminate, LiquidFields, TelemetryGroup, liquid_message,
+};
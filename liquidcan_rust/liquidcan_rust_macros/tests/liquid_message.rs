@@ -0,0 +1,119 @@
+use liquidcan_rust_macros::liquid_message;
+use zerocopy::{IntoBytes, TryFromBytes};
+
+/// Variants of every payload size from empty to full, including fields whose alignment would
+/// add padding in a `repr(C)` struct.
+#[liquid_message(size = 8)]
+#[derive(Debug, Clone, PartialEq)]
+#[repr(u8)]
+pub enum Sample {
+    Empty,
+    Byte { value: u8 },
+    Unaligned { flag: u8, value: u32 },
+    Pair { a: u16, b: u16, c: u8 } = 10,
+    Full { data: [u8; 7] },
+    Nested { inner: Inner },
+}
+
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    zerocopy_derive::IntoBytes,
+    zerocopy_derive::TryFromBytes,
+    zerocopy_derive::Immutable,
+    zerocopy_derive::KnownLayout,
+)]
+#[repr(C, packed)]
+pub struct Inner {
+    id: u8,
+    value: i16,
+}
+
+#[test]
+fn test_layout_size() {
+    assert_eq!(size_of::<SamplePadded>(), 8);
+}
+
+#[test]
+fn test_padding_is_computed() {
+    assert_eq!(
+        Sample::VARIANTS,
+        &[
+            (0, "Empty", 0),
+            (1, "Byte", 1),
+            (2, "Unaligned", 5),
+            (10, "Pair", 5),
+            (11, "Full", 7),
+            (12, "Nested", 3),
+        ]
+    );
+    for &(discriminant, _, payload_len) in Sample::VARIANTS {
+        assert_eq!(Sample::pad_len(discriminant), Some(7 - payload_len));
+    }
+    assert_eq!(Sample::pad_len(3), None);
+}
+
+#[test]
+fn test_padded_bytes() {
+    let unaligned = Sample::Unaligned {
+        flag: 1,
+        value: 0xA1B2C3D4,
+    };
+    let padded = SamplePadded::from(unaligned.clone());
+    assert_eq!(padded.as_bytes(), &[2, 1, 0xD4, 0xC3, 0xB2, 0xA1, 0, 0]);
+    let back = SamplePadded::try_read_from_bytes(padded.as_bytes()).unwrap();
+    assert_eq!(Sample::from(back), unaligned);
+
+    let empty = SamplePadded::from(Sample::Empty);
+    assert_eq!(empty.as_bytes(), &[0; 8]);
+}
+
+#[test]
+fn test_to_from_bytes() {
+    let mut buf = [0u8; 8];
+    let samples = [
+        Sample::Empty,
+        Sample::Byte { value: 0x77 },
+        Sample::Pair { a: 1, b: 2, c: 3 },
+        Sample::Full { data: [9; 7] },
+        Sample::Nested {
+            inner: Inner { id: 4, value: -2 },
+        },
+    ];
+    for sample in samples {
+        // Padding is omitted when encoding and filled in when decoding.
+        let bytes = sample.clone().to_bytes(&mut buf).to_vec();
+        assert_eq!(bytes.len(), 1 + sample.payload_len());
+        assert_eq!(Sample::from_bytes(&bytes).unwrap(), sample);
+    }
+    assert_eq!(
+        Sample::Nested {
+            inner: Inner { id: 4, value: -2 }
+        }
+        .to_bytes(&mut buf),
+        &[12, 4, 0xFE, 0xFF]
+    );
+    assert_eq!(Sample::from_bytes(&[3]), Err(SampleFromBytesError::Invalid));
+    assert_eq!(
+        Sample::from_bytes(&[0; 9]),
+        Err(SampleFromBytesError::TooLong { len: 9 })
+    );
+    assert_eq!(Sample::from_bytes(&[0; 8]), Ok(Sample::Empty));
+    assert_eq!(
+        SampleFromBytesError::TooLong { len: 9 }.to_string(),
+        "9 bytes don't fit into 8 bytes"
+    );
+}
+
+#[test]
+fn test_discriminant_names() {
+    assert_eq!(Sample::discriminant_name(10), Some("Pair"));
+    assert_eq!(Sample::from_discriminant_name("Full"), Some(11));
+    assert_eq!(Sample::from_discriminant_name("Move"), None);
+}
+
+#[test]
+fn test_diagnostics() {
+    trybuild::TestCases::new().compile_fail("tests/ui/*.rs");
+}
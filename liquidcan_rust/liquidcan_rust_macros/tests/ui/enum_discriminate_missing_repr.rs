@@ -0,0 +1,9 @@
+use liquidcan_rust_macros::EnumDiscriminate;
+
+#[derive(EnumDiscriminate)]
+enum Command {
+    Start,
+    Stop,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use liquidcan_rust_macros::EnumDiscriminate;
+
+#[derive(EnumDiscriminate)]
+#[repr(C)]
+struct Command {
+    id: u8,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use liquidcan_rust_macros::liquid_message;
+
+#[liquid_message(size = 4)]
+#[repr(u8)]
+pub enum Command {
+    Stop,
+    #[pad(2)]
+    Jump { height: u8 },
+}
+
+fn main() {}
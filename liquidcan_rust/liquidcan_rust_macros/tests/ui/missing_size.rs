@@ -0,0 +1,9 @@
+use liquidcan_rust_macros::liquid_message;
+
+#[liquid_message]
+#[repr(u8)]
+pub enum Command {
+    Stop,
+}
+
+fn main() {}
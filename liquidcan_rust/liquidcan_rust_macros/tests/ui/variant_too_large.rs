@@ -0,0 +1,10 @@
+use liquidcan_rust_macros::liquid_message;
+
+#[liquid_message(size = 4)]
+#[repr(u8)]
+pub enum Command {
+    Stop,
+    Move { distance: u32 },
+}
+
+fn main() {}
@@ -0,0 +1,31 @@
+//! Writes generated artifacts derived from the Rust message definitions.
+//!
+//! Usage: `liquidcan_codegen <c-header|test-vectors> [OUTPUT]`. Without `OUTPUT` the result is printed to stdout.
+
+use anyhow::{Context, bail};
+use liquidcan_rust::c_header;
+
+fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let contents = match args.next().as_deref() {
+        Some("c-header") => c_header::generate(),
+        #[cfg(feature = "test-vectors")]
+        Some("test-vectors") => liquidcan_rust::test_vectors::generate(),
+        #[cfg(not(feature = "test-vectors"))]
+        Some("test-vectors") => {
+            bail!("Build with `--features test-vectors` to generate test vectors")
+        }
+        Some(other) => {
+            bail!("Unknown artifact '{other}', expected 'c-header' or 'test-vectors'")
+        }
+        None => bail!("Usage: liquidcan_codegen <c-header|test-vectors> [OUTPUT]"),
+    };
+
+    match args.next() {
+        Some(path) => {
+            std::fs::write(&path, contents).with_context(|| format!("Failed to write {path}"))?
+        }
+        None => print!("{contents}"),
+    }
+    Ok(())
+}
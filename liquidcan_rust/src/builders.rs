@@ -0,0 +1,452 @@
+//! Validated construction of messages that carry strings or field IDs.
+//!
+//! The payload structs hold fixed-size, zero-padded arrays. The types here take Rust strings
+//! instead and check the rules of the spec: strings are ASCII and null terminated, field ID 0 is
+//! reserved, parameter IDs have the top bit cleared and telemetry value IDs have it set.
+
+use crate::CanMessage;
+use crate::payloads::{
+    CanDataType, FieldIDLookupReqPayload, FieldRegistrationPayload, NodeInfoResPayload,
+    TelemetryGroupDefinitionPayload,
+};
+use crate::strings::{self, StringError};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Whether a field is a parameter or a telemetry value, which is encoded in its ID's top bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldKind {
+    Parameter,
+    Telemetry,
+}
+
+impl FieldKind {
+    /// Returns the kind of the field with the given ID, or `None` for the reserved ID 0.
+    pub fn of(field_id: u8) -> Option<Self> {
+        match field_id {
+            0 => None,
+            0x80.. => Some(FieldKind::Telemetry),
+            _ => Some(FieldKind::Parameter),
+        }
+    }
+
+    /// Checks that `field_id` is a valid ID for a field of this kind.
+    pub fn check(self, field_id: u8) -> Result<(), BuildError> {
+        match FieldKind::of(field_id) {
+            None => Err(BuildError::ReservedFieldId),
+            Some(kind) if kind != self => Err(BuildError::WrongFieldIdKind {
+                field_id,
+                expected: self,
+            }),
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// A string doesn't fit the rules for the named payload field.
+    InvalidString {
+        field: &'static str,
+        error: StringError,
+    },
+    /// Field ID 0 is reserved to mark invalid IDs.
+    ReservedFieldId,
+    /// The top bit of the field ID doesn't match the kind of field.
+    WrongFieldIdKind { field_id: u8, expected: FieldKind },
+    /// A required value wasn't set on a builder.
+    Missing(&'static str),
+    /// A telemetry group lists more fields than fit into the definition.
+    TooManyFields { len: usize, max: usize },
+    /// A node ID is above [`MAX_NODE_ID`](crate::raw_can_message::MAX_NODE_ID).
+    InvalidNodeId(u8),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::InvalidString { field, error } => write!(f, "Invalid {field}: {error}"),
+            BuildError::ReservedFieldId => write!(f, "Field ID 0 is reserved"),
+            BuildError::WrongFieldIdKind { field_id, expected } => {
+                write!(f, "Field ID {field_id:#04x} is not a valid {expected:?} ID")
+            }
+            BuildError::Missing(what) => write!(f, "Missing {what}"),
+            BuildError::TooManyFields { len, max } => {
+                write!(
+                    f,
+                    "Telemetry group has {len} fields, at most {max} are allowed"
+                )
+            }
+            BuildError::InvalidNodeId(node) => write!(f, "Node ID {node} is out of range"),
+        }
+    }
+}
+
+impl core::error::Error for BuildError {}
+
+fn encode_str<const N: usize>(field: &'static str, s: &str) -> Result<[u8; N], BuildError> {
+    strings::encode(s).map_err(|error| BuildError::InvalidString { field, error })
+}
+
+fn decode_str(field: &'static str, bytes: &[u8]) -> Result<String, BuildError> {
+    strings::decode(bytes)
+        .map(str::to_string)
+        .map_err(|error| BuildError::InvalidString { field, error })
+}
+
+/// The contents of a `NodeInfoAnnouncement`, with the device name as a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeInfo {
+    tel_count: u8,
+    par_count: u8,
+    firmware_hash: u32,
+    liquid_hash: u32,
+    device_name: String,
+}
+
+impl NodeInfo {
+    pub fn builder() -> NodeInfoBuilder {
+        NodeInfoBuilder::default()
+    }
+
+    pub fn tel_count(&self) -> u8 {
+        self.tel_count
+    }
+
+    pub fn par_count(&self) -> u8 {
+        self.par_count
+    }
+
+    pub fn firmware_hash(&self) -> u32 {
+        self.firmware_hash
+    }
+
+    pub fn liquid_hash(&self) -> u32 {
+        self.liquid_hash
+    }
+
+    pub fn device_name(&self) -> &str {
+        &self.device_name
+    }
+
+    pub fn to_payload(&self) -> NodeInfoResPayload {
+        NodeInfoResPayload {
+            tel_count: self.tel_count,
+            par_count: self.par_count,
+            firmware_hash: self.firmware_hash.into(),
+            liquid_hash: self.liquid_hash.into(),
+            device_name: strings::encode(&self.device_name).expect("validated when built"),
+        }
+    }
+
+    pub fn to_message(&self) -> CanMessage {
+        CanMessage::NodeInfoAnnouncement {
+            payload: self.to_payload(),
+        }
+    }
+}
+
+impl TryFrom<&NodeInfoResPayload> for NodeInfo {
+    type Error = BuildError;
+
+    fn try_from(payload: &NodeInfoResPayload) -> Result<Self, Self::Error> {
+        Ok(NodeInfo {
+            tel_count: payload.tel_count,
+            par_count: payload.par_count,
+            firmware_hash: payload.firmware_hash(),
+            liquid_hash: payload.liquid_hash(),
+            device_name: decode_str("device_name", &payload.device_name)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NodeInfoBuilder {
+    name: Option<String>,
+    tel_count: u8,
+    par_count: u8,
+    firmware_hash: u32,
+    liquid_hash: u32,
+}
+
+impl NodeInfoBuilder {
+    /// Sets the device name, at most 52 ASCII characters.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn tel_count(mut self, tel_count: u8) -> Self {
+        self.tel_count = tel_count;
+        self
+    }
+
+    pub fn par_count(mut self, par_count: u8) -> Self {
+        self.par_count = par_count;
+        self
+    }
+
+    pub fn firmware_hash(mut self, firmware_hash: u32) -> Self {
+        self.firmware_hash = firmware_hash;
+        self
+    }
+
+    pub fn liquid_hash(mut self, liquid_hash: u32) -> Self {
+        self.liquid_hash = liquid_hash;
+        self
+    }
+
+    pub fn build(self) -> Result<NodeInfo, BuildError> {
+        let device_name = self.name.ok_or(BuildError::Missing("device name"))?;
+        encode_str::<53>("device_name", &device_name)?;
+        Ok(NodeInfo {
+            tel_count: self.tel_count,
+            par_count: self.par_count,
+            firmware_hash: self.firmware_hash,
+            liquid_hash: self.liquid_hash,
+            device_name,
+        })
+    }
+}
+
+/// The contents of a `TelemetryValueRegistration` or `ParameterRegistration`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldRegistration {
+    kind: FieldKind,
+    field_id: u8,
+    data_type: CanDataType,
+    name: String,
+}
+
+impl FieldRegistration {
+    /// Describes a telemetry value. Its ID must have the top bit set.
+    pub fn telemetry(
+        field_id: u8,
+        data_type: CanDataType,
+        name: impl Into<String>,
+    ) -> Result<Self, BuildError> {
+        Self::new(FieldKind::Telemetry, field_id, data_type, name.into())
+    }
+
+    /// Describes a parameter. Its ID must have the top bit cleared.
+    pub fn parameter(
+        field_id: u8,
+        data_type: CanDataType,
+        name: impl Into<String>,
+    ) -> Result<Self, BuildError> {
+        Self::new(FieldKind::Parameter, field_id, data_type, name.into())
+    }
+
+    fn new(
+        kind: FieldKind,
+        field_id: u8,
+        data_type: CanDataType,
+        name: String,
+    ) -> Result<Self, BuildError> {
+        kind.check(field_id)?;
+        encode_str::<61>("field_name", &name)?;
+        Ok(FieldRegistration {
+            kind,
+            field_id,
+            data_type,
+            name,
+        })
+    }
+
+    /// Reads a received registration payload, checking it like the constructors do.
+    pub fn from_payload(
+        kind: FieldKind,
+        payload: &FieldRegistrationPayload,
+    ) -> Result<Self, BuildError> {
+        let name = decode_str("field_name", &payload.field_name)?;
+        Self::new(kind, payload.field_id, payload.field_type, name)
+    }
+
+    pub fn kind(&self) -> FieldKind {
+        self.kind
+    }
+
+    pub fn field_id(&self) -> u8 {
+        self.field_id
+    }
+
+    pub fn data_type(&self) -> CanDataType {
+        self.data_type
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn to_payload(&self) -> FieldRegistrationPayload {
+        FieldRegistrationPayload {
+            field_id: self.field_id,
+            field_type: self.data_type,
+            field_name: strings::encode(&self.name).expect("validated when built"),
+        }
+    }
+
+    pub fn to_message(&self) -> CanMessage {
+        let payload = self.to_payload();
+        match self.kind {
+            FieldKind::Telemetry => CanMessage::TelemetryValueRegistration { payload },
+            FieldKind::Parameter => CanMessage::ParameterRegistration { payload },
+        }
+    }
+}
+
+impl FieldIDLookupReqPayload {
+    /// Asks for the ID of the field with the given name.
+    pub fn new(field_name: &str) -> Result<Self, BuildError> {
+        Ok(FieldIDLookupReqPayload {
+            field_name: encode_str("field_name", field_name)?,
+        })
+    }
+}
+
+impl TelemetryGroupDefinitionPayload {
+    /// Defines a group of telemetry values. Unused entries are filled with the invalid ID 0.
+    pub fn new(group_id: u8, field_ids: &[u8]) -> Result<Self, BuildError> {
+        let mut payload = TelemetryGroupDefinitionPayload {
+            group_id,
+            field_ids: [0; 62],
+        };
+        if field_ids.len() > payload.field_ids.len() {
+            return Err(BuildError::TooManyFields {
+                len: field_ids.len(),
+                max: payload.field_ids.len(),
+            });
+        }
+        for &field_id in field_ids {
+            FieldKind::Telemetry.check(field_id)?;
+        }
+        payload.field_ids[..field_ids.len()].copy_from_slice(field_ids);
+        Ok(payload)
+    }
+
+    /// Returns the IDs of the group's fields, in the order of their values in group updates.
+    pub fn field_ids(&self) -> Vec<u8> {
+        self.field_ids
+            .iter()
+            .copied()
+            .take_while(|&id| id != 0)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_info_builder() {
+        let info = NodeInfo::builder()
+            .name("ECU-Main")
+            .tel_count(12)
+            .par_count(3)
+            .firmware_hash(0xDEADBEEF)
+            .build()
+            .unwrap();
+        let CanMessage::NodeInfoAnnouncement { payload } = info.to_message() else {
+            panic!("wrong message");
+        };
+        assert_eq!(&payload.device_name[..9], b"ECU-Main\0");
+        assert!(payload.device_name[9..].iter().all(|&b| b == 0));
+        assert_eq!(payload.firmware_hash(), 0xDEADBEEF);
+        assert_eq!(NodeInfo::try_from(&payload), Ok(info));
+    }
+
+    #[test]
+    fn test_node_info_builder_validates_name() {
+        assert_eq!(
+            NodeInfo::builder().build(),
+            Err(BuildError::Missing("device name"))
+        );
+        assert_eq!(
+            NodeInfo::builder().name("x".repeat(53)).build(),
+            Err(BuildError::InvalidString {
+                field: "device_name",
+                error: StringError::TooLong { len: 53, max: 52 }
+            })
+        );
+        assert!(NodeInfo::builder().name("x".repeat(52)).build().is_ok());
+        assert!(matches!(
+            NodeInfo::builder().name("Ventil→1").build(),
+            Err(BuildError::InvalidString {
+                error: StringError::NotAscii,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_field_registration() {
+        let reg =
+            FieldRegistration::telemetry(0x81, CanDataType::Float32, "tank_pressure").unwrap();
+        let CanMessage::TelemetryValueRegistration { payload } = reg.to_message() else {
+            panic!("wrong message");
+        };
+        assert_eq!(&payload.field_name[..14], b"tank_pressure\0");
+        assert_eq!(
+            FieldRegistration::from_payload(FieldKind::Telemetry, &payload),
+            Ok(reg)
+        );
+
+        let reg = FieldRegistration::parameter(0x05, CanDataType::Boolean, "valve_open").unwrap();
+        assert!(matches!(
+            reg.to_message(),
+            CanMessage::ParameterRegistration { .. }
+        ));
+    }
+
+    #[test]
+    fn test_field_id_rules() {
+        assert_eq!(FieldKind::of(0), None);
+        assert_eq!(FieldKind::of(0x7F), Some(FieldKind::Parameter));
+        assert_eq!(FieldKind::of(0x80), Some(FieldKind::Telemetry));
+
+        assert_eq!(
+            FieldRegistration::telemetry(0, CanDataType::UInt8, "a"),
+            Err(BuildError::ReservedFieldId)
+        );
+        assert_eq!(
+            FieldRegistration::telemetry(0x05, CanDataType::UInt8, "a"),
+            Err(BuildError::WrongFieldIdKind {
+                field_id: 0x05,
+                expected: FieldKind::Telemetry
+            })
+        );
+        assert_eq!(
+            FieldRegistration::parameter(0x85, CanDataType::UInt8, "a"),
+            Err(BuildError::WrongFieldIdKind {
+                field_id: 0x85,
+                expected: FieldKind::Parameter
+            })
+        );
+    }
+
+    #[test]
+    fn test_group_definition() {
+        let payload = TelemetryGroupDefinitionPayload::new(2, &[0x81, 0x82, 0x90]).unwrap();
+        assert_eq!(payload.field_ids(), vec![0x81, 0x82, 0x90]);
+        assert_eq!(
+            TelemetryGroupDefinitionPayload::new(2, &[0x81, 0x01]),
+            Err(BuildError::WrongFieldIdKind {
+                field_id: 0x01,
+                expected: FieldKind::Telemetry
+            })
+        );
+        assert_eq!(
+            TelemetryGroupDefinitionPayload::new(2, &[0x81; 63]),
+            Err(BuildError::TooManyFields { len: 63, max: 62 })
+        );
+    }
+
+    #[test]
+    fn test_field_id_lookup_req() {
+        let payload = FieldIDLookupReqPayload::new("tank_pressure").unwrap();
+        assert_eq!(strings::decode(&payload.field_name), Ok("tank_pressure"));
+        assert!(FieldIDLookupReqPayload::new(&"x".repeat(61)).is_err());
+    }
+}
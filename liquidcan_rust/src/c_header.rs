@@ -0,0 +1,459 @@
+//! Generates the packed C/C++ header (`Cpp-Implementation/LiquidCan.h`) from the Rust wire types.
+//!
+//! Every payload struct, data enum and the [`CanMessage`] enum derive [`CType`], which records
+//! field names, offsets and sizes. [`generate`] turns that metadata into a header so the C++
+//! implementation can no longer drift from the Rust definitions. Run
+//! `cargo run --bin liquidcan_codegen -- c-header ../Cpp-Implementation/LiquidCan.h` after changing
+//! any wire type; a unit test fails while the checked-in header is out of date.
+
+#[cfg(feature = "std")]
+use crate::payloads::{CanDataType, ParameterLockStatus, ParameterSetStatus};
+#[cfg(feature = "std")]
+use crate::raw_can_message::CanMessagePriority;
+#[cfg(feature = "std")]
+use crate::{CanMessage, CanMessageFrame, CanMessageId, CanMessageType};
+#[cfg(feature = "std")]
+use core::fmt::Write;
+
+/// A type that can be described in the generated C header.
+pub trait CType {
+    const C_TYPE: CTypeInfo;
+}
+
+/// Layout information of a single Rust type.
+#[derive(Debug)]
+pub struct CTypeInfo {
+    pub rust_name: &'static str,
+    /// Size of the Rust type. Only meaningful for primitives, structs and fieldless enums.
+    pub size: usize,
+    pub kind: CTypeKind,
+}
+
+#[derive(Debug)]
+pub enum CTypeKind {
+    /// A C builtin, e.g. `uint32_t`.
+    Primitive(&'static str),
+    Struct(&'static [CField]),
+    /// A fieldless `u8` enum.
+    Enum(&'static [CEnumVariant]),
+    /// An enum whose variants carry at most one payload each, like [`CanMessage`].
+    Message(&'static [CMessageVariant]),
+}
+
+#[derive(Debug)]
+pub struct CField {
+    pub name: &'static str,
+    /// Element type for arrays, field type otherwise.
+    pub ty: &'static CTypeInfo,
+    pub array_len: Option<usize>,
+    pub offset: usize,
+}
+
+#[derive(Debug)]
+pub struct CEnumVariant {
+    pub name: &'static str,
+    pub value: u8,
+}
+
+#[derive(Debug)]
+pub struct CMessageVariant {
+    pub name: &'static str,
+    pub discriminant: u8,
+    pub payload: Option<&'static CTypeInfo>,
+}
+
+macro_rules! impl_primitive_c_type {
+    ($($rust:ty => $c:literal),* $(,)?) => {
+        $(
+            impl CType for $rust {
+                const C_TYPE: CTypeInfo = CTypeInfo {
+                    rust_name: stringify!($rust),
+                    size: size_of::<$rust>(),
+                    kind: CTypeKind::Primitive($c),
+                };
+            }
+        )*
+    };
+}
+
+impl_primitive_c_type! {
+    u8 => "uint8_t",
+    i8 => "int8_t",
+    u16 => "uint16_t",
+    i16 => "int16_t",
+    u32 => "uint32_t",
+    // C has no byte-order types, the generated header assumes a little-endian target.
+    zerocopy::little_endian::U32 => "uint32_t",
+    i32 => "int32_t",
+    f32 => "float",
+}
+
+#[cfg(feature = "std")]
+impl CTypeInfo {
+    /// Name of the type in C: payload structs follow the spec (`NodeInfoResPayload` ->
+    /// `node_info_res_t`), everything else is the snake-cased Rust name with a `_t` suffix.
+    pub fn c_name(&self) -> String {
+        match self.kind {
+            CTypeKind::Primitive(name) => name.to_string(),
+            _ => {
+                let name = self.rust_name;
+                let name = name.strip_suffix("Payload").unwrap_or(name);
+                format!("{}_t", snake_case(name))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+/// Converts a CamelCase identifier to snake_case, keeping acronyms (`FieldIDLookupReq` ->
+/// `field_id_lookup_req`) and single-letter prefixes (`UInt32` -> `uint32`) together.
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            let starts_word = prev.is_ascii_lowercase() || prev.is_ascii_digit();
+            // Split an acronym from the following word, but only if the acronym has at least
+            // two letters: "IDLookup" -> "id_lookup", "UInt" -> "uint".
+            let ends_acronym = prev.is_ascii_uppercase()
+                && next_is_lower
+                && i >= 2
+                && chars[i - 2].is_ascii_uppercase();
+            if starts_word || ends_acronym {
+                out.push('_');
+            }
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out
+}
+
+#[cfg(feature = "std")]
+fn upper_snake_case(name: &str) -> String {
+    snake_case(name).to_ascii_uppercase()
+}
+
+#[cfg(feature = "std")]
+/// Header guard, helpers and includes emitted before any type.
+const PRELUDE: &str = "\
+/*
+ * LiquidCAN protocol definitions.
+ *
+ * Generated from the Rust implementation (liquidcan_rust/src/c_header.rs). Do not edit by hand,
+ * regenerate with `cargo run --bin liquidcan_codegen -- c-header ../Cpp-Implementation/LiquidCan.h`.
+ */
+#ifndef LIQUIDCAN_H
+#define LIQUIDCAN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+#define LIQUIDCAN_STATIC_ASSERT(cond, msg) static_assert(cond, msg)
+#else
+#define LIQUIDCAN_STATIC_ASSERT(cond, msg) _Static_assert(cond, msg)
+#endif
+
+#ifdef __cplusplus
+extern \"C\" {
+#endif
+";
+
+#[cfg(feature = "std")]
+const EPILOGUE: &str = "\
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* LIQUIDCAN_H */
+";
+
+#[cfg(feature = "std")]
+/// Generates the complete contents of `LiquidCan.h`.
+pub fn generate() -> String {
+    let mut out = String::from(PRELUDE);
+
+    out.push_str("\n/* ---- CAN identifier ---- */\n\n");
+    write_can_id(&mut out);
+    write_enum(&mut out, &CanMessagePriority::C_TYPE);
+
+    out.push_str("/* ---- Data enums ---- */\n\n");
+    for info in [
+        &CanDataType::C_TYPE,
+        &ParameterSetStatus::C_TYPE,
+        &ParameterLockStatus::C_TYPE,
+    ] {
+        write_enum(&mut out, info);
+    }
+
+    out.push_str("/* ---- Message types ---- */\n\n");
+    write_enum(&mut out, &CanMessageType::C_TYPE);
+
+    out.push_str("/* ---- Payloads ---- */\n\n#pragma pack(push, 1)\n\n");
+    let variants = message_variants(&CanMessage::C_TYPE);
+    let mut written: Vec<&str> = Vec::new();
+    for payload in variants.iter().filter_map(|v| v.payload) {
+        if !written.contains(&payload.rust_name) {
+            written.push(payload.rust_name);
+            write_struct(&mut out, payload);
+        }
+    }
+
+    out.push_str("/* ---- Frames ---- */\n\n");
+    write_struct(&mut out, &CanMessageFrame::C_TYPE);
+    write_message_union(&mut out, &CanMessage::C_TYPE);
+    out.push_str("#pragma pack(pop)\n\n");
+
+    out.push_str(EPILOGUE);
+    out
+}
+
+#[cfg(feature = "std")]
+fn message_variants(info: &CTypeInfo) -> &'static [CMessageVariant] {
+    match info.kind {
+        CTypeKind::Message(variants) => variants,
+        _ => panic!("{} is not a message enum", info.rust_name),
+    }
+}
+
+#[cfg(feature = "std")]
+fn write_can_id(out: &mut String) {
+    let fields: [(&str, u16); 3] = [
+        (
+            "RECEIVER_ID",
+            CanMessageId::new().with_receiver_id(0x1F).into(),
+        ),
+        ("SENDER_ID", CanMessageId::new().with_sender_id(0x1F).into()),
+        (
+            "PRIORITY",
+            CanMessageId::new()
+                .with_priority(CanMessagePriority::High)
+                .into(),
+        ),
+    ];
+    for (name, mask) in fields {
+        writeln!(
+            out,
+            "#define LIQUIDCAN_ID_{name}_SHIFT {}u",
+            mask.trailing_zeros()
+        )
+        .unwrap();
+        writeln!(out, "#define LIQUIDCAN_ID_{name}_MASK 0x{mask:04X}u").unwrap();
+    }
+    out.push('\n');
+
+    out.push_str(
+        "static inline uint16_t liquidcan_id_make(uint8_t receiver_id, uint8_t sender_id, uint8_t priority) {\n",
+    );
+    out.push_str("    return (uint16_t)(");
+    let parts: Vec<String> = fields
+        .iter()
+        .zip(["receiver_id", "sender_id", "priority"])
+        .map(|((name, _), arg)| {
+            format!("(((uint16_t){arg} << LIQUIDCAN_ID_{name}_SHIFT) & LIQUIDCAN_ID_{name}_MASK)")
+        })
+        .collect();
+    out.push_str(&parts.join("\n        | "));
+    out.push_str(");\n}\n\n");
+
+    for ((name, _), arg) in fields.iter().zip(["receiver_id", "sender_id", "priority"]) {
+        writeln!(
+            out,
+            "static inline uint8_t liquidcan_id_{arg}(uint16_t can_id) {{\n    \
+             return (uint8_t)((can_id & LIQUIDCAN_ID_{name}_MASK) >> LIQUIDCAN_ID_{name}_SHIFT);\n}}\n"
+        )
+        .unwrap();
+    }
+}
+
+#[cfg(feature = "std")]
+fn write_enum(out: &mut String, info: &CTypeInfo) {
+    let CTypeKind::Enum(variants) = info.kind else {
+        panic!("{} is not a fieldless enum", info.rust_name);
+    };
+    let c_name = info.c_name();
+    let prefix = upper_snake_case(info.rust_name);
+    writeln!(out, "typedef uint8_t {c_name};").unwrap();
+    out.push_str("enum {\n");
+    for variant in variants {
+        writeln!(
+            out,
+            "    {prefix}_{} = {},",
+            upper_snake_case(variant.name),
+            variant.value
+        )
+        .unwrap();
+    }
+    out.push_str("};\n");
+    writeln!(
+        out,
+        "LIQUIDCAN_STATIC_ASSERT(sizeof({c_name}) == {}, \"{c_name} has the wrong size\");\n",
+        info.size
+    )
+    .unwrap();
+}
+
+#[cfg(feature = "std")]
+fn write_struct(out: &mut String, info: &CTypeInfo) {
+    let CTypeKind::Struct(fields) = info.kind else {
+        panic!("{} is not a struct", info.rust_name);
+    };
+    let c_name = info.c_name();
+    writeln!(out, "/* {} */", info.rust_name).unwrap();
+    out.push_str("typedef struct {\n");
+    for field in fields {
+        match field.array_len {
+            Some(len) => writeln!(out, "    {} {}[{len}];", field.ty.c_name(), field.name),
+            None => writeln!(out, "    {} {};", field.ty.c_name(), field.name),
+        }
+        .unwrap();
+    }
+    writeln!(out, "}} {c_name};").unwrap();
+    writeln!(
+        out,
+        "LIQUIDCAN_STATIC_ASSERT(sizeof({c_name}) == {}, \"{c_name} has the wrong size\");",
+        info.size
+    )
+    .unwrap();
+    for field in fields {
+        writeln!(
+            out,
+            "LIQUIDCAN_STATIC_ASSERT(offsetof({c_name}, {}) == {}, \"{c_name}.{} is at the wrong offset\");",
+            field.name, field.offset, field.name
+        )
+        .unwrap();
+    }
+    out.push('\n');
+}
+
+#[cfg(feature = "std")]
+/// Writes the tagged union of all payloads, which has the same layout as a full frame.
+fn write_message_union(out: &mut String, info: &CTypeInfo) {
+    let c_name = info.c_name();
+    let frame_size = size_of::<CanMessageFrame>();
+    writeln!(out, "/* {} */", info.rust_name).unwrap();
+    out.push_str("typedef struct {\n");
+    writeln!(out, "    {} message_type;", CanMessageType::C_TYPE.c_name()).unwrap();
+    out.push_str("    union {\n");
+    for variant in message_variants(info) {
+        if let Some(payload) = variant.payload {
+            writeln!(
+                out,
+                "        {} {};",
+                payload.c_name(),
+                snake_case(variant.name)
+            )
+            .unwrap();
+        }
+    }
+    writeln!(out, "        uint8_t raw[{}];", frame_size - 1).unwrap();
+    out.push_str("    } payload;\n");
+    writeln!(out, "}} {c_name};").unwrap();
+    writeln!(
+        out,
+        "LIQUIDCAN_STATIC_ASSERT(sizeof({c_name}) == {frame_size}, \"{c_name} has the wrong size\");\n"
+    )
+    .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    const CHECKED_IN_HEADER: &str = include_str!("../../Cpp-Implementation/LiquidCan.h");
+
+    #[test]
+    fn test_snake_case() {
+        assert_eq!(snake_case("NodeInfoRes"), "node_info_res");
+        assert_eq!(snake_case("FieldIDLookupReq"), "field_id_lookup_req");
+        assert_eq!(snake_case("InvalidParameterID"), "invalid_parameter_id");
+        assert_eq!(snake_case("UInt32"), "uint32");
+        assert_eq!(snake_case("Float32"), "float32");
+        assert_eq!(snake_case("Heartbeat"), "heartbeat");
+    }
+
+    #[test]
+    fn test_c_names_match_spec() {
+        use crate::payloads::*;
+        assert_eq!(NodeInfoResPayload::C_TYPE.c_name(), "node_info_res_t");
+        assert_eq!(HeartbeatPayload::C_TYPE.c_name(), "heartbeat_t");
+        assert_eq!(
+            FieldIDLookupResPayload::C_TYPE.c_name(),
+            "field_id_lookup_res_t"
+        );
+        assert_eq!(
+            ParameterSetConfirmationPayload::C_TYPE.c_name(),
+            "parameter_set_confirmation_t"
+        );
+    }
+
+    #[test]
+    fn test_checked_in_header_is_up_to_date() {
+        assert!(
+            CHECKED_IN_HEADER == generate(),
+            "Cpp-Implementation/LiquidCan.h is out of date, regenerate it with \
+             `cargo run --bin liquidcan_codegen -- c-header ../Cpp-Implementation/LiquidCan.h`"
+        );
+    }
+
+    fn compile(compiler: &str, args: &[&str], source_name: &str, source: &str) {
+        let dir: PathBuf = std::env::temp_dir().join(format!(
+            "liquidcan_c_header_{}_{}",
+            std::process::id(),
+            source_name
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("LiquidCan.h"), generate()).unwrap();
+        std::fs::write(dir.join(source_name), source).unwrap();
+
+        let output = Command::new(compiler)
+            .args(args)
+            .arg("-fsyntax-only")
+            .arg(dir.join(source_name))
+            .output()
+            .unwrap_or_else(|e| panic!("Failed to run {compiler}: {e}"));
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(
+            output.status.success(),
+            "{compiler} rejected the generated header:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    const TEST_SOURCE: &str = r#"#include "LiquidCan.h"
+
+int main(void) {
+    can_message_t msg;
+    msg.message_type = CAN_MESSAGE_TYPE_HEARTBEAT_REQ;
+    msg.payload.heartbeat_req.counter = 42;
+    uint16_t id = liquidcan_id_make(1, 0, CAN_MESSAGE_PRIORITY_HIGH);
+    return liquidcan_id_receiver_id(id) == 1 && msg.payload.raw[0] == 42 ? 0 : 1;
+}
+"#;
+
+    #[test]
+    fn test_header_compiles_as_c() {
+        let cc = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+        compile(
+            &cc,
+            &["-std=c11", "-Wall", "-Wextra", "-Werror"],
+            "test.c",
+            TEST_SOURCE,
+        );
+    }
+
+    #[test]
+    fn test_header_compiles_as_cpp() {
+        let cxx = std::env::var("CXX").unwrap_or_else(|_| "c++".to_string());
+        compile(
+            &cxx,
+            &["-std=c++11", "-Wall", "-Wextra", "-Werror"],
+            "test.cpp",
+            TEST_SOURCE,
+        );
+    }
+}
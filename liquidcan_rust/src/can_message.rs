@@ -1,101 +1,277 @@
 use crate::payloads;
-use liquidcan_rust_macros::padded_enum;
-use liquidcan_rust_macros_derive::EnumDiscriminate;
+use crate::raw_can_message::{CanMessageId, CanMessagePriority, MAX_NODE_ID};
+use core::fmt;
+use liquidcan_rust_macros_derive::{CType, EnumDiscriminate, liquid_message};
 
-padded_enum! {
-
-(size = 64)
-
-#[derive(Debug, EnumDiscriminate, PartialEq, Clone)]
+#[liquid_message(size = 64)]
+#[derive(Debug, EnumDiscriminate, PartialEq, Clone, CType)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
+#[enum_discriminate(derive(
+    zerocopy_derive::FromZeros,
+    zerocopy_derive::IntoBytes,
+    zerocopy_derive::Immutable,
+    zerocopy_derive::KnownLayout,
+    CType
+))]
 #[repr(u8)]
 pub enum CanMessage {
     // Node Discovery and Information
-    #[pad(63)]
     NodeInfoReq = 0, // NO payload
-    #[pad(0)]
     NodeInfoAnnouncement {
         payload: payloads::NodeInfoResPayload,
     } = 1,
 
     // Status Messages
-    #[pad(0)]
     InfoStatus {
-        payload: payloads::StatusPayload
+        payload: payloads::StatusPayload,
     } = 10,
-    #[pad(0)]
     WarningStatus {
-        payload: payloads::StatusPayload
+        payload: payloads::StatusPayload,
     } = 11,
-    #[pad(0)]
     ErrorStatus {
-       payload: payloads::StatusPayload
+        payload: payloads::StatusPayload,
     } = 12,
 
     // Field Registration
-    #[pad(0)]
     TelemetryValueRegistration {
         payload: payloads::FieldRegistrationPayload,
     } = 20,
-    #[pad(0)]
     ParameterRegistration {
         payload: payloads::FieldRegistrationPayload,
     } = 21,
 
     // Telemetry Group Management
-    #[pad(0)]
     TelemetryGroupDefinition {
         payload: payloads::TelemetryGroupDefinitionPayload,
     } = 30,
-    #[pad(0)]
     TelemetryGroupUpdate {
         payload: payloads::TelemetryGroupUpdatePayload,
     } = 31,
 
     // Heartbeat
-    #[pad(59)]
     HeartbeatReq {
-        payload: payloads::HeartbeatPayload
+        payload: payloads::HeartbeatPayload,
     } = 40,
-    #[pad(59)]
     HeartbeatRes {
-        payload: payloads::HeartbeatPayload
+        payload: payloads::HeartbeatPayload,
     } = 41,
 
     // Parameter Management
-    #[pad(1)]
     ParameterSetReq {
         payload: payloads::ParameterSetReqPayload,
     } = 50,
-    #[pad(0)]
     ParameterSetConfirmation {
         payload: payloads::ParameterSetConfirmationPayload,
     } = 51,
-    #[pad(61)]
     ParameterSetLockReq {
         payload: payloads::ParameterSetLockPayload,
     } = 52,
-    #[pad(61)]
     ParameterSetLockConfirmation {
         payload: payloads::ParameterSetLockPayload,
     } = 53,
 
     // Field Access
-    #[pad(62)]
     FieldGetReq {
         payload: payloads::FieldGetReqPayload,
     } = 60,
-    #[pad(0)]
     FieldGetRes {
         payload: payloads::FieldGetResPayload,
     } = 61,
-    #[pad(2)]
     FieldIDLookupReq {
         payload: payloads::FieldIDLookupReqPayload,
     } = 62,
-    #[pad(61)]
     FieldIDLookupRes {
         payload: payloads::FieldIDLookupResPayload,
     } = 63,
 }
 
+/// Which kinds of nodes send a message to which.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    ServerToNode,
+    NodeToServer,
+    NodeToNode,
+}
+
+impl Direction {
+    /// Returns the direction of a message from `sender` to `receiver`, or `None` if both are the
+    /// server.
+    pub fn of(sender: u8, receiver: u8) -> Option<Self> {
+        match (sender, receiver) {
+            (0, 0) => None,
+            (0, _) => Some(Direction::ServerToNode),
+            (_, 0) => Some(Direction::NodeToServer),
+            _ => Some(Direction::NodeToNode),
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Direction::ServerToNode => "Server -> Node",
+            Direction::NodeToServer => "Node -> Server",
+            Direction::NodeToNode => "Node -> Node",
+        })
+    }
+}
+
+/// Static properties of a message type, following the message type table of the spec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageMetadata {
+    /// Errors, heartbeats and parameter changes go first, everything else is low priority.
+    pub default_priority: CanMessagePriority,
+    pub directions: &'static [Direction],
+    /// The message type a receiver answers with, if any.
+    pub response: Option<CanMessageType>,
+}
+
+impl MessageMetadata {
+    pub fn expects_response(&self) -> bool {
+        self.response.is_some()
+    }
+
+    /// Whether the message may be sent from `sender` to `receiver`.
+    pub fn allows(&self, sender: u8, receiver: u8) -> bool {
+        Direction::of(sender, receiver).is_some_and(|d| self.directions.contains(&d))
+    }
+}
+
+impl CanMessageType {
+    pub const fn metadata(self) -> MessageMetadata {
+        use CanMessagePriority::{High, Low};
+        use Direction::*;
+        const TO_SERVER: &[Direction] = &[NodeToServer];
+        const TO_NODE: &[Direction] = &[ServerToNode];
+        const REQUEST: &[Direction] = &[ServerToNode, NodeToNode];
+        const RESPONSE: &[Direction] = &[NodeToServer, NodeToNode];
+        const BETWEEN_NODES: &[Direction] = &[NodeToNode];
+
+        let (default_priority, directions, response) = match self {
+            CanMessageType::NodeInfoReq => {
+                (Low, TO_NODE, Some(CanMessageType::NodeInfoAnnouncement))
+            }
+            CanMessageType::NodeInfoAnnouncement => (Low, TO_SERVER, None),
+            CanMessageType::InfoStatus | CanMessageType::WarningStatus => (Low, TO_SERVER, None),
+            CanMessageType::ErrorStatus => (High, TO_SERVER, None),
+            CanMessageType::TelemetryValueRegistration
+            | CanMessageType::ParameterRegistration
+            | CanMessageType::TelemetryGroupDefinition
+            | CanMessageType::TelemetryGroupUpdate => (Low, TO_SERVER, None),
+            CanMessageType::HeartbeatReq => (High, TO_NODE, Some(CanMessageType::HeartbeatRes)),
+            CanMessageType::HeartbeatRes => (High, TO_SERVER, None),
+            CanMessageType::ParameterSetReq => (
+                High,
+                REQUEST,
+                Some(CanMessageType::ParameterSetConfirmation),
+            ),
+            CanMessageType::ParameterSetConfirmation => (High, RESPONSE, None),
+            CanMessageType::ParameterSetLockReq => (
+                High,
+                REQUEST,
+                Some(CanMessageType::ParameterSetLockConfirmation),
+            ),
+            CanMessageType::ParameterSetLockConfirmation => (High, TO_SERVER, None),
+            CanMessageType::FieldGetReq => (Low, REQUEST, Some(CanMessageType::FieldGetRes)),
+            CanMessageType::FieldGetRes => (Low, RESPONSE, None),
+            CanMessageType::FieldIDLookupReq => {
+                (Low, BETWEEN_NODES, Some(CanMessageType::FieldIDLookupRes))
+            }
+            CanMessageType::FieldIDLookupRes => (Low, BETWEEN_NODES, None),
+        };
+        MessageMetadata {
+            default_priority,
+            directions,
+            response,
+        }
+    }
+}
+
+impl CanMessage {
+    pub const fn metadata(&self) -> MessageMetadata {
+        self.message_type().metadata()
+    }
+
+    /// Returns the ID for sending this message from `sender` to `receiver`, with the message's
+    /// default priority. Use `with_priority` on the result to override it.
+    ///
+    /// # Panics
+    ///
+    /// If `sender` or `receiver` is above [`MAX_NODE_ID`]. Use [`try_id`](Self::try_id) for IDs
+    /// that weren't checked yet.
+    pub fn id(&self, sender: u8, receiver: u8) -> CanMessageId {
+        self.try_id(sender, receiver).unwrap_or_else(|| {
+            panic!("Node IDs {sender} and {receiver} must be at most {MAX_NODE_ID}")
+        })
+    }
+
+    /// Like [`id`](Self::id), but returns `None` if `sender` or `receiver` is above
+    /// [`MAX_NODE_ID`].
+    pub fn try_id(&self, sender: u8, receiver: u8) -> Option<CanMessageId> {
+        if sender > MAX_NODE_ID || receiver > MAX_NODE_ID {
+            return None;
+        }
+        Some(
+            CanMessageId::new()
+                .with_sender_id(sender)
+                .with_receiver_id(receiver)
+                .with_priority(self.metadata().default_priority),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_responses_go_back() {
+        for message_type in CanMessageType::iter() {
+            let metadata = message_type.metadata();
+            let Some(response) = metadata.response else {
+                continue;
+            };
+            let response = response.metadata();
+            assert!(!response.expects_response(), "{message_type:?}");
+            // Requests from the server are answered to the server.
+            if metadata.directions.contains(&Direction::ServerToNode) {
+                assert!(
+                    response.directions.contains(&Direction::NodeToServer),
+                    "{message_type:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_try_id_checks_node_ids() {
+        let msg = CanMessage::NodeInfoReq;
+        assert_eq!(msg.try_id(0, MAX_NODE_ID), Some(msg.id(0, MAX_NODE_ID)));
+        assert_eq!(msg.try_id(0, 32), None);
+        assert_eq!(msg.try_id(32, 0), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "must be at most 31")]
+    fn test_id_panics_for_node_32() {
+        CanMessage::NodeInfoReq.id(0, 32);
+    }
+
+    #[test]
+    fn test_id_uses_default_priority() {
+        let msg = CanMessage::NodeInfoReq;
+        let id = msg.id(0, 3);
+        assert_eq!((id.sender_id(), id.receiver_id()), (0, 3));
+        assert_eq!(id.priority(), CanMessagePriority::Low);
+        assert!(msg.metadata().allows(0, 3));
+        assert!(!msg.metadata().allows(3, 0));
+        assert!(!msg.metadata().allows(0, 0));
+
+        let msg = CanMessage::HeartbeatReq {
+            payload: payloads::HeartbeatPayload::new(1),
+        };
+        assert_eq!(msg.id(0, 3).priority(), CanMessagePriority::High);
+        let id = msg.id(0, 3).with_priority(CanMessagePriority::Low);
+        assert_eq!(id.priority(), CanMessagePriority::Low);
+    }
 }
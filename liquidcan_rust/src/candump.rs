@@ -0,0 +1,238 @@
+//! Parsing of captures recorded with `candump` from can-utils.
+//!
+//! Both the log file format (`candump -l` or `-L`) and the default screen format with optional
+//! absolute timestamps (`candump -ta`) are understood:
+//!
+//! ```text
+//! (1700000000.250000) can0 003##01F0000C03F01
+//! (1700000000.250000)  can0  003  [06]  1F 00 00 C0 3F 01
+//! ```
+//!
+//! LiquidCAN only uses 11-bit identifiers, so frames with extended identifiers are rejected.
+//! Whether a frame was a classic or a CAN FD frame, and the flags of CAN FD frames (the digit
+//! after `##`), aren't kept: a [`BusFrame`] only has the identifier and the data.
+
+use crate::CanMessageId;
+use crate::transport::BusFrame;
+use std::fmt;
+use std::io::BufRead;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub enum CandumpError {
+    Io(std::io::Error),
+    /// A line doesn't follow any of the supported formats.
+    Malformed {
+        line: usize,
+    },
+    /// A frame has a 29-bit identifier, which LiquidCAN doesn't use.
+    ExtendedId {
+        line: usize,
+    },
+}
+
+impl fmt::Display for CandumpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CandumpError::Io(e) => write!(f, "I/O error: {e}"),
+            CandumpError::Malformed { line } => write!(f, "Line {line} isn't a candump frame"),
+            CandumpError::ExtendedId { line } => {
+                write!(f, "Line {line} has an extended identifier")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CandumpError {}
+
+/// A frame from a capture.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CandumpFrame {
+    /// Time since the Unix epoch, if the capture has timestamps.
+    pub timestamp: Option<Duration>,
+    pub interface: String,
+    pub frame: BusFrame,
+}
+
+/// Parses a single line, `line` is its 1-based number for errors.
+pub fn parse_line(text: &str, line: usize) -> Result<CandumpFrame, CandumpError> {
+    let malformed = || CandumpError::Malformed { line };
+    let mut words = text.split_whitespace().peekable();
+    let timestamp = match words.peek() {
+        Some(word) if word.starts_with('(') => {
+            let word = words.next().unwrap();
+            let seconds = word
+                .strip_prefix('(')
+                .and_then(|w| w.strip_suffix(')'))
+                .and_then(|w| w.parse::<f64>().ok())
+                .and_then(|s| Duration::try_from_secs_f64(s).ok())
+                .ok_or_else(malformed)?;
+            Some(seconds)
+        }
+        _ => None,
+    };
+    let interface = words.next().ok_or_else(malformed)?;
+    let frame = words.next().ok_or_else(malformed)?;
+
+    let (id, data) = match frame.split_once('#') {
+        // Log format: `ID#DATA` or `ID##<flags>DATA`.
+        Some((id, data)) => {
+            let data = match data.strip_prefix('#') {
+                Some(fd) => fd.get(1..).ok_or_else(malformed)?,
+                None => data,
+            };
+            (id, hex_bytes(data).ok_or_else(malformed)?)
+        }
+        // Screen format: `ID [LEN] BYTE BYTE ...`.
+        None => {
+            let len = words
+                .next()
+                .and_then(|w| {
+                    w.strip_prefix('[')?
+                        .strip_suffix(']')?
+                        .parse::<usize>()
+                        .ok()
+                })
+                .ok_or_else(malformed)?;
+            let data: Option<Vec<u8>> = words
+                .by_ref()
+                .take(len)
+                .map(|b| u8::from_str_radix(b, 16).ok())
+                .collect();
+            let data = data.filter(|d| d.len() == len).ok_or_else(malformed)?;
+            (frame, data)
+        }
+    };
+
+    if id.len() > 3 {
+        return Err(CandumpError::ExtendedId { line });
+    }
+    let id = u16::from_str_radix(id, 16)
+        .ok()
+        .filter(|&id| id < 0x800)
+        .ok_or_else(malformed)?;
+    let frame = BusFrame::new(CanMessageId::from(id), &data).ok_or_else(malformed)?;
+    Ok(CandumpFrame {
+        timestamp,
+        interface: interface.to_string(),
+        frame,
+    })
+}
+
+fn hex_bytes(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Parses all frames of a capture, skipping empty lines.
+pub fn parse(reader: impl BufRead) -> impl Iterator<Item = Result<CandumpFrame, CandumpError>> {
+    reader
+        .lines()
+        .enumerate()
+        .filter(|(_, text)| !matches!(text, Ok(t) if t.trim().is_empty()))
+        .map(|(i, text)| parse_line(&text.map_err(CandumpError::Io)?, i + 1))
+}
+
+/// Parses all frames of a capture like [`parse`], together with their timestamps. Lines without
+/// timestamp get the one of the line before, or zero at the start. Errors come with the
+/// timestamp of the line before them.
+pub fn parse_timed(
+    reader: impl BufRead,
+) -> impl Iterator<Item = (Duration, Result<BusFrame, CandumpError>)> {
+    let mut timestamp = Duration::ZERO;
+    parse(reader).map(move |line| match line {
+        Ok(line) => {
+            timestamp = line.timestamp.unwrap_or(timestamp);
+            (timestamp, Ok(line.frame))
+        }
+        Err(e) => (timestamp, Err(e)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_format() {
+        let frame = parse_line("(1700000000.250000) can0 023##01F0000C03F01", 1).unwrap();
+        assert_eq!(
+            frame.timestamp,
+            Some(Duration::from_millis(1_700_000_000_250))
+        );
+        assert_eq!(frame.interface, "can0");
+        assert_eq!(frame.frame.id.sender_id(), 1);
+        assert_eq!(frame.frame.id.receiver_id(), 3);
+        assert_eq!(frame.frame.data(), &[0x1F, 0, 0, 0xC0, 0x3F, 0x01]);
+
+        let classic = parse_line("(0.5) vcan0 400#00", 1).unwrap();
+        assert_eq!(classic.frame.data(), &[0]);
+        assert_eq!(
+            classic.frame.id.priority(),
+            crate::raw_can_message::CanMessagePriority::High
+        );
+    }
+
+    #[test]
+    fn test_screen_format() {
+        let frame = parse_line("(1700000000.250000)  can0  023  [03]  1F 00 01", 1).unwrap();
+        assert_eq!(frame.frame.data(), &[0x1F, 0, 1]);
+        let untimed = parse_line("  can0  023   [2]  1F 00", 1).unwrap();
+        assert_eq!(untimed.timestamp, None);
+        assert_eq!(untimed.frame.data(), &[0x1F, 0]);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            parse_line("(1.0) can0 12345678#00", 4),
+            Err(CandumpError::ExtendedId { line: 4 })
+        ));
+        assert!(matches!(
+            parse_line("can0 023 [3] 1F 00", 2),
+            Err(CandumpError::Malformed { line: 2 })
+        ));
+        assert!(matches!(
+            parse_line("(1.0) can0 023#0", 3),
+            Err(CandumpError::Malformed { line: 3 })
+        ));
+        for timestamp in ["(1e20)", "(-1.0)", "(inf)", "(NaN)"] {
+            assert!(matches!(
+                parse_line(&format!("{timestamp} can0 023#00"), 5),
+                Err(CandumpError::Malformed { line: 5 })
+            ));
+        }
+
+        let capture = "(1.0) can0 023#00\n\n(2.0) can0 023#zz\n";
+        let results: Vec<_> = parse(capture.as_bytes()).collect();
+        assert_eq!(results.len(), 2);
+        assert!(matches!(
+            results[1],
+            Err(CandumpError::Malformed { line: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_parse_timed() {
+        let capture = "  vcan0  023  [1]  1F\n(1.5) vcan0 023#1F00\n  vcan0  023  [1]  1F\n\
+                       (2.0) vcan0 023#R\n(2.5) vcan0 023#1F\n";
+        let timed: Vec<_> = parse_timed(capture.as_bytes())
+            .map(|(timestamp, frame)| (timestamp.as_millis(), frame.is_ok()))
+            .collect();
+        assert_eq!(
+            timed,
+            vec![
+                (0, true),
+                (1500, true),
+                (1500, true),
+                (1500, false),
+                (2500, true)
+            ]
+        );
+    }
+}
@@ -0,0 +1,218 @@
+//! Export of decoded telemetry for analysis in other tools, e.g. pandas.
+//!
+//! [`write_csv`] writes a wide table with one column per `node/field_name`, either with a row
+//! per point in time that has values or resampled to a fixed period. With the `parquet` feature,
+//! `parquet::write_parquet` writes a long table with one row per value instead.
+//!
+//! Samples come from a log via [`samples_from_log`], from a capture via
+//! [`LogAnalyzer::samples`](crate::log_analyzer::LogAnalyzer::samples), or from the values
+//! received in a live session via [`FieldStore::samples`](crate::field_store::FieldStore::samples).
+
+#[cfg(feature = "parquet")]
+pub mod parquet;
+
+use crate::field_value::FieldValue;
+use crate::lcanlog::{LogError, LogReader, NamedValue};
+use crate::registry::{FieldId, NodeId};
+use std::collections::BTreeMap;
+use std::io::{self, Read, Seek, Write};
+use std::time::Duration;
+
+/// A decoded value and when it was received.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub timestamp: Duration,
+    pub node: NodeId,
+    pub field_id: FieldId,
+    /// The name the node registered for the field.
+    pub name: String,
+    pub value: FieldValue,
+}
+
+impl Sample {
+    pub fn new(timestamp: Duration, value: NamedValue) -> Self {
+        Sample {
+            timestamp,
+            node: value.node,
+            field_id: value.field_id,
+            name: value.name,
+            value: value.value,
+        }
+    }
+}
+
+/// Decodes all values in a log from its current position. Frames that can't be decoded are
+/// skipped.
+pub fn samples_from_log<R: Read + Seek>(
+    reader: &mut LogReader<R>,
+) -> Result<Vec<Sample>, LogError> {
+    let mut samples = Vec::new();
+    while let Some(frame) = reader.next_frame()? {
+        if let Ok(values) = reader.decode_values(&frame) {
+            samples.extend(values.into_iter().map(|v| Sample::new(frame.timestamp, v)));
+        }
+    }
+    Ok(samples)
+}
+
+/// Which rows a wide CSV has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvRows {
+    /// One row per timestamp that has values, with the other columns left empty.
+    Events,
+    /// One row per period from the first sample until a row at or after the last one, with the
+    /// latest value of each field at that time. The period must not be zero.
+    Resampled(Duration),
+}
+
+/// Quotes a CSV field if necessary.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Writes a wide CSV: a `timestamp` column in seconds, followed by one column per field named
+/// `node/field_name`, ordered by node and field ID. Samples must be ordered by timestamp.
+///
+/// Returns an [`io::ErrorKind::InvalidInput`] error if the resampling period is zero.
+pub fn write_csv(mut writer: impl Write, samples: &[Sample], rows: CsvRows) -> io::Result<()> {
+    if rows == CsvRows::Resampled(Duration::ZERO) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the resampling period must not be zero",
+        ));
+    }
+    let mut columns = BTreeMap::new();
+    for sample in samples {
+        columns
+            .entry((sample.node, sample.field_id))
+            .or_insert_with(|| format!("{}/{}", sample.node, sample.name));
+    }
+    let column_index: BTreeMap<_, _> = columns.keys().enumerate().map(|(i, k)| (*k, i)).collect();
+
+    write!(writer, "timestamp")?;
+    for name in columns.values() {
+        write!(writer, ",{}", csv_field(name))?;
+    }
+    writeln!(writer)?;
+
+    let mut write_row = |timestamp: Duration, cells: &[Option<FieldValue>]| -> io::Result<()> {
+        write!(writer, "{}", timestamp.as_secs_f64())?;
+        for cell in cells {
+            match cell {
+                Some(value) => write!(writer, ",{value}")?,
+                None => write!(writer, ",")?,
+            }
+        }
+        writeln!(writer)
+    };
+
+    let mut cells = vec![None; columns.len()];
+    match rows {
+        CsvRows::Events => {
+            let mut samples = samples.iter().peekable();
+            while let Some(first) = samples.peek() {
+                let timestamp = first.timestamp;
+                cells.fill(None);
+                while let Some(sample) = samples.next_if(|s| s.timestamp == timestamp) {
+                    cells[column_index[&(sample.node, sample.field_id)]] = Some(sample.value);
+                }
+                write_row(timestamp, &cells)?;
+            }
+        }
+        CsvRows::Resampled(period) => {
+            let (Some(first), Some(last)) = (samples.first(), samples.last()) else {
+                return Ok(());
+            };
+            let mut samples = samples.iter().peekable();
+            let mut timestamp = first.timestamp;
+            loop {
+                while let Some(sample) = samples.next_if(|s| s.timestamp <= timestamp) {
+                    cells[column_index[&(sample.node, sample.field_id)]] = Some(sample.value);
+                }
+                write_row(timestamp, &cells)?;
+                if timestamp >= last.timestamp {
+                    break;
+                }
+                timestamp += period;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn sample(millis: u64, field_id: FieldId, name: &str, value: FieldValue) -> Sample {
+        Sample {
+            timestamp: Duration::from_millis(millis),
+            node: 3,
+            field_id,
+            name: name.to_string(),
+            value,
+        }
+    }
+
+    pub(crate) fn example_samples() -> Vec<Sample> {
+        vec![
+            sample(0, 0x81, "tank_pressure", FieldValue::Float32(1.5)),
+            sample(0, 0x82, "vent_open", FieldValue::Boolean(false)),
+            sample(250, 0x01, "fill, target", FieldValue::UInt16(40)),
+            sample(1000, 0x81, "tank_pressure", FieldValue::Float32(2.5)),
+        ]
+    }
+
+    fn csv(rows: CsvRows) -> String {
+        let mut out = Vec::new();
+        write_csv(&mut out, &example_samples(), rows).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_csv_events() {
+        assert_eq!(
+            csv(CsvRows::Events),
+            "timestamp,\"3/fill, target\",3/tank_pressure,3/vent_open\n\
+             0,,1.5,false\n\
+             0.25,40,,\n\
+             1,,2.5,\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_resampled() {
+        assert_eq!(
+            csv(CsvRows::Resampled(Duration::from_millis(400))),
+            "timestamp,\"3/fill, target\",3/tank_pressure,3/vent_open\n\
+             0,,1.5,false\n\
+             0.4,40,1.5,false\n\
+             0.8,40,1.5,false\n\
+             1.2,40,2.5,false\n"
+        );
+        // A sample exactly on a row gets no extra row after it.
+        assert_eq!(
+            csv(CsvRows::Resampled(Duration::from_millis(500))),
+            "timestamp,\"3/fill, target\",3/tank_pressure,3/vent_open\n\
+             0,,1.5,false\n\
+             0.5,40,1.5,false\n\
+             1,40,2.5,false\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_zero_period() {
+        let mut out = Vec::new();
+        let result = write_csv(
+            &mut out,
+            &example_samples(),
+            CsvRows::Resampled(Duration::ZERO),
+        );
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert!(out.is_empty());
+    }
+}
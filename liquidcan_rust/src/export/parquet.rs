@@ -0,0 +1,122 @@
+//! Long-format export to Arrow and Parquet.
+//!
+//! Every [`Sample`] becomes a row with the columns `timestamp` (in seconds, like in the CSV),
+//! `node`, `field_id`, `field_name`, `data_type` (the name of the [`CanDataType`]) and `value`.
+//! All data types fit into the `Float64` value column without loss, booleans are 0 or 1.
+//!
+//! [`CanDataType`]: crate::payloads::CanDataType
+
+use super::Sample;
+use arrow_array::{ArrayRef, Float64Array, RecordBatch, StringArray, UInt8Array};
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+use parquet::errors::ParquetError;
+use std::fmt;
+use std::io::Write;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub enum ExportError {
+    Arrow(ArrowError),
+    Parquet(ParquetError),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Arrow(e) => write!(f, "Arrow error: {e}"),
+            ExportError::Parquet(e) => write!(f, "Parquet error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+impl From<ArrowError> for ExportError {
+    fn from(e: ArrowError) -> Self {
+        ExportError::Arrow(e)
+    }
+}
+
+impl From<ParquetError> for ExportError {
+    fn from(e: ParquetError) -> Self {
+        ExportError::Parquet(e)
+    }
+}
+
+pub fn schema() -> Schema {
+    Schema::new(vec![
+        Field::new("timestamp", DataType::Float64, false),
+        Field::new("node", DataType::UInt8, false),
+        Field::new("field_id", DataType::UInt8, false),
+        Field::new("field_name", DataType::Utf8, false),
+        Field::new("data_type", DataType::Utf8, false),
+        Field::new("value", DataType::Float64, false),
+    ])
+}
+
+pub fn to_record_batch(samples: &[Sample]) -> Result<RecordBatch, ExportError> {
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Float64Array::from_iter_values(
+            samples.iter().map(|s| s.timestamp.as_secs_f64()),
+        )),
+        Arc::new(UInt8Array::from_iter_values(samples.iter().map(|s| s.node))),
+        Arc::new(UInt8Array::from_iter_values(
+            samples.iter().map(|s| s.field_id),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            samples.iter().map(|s| &s.name),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            samples.iter().map(|s| format!("{:?}", s.value.data_type())),
+        )),
+        Arc::new(Float64Array::from_iter_values(
+            samples.iter().map(|s| s.value.as_f64()),
+        )),
+    ];
+    Ok(RecordBatch::try_new(Arc::new(schema()), columns)?)
+}
+
+/// Writes the samples as a Parquet file.
+pub fn write_parquet<W: Write + Send>(writer: W, samples: &[Sample]) -> Result<(), ExportError> {
+    let batch = to_record_batch(samples)?;
+    let mut writer = ArrowWriter::try_new(writer, batch.schema(), None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::example_samples;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::Float64Type;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    #[test]
+    fn test_parquet_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("liquidcan_export_{}.parquet", std::process::id()));
+        write_parquet(std::fs::File::create(&path).unwrap(), &example_samples()).unwrap();
+
+        let file = std::fs::File::open(&path).unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches: Vec<_> = reader.map(Result::unwrap).collect();
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.schema().as_ref(), &schema());
+        assert_eq!(batch.num_rows(), 4);
+
+        let names = batch.column(3).as_string::<i32>();
+        assert_eq!(names.value(2), "fill, target");
+        let data_types = batch.column(4).as_string::<i32>();
+        assert_eq!(data_types.value(1), "Boolean");
+        let values = batch.column(5).as_primitive::<Float64Type>();
+        assert_eq!(values.values(), &[1.5, 0.0, 40.0, 2.5]);
+        std::fs::remove_file(path).unwrap();
+    }
+}
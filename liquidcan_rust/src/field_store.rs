@@ -0,0 +1,493 @@
+//! The latest values of all fields on the bus.
+//!
+//! A [`FieldStore`] takes the messages that carry field values, decodes them with the types from
+//! a [`NodeRegistry`] and keeps the newest value of every field, optionally with a bounded
+//! history.
+//!
+//! Values are only valid for the registrations they were decoded with. When
+//! [`NodeRegistry::observe`] sees a node announce its info again, the node has restarted and
+//! may register different fields, so callers must drop its old values with
+//! [`FieldStore::forget_node`].
+
+use crate::CanMessage;
+use crate::export::Sample;
+use crate::field_value::FieldValue;
+use crate::payloads::ParameterSetStatus;
+use crate::registry::{FieldId, NodeId, NodeRegistry};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::time::Instant;
+
+/// Errors returned when a value message can't be interpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IngestError {
+    /// The node hasn't registered a field with this ID.
+    UnknownField { node: NodeId, field_id: FieldId },
+    /// The node hasn't defined this telemetry group, or not registered all of its fields.
+    UnknownGroup { node: NodeId, group_id: u8 },
+    /// The group's values need more bytes than the update carries.
+    GroupTooLarge { node: NodeId, group_id: u8 },
+}
+
+impl fmt::Display for IngestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IngestError::UnknownField { node, field_id } => {
+                write!(f, "Node {node} has no field with ID {field_id:#04x}")
+            }
+            IngestError::UnknownGroup { node, group_id } => {
+                write!(f, "Node {node} has no complete telemetry group {group_id}")
+            }
+            IngestError::GroupTooLarge { node, group_id } => {
+                write!(
+                    f,
+                    "Telemetry group {group_id} of node {node} doesn't fit into an update"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for IngestError {}
+
+/// The latest value of a field and where it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldSample {
+    pub value: FieldValue,
+    pub timestamp: Instant,
+    /// The message the value was taken from.
+    pub source: CanMessage,
+}
+
+/// Summary of the values in a field's history.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldStats {
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+#[derive(Debug, Clone)]
+struct FieldEntry {
+    latest: FieldSample,
+    history: VecDeque<(Instant, FieldValue)>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FieldStore {
+    entries: HashMap<(NodeId, FieldId), FieldEntry>,
+    history_len: usize,
+}
+
+impl FieldStore {
+    /// Creates a store that only keeps the latest value of each field.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a store that additionally keeps the last `history_len` values of each field.
+    pub fn with_history(history_len: usize) -> Self {
+        FieldStore {
+            entries: HashMap::new(),
+            history_len,
+        }
+    }
+
+    /// Stores the values carried by `msg` and returns how many there were.
+    ///
+    /// Takes the values of `TelemetryGroupUpdate`, `FieldGetRes` and `ParameterSetConfirmation`
+    /// messages, other messages are ignored. Confirmations for unknown parameter IDs carry no
+    /// value and are skipped as well.
+    pub fn ingest(
+        &mut self,
+        registry: &NodeRegistry,
+        sender: NodeId,
+        msg: &CanMessage,
+        timestamp: Instant,
+    ) -> Result<usize, IngestError> {
+        let values = field_values(registry, sender, msg)?;
+        let count = values.len();
+        for (field_id, value) in values {
+            self.insert(sender, field_id, value, timestamp, msg);
+        }
+        Ok(count)
+    }
+
+    fn insert(
+        &mut self,
+        node: NodeId,
+        field_id: FieldId,
+        value: FieldValue,
+        timestamp: Instant,
+        source: &CanMessage,
+    ) {
+        let latest = FieldSample {
+            value,
+            timestamp,
+            source: source.clone(),
+        };
+        let entry = match self.entries.entry((node, field_id)) {
+            Entry::Occupied(entry) => {
+                let entry = entry.into_mut();
+                entry.latest = latest;
+                entry
+            }
+            Entry::Vacant(entry) => entry.insert(FieldEntry {
+                latest,
+                history: VecDeque::with_capacity(self.history_len),
+            }),
+        };
+        if self.history_len > 0 {
+            if entry.history.len() == self.history_len {
+                entry.history.pop_front();
+            }
+            entry.history.push_back((timestamp, value));
+        }
+    }
+
+    pub fn latest(&self, node: NodeId, field_id: FieldId) -> Option<&FieldSample> {
+        self.entries
+            .get(&(node, field_id))
+            .map(|entry| &entry.latest)
+    }
+
+    /// Returns the remembered values of a field, oldest first.
+    pub fn history(
+        &self,
+        node: NodeId,
+        field_id: FieldId,
+    ) -> impl Iterator<Item = (Instant, FieldValue)> + '_ {
+        self.entries
+            .get(&(node, field_id))
+            .into_iter()
+            .flat_map(|entry| entry.history.iter().copied())
+    }
+
+    /// Returns statistics over the field's history, or `None` if there is none.
+    pub fn stats(&self, node: NodeId, field_id: FieldId) -> Option<FieldStats> {
+        let mut values = self.history(node, field_id).map(|(_, v)| v.as_f64());
+        let first = values.next()?;
+        let mut stats = FieldStats {
+            count: 1,
+            min: first,
+            max: first,
+            mean: first,
+        };
+        let mut sum = first;
+        for value in values {
+            stats.count += 1;
+            stats.min = stats.min.min(value);
+            stats.max = stats.max.max(value);
+            sum += value;
+        }
+        stats.mean = sum / stats.count as f64;
+        Some(stats)
+    }
+
+    /// Returns the stored values as [`Sample`]s for export, ordered by time, with timestamps
+    /// relative to `since`, e.g. the start of the session. Values from before `since` and of
+    /// fields missing from `registry` are left out.
+    ///
+    /// Uses the history of each field, or only its latest value if the store keeps no history.
+    pub fn samples(&self, registry: &NodeRegistry, since: Instant) -> Vec<Sample> {
+        let mut samples = Vec::new();
+        for (&(node, field_id), entry) in &self.entries {
+            let Some(field) = registry.field(node, field_id) else {
+                continue;
+            };
+            let latest = (entry.latest.timestamp, entry.latest.value);
+            let values = if self.history_len > 0 {
+                entry.history.iter().copied().collect()
+            } else {
+                vec![latest]
+            };
+            samples.extend(values.into_iter().filter_map(|(timestamp, value)| {
+                Some(Sample {
+                    timestamp: timestamp.checked_duration_since(since)?,
+                    node,
+                    field_id,
+                    name: field.name().to_string(),
+                    value,
+                })
+            }));
+        }
+        samples.sort_by_key(|sample| (sample.timestamp, sample.node, sample.field_id));
+        samples
+    }
+
+    /// Returns the keys of all fields that have a value.
+    pub fn keys(&self) -> impl Iterator<Item = (NodeId, FieldId)> + '_ {
+        self.entries.keys().copied()
+    }
+
+    /// Removes all values of a node, e.g. after it restarted and announced its info again.
+    pub fn forget_node(&mut self, node: NodeId) {
+        self.entries.retain(|&(owner, _), _| owner != node);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Decodes the field values carried by `msg`, with the same rules as [`FieldStore::ingest`].
+pub fn field_values(
+    registry: &NodeRegistry,
+    sender: NodeId,
+    msg: &CanMessage,
+) -> Result<Vec<(FieldId, FieldValue)>, IngestError> {
+    Ok(match msg {
+        CanMessage::TelemetryGroupUpdate { payload } => {
+            let group_id = payload.group_id;
+            let node = registry.node(sender);
+            let ids = node.and_then(|node| node.group(group_id));
+            let types = node.and_then(|node| node.group_types(group_id));
+            let (Some(ids), Some(types)) = (ids, types) else {
+                return Err(IngestError::UnknownGroup {
+                    node: sender,
+                    group_id,
+                });
+            };
+            let values = payload
+                .unpack(&types)
+                .map_err(|_| IngestError::GroupTooLarge {
+                    node: sender,
+                    group_id,
+                })?;
+            ids.iter().copied().zip(values).collect()
+        }
+        CanMessage::FieldGetRes { payload } => {
+            vec![decode(registry, sender, payload.field_id, &payload.value)?]
+        }
+        CanMessage::ParameterSetConfirmation { payload } => {
+            if payload.status == ParameterSetStatus::InvalidParameterID {
+                return Ok(Vec::new());
+            }
+            vec![decode(
+                registry,
+                sender,
+                payload.parameter_id,
+                &payload.value,
+            )?]
+        }
+        _ => Vec::new(),
+    })
+}
+
+fn decode(
+    registry: &NodeRegistry,
+    node: NodeId,
+    field_id: FieldId,
+    bytes: &[u8],
+) -> Result<(FieldId, FieldValue), IngestError> {
+    let field = registry
+        .field(node, field_id)
+        .ok_or(IngestError::UnknownField { node, field_id })?;
+    let value = FieldValue::decode(field.data_type(), bytes).expect("value fields fit any type");
+    Ok((field_id, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payloads::{
+        FieldGetResPayload, ParameterSetConfirmationPayload, TelemetryGroupUpdatePayload,
+    };
+    use crate::registry::tests::example_registry;
+    use std::time::Duration;
+
+    fn group_update(pressure: f32, vent_open: bool) -> CanMessage {
+        CanMessage::TelemetryGroupUpdate {
+            payload: TelemetryGroupUpdatePayload::pack(
+                1,
+                &[
+                    FieldValue::Float32(pressure),
+                    FieldValue::Boolean(vent_open),
+                ],
+            )
+            .unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_ingest_group_update() {
+        let registry = example_registry();
+        let mut store = FieldStore::new();
+        let now = Instant::now();
+        let msg = group_update(12.5, true);
+        assert_eq!(store.ingest(&registry, 3, &msg, now), Ok(2));
+
+        let sample = store.latest(3, 0x81).unwrap();
+        assert_eq!(sample.value, FieldValue::Float32(12.5));
+        assert_eq!(sample.timestamp, now);
+        assert_eq!(sample.source, msg);
+        assert_eq!(
+            store.latest(3, 0x82).unwrap().value,
+            FieldValue::Boolean(true)
+        );
+        assert_eq!(store.history(3, 0x81).count(), 0);
+    }
+
+    #[test]
+    fn test_ingest_single_values() {
+        let registry = example_registry();
+        let mut store = FieldStore::new();
+        let now = Instant::now();
+
+        let mut value = [0; 62];
+        value[0] = 1;
+        let msg = CanMessage::FieldGetRes {
+            payload: FieldGetResPayload {
+                field_id: 0x82,
+                value,
+            },
+        };
+        assert_eq!(store.ingest(&registry, 3, &msg, now), Ok(1));
+        assert_eq!(
+            store.latest(3, 0x82).unwrap().value,
+            FieldValue::Boolean(true)
+        );
+
+        let mut value = [0; 61];
+        value[..2].copy_from_slice(&500u16.to_le_bytes());
+        let mut payload = ParameterSetConfirmationPayload {
+            parameter_id: 0x01,
+            status: ParameterSetStatus::ParameterLocked,
+            value,
+        };
+        let msg = CanMessage::ParameterSetConfirmation {
+            payload: payload.clone(),
+        };
+        assert_eq!(store.ingest(&registry, 3, &msg, now), Ok(1));
+        assert_eq!(
+            store.latest(3, 0x01).unwrap().value,
+            FieldValue::UInt16(500)
+        );
+
+        payload.status = ParameterSetStatus::InvalidParameterID;
+        payload.parameter_id = 0x7F;
+        let msg = CanMessage::ParameterSetConfirmation { payload };
+        assert_eq!(store.ingest(&registry, 3, &msg, now), Ok(0));
+        assert_eq!(
+            store.ingest(&registry, 3, &CanMessage::NodeInfoReq, now),
+            Ok(0)
+        );
+    }
+
+    #[test]
+    fn test_ingest_unknown() {
+        let registry = example_registry();
+        let mut store = FieldStore::new();
+        let now = Instant::now();
+        assert_eq!(
+            store.ingest(&registry, 4, &group_update(1.0, false), now),
+            Err(IngestError::UnknownGroup {
+                node: 4,
+                group_id: 1
+            })
+        );
+        let msg = CanMessage::FieldGetRes {
+            payload: FieldGetResPayload {
+                field_id: 0x90,
+                value: [0; 62],
+            },
+        };
+        assert_eq!(
+            store.ingest(&registry, 3, &msg, now),
+            Err(IngestError::UnknownField {
+                node: 3,
+                field_id: 0x90
+            })
+        );
+        assert_eq!(store.keys().count(), 0);
+    }
+
+    #[test]
+    fn test_history_and_stats() {
+        let registry = example_registry();
+        let mut store = FieldStore::with_history(3);
+        let start = Instant::now();
+        assert_eq!(store.stats(3, 0x81), None);
+        for (i, pressure) in [1.0, 2.0, 4.0, 6.0].into_iter().enumerate() {
+            let at = start + Duration::from_millis(i as u64);
+            store
+                .ingest(&registry, 3, &group_update(pressure, i % 2 == 0), at)
+                .unwrap();
+        }
+
+        let history: Vec<_> = store.history(3, 0x81).collect();
+        assert_eq!(
+            history,
+            vec![
+                (start + Duration::from_millis(1), FieldValue::Float32(2.0)),
+                (start + Duration::from_millis(2), FieldValue::Float32(4.0)),
+                (start + Duration::from_millis(3), FieldValue::Float32(6.0)),
+            ]
+        );
+        assert_eq!(
+            store.stats(3, 0x81),
+            Some(FieldStats {
+                count: 3,
+                min: 2.0,
+                max: 6.0,
+                mean: 4.0
+            })
+        );
+        let vent = store.stats(3, 0x82).unwrap();
+        assert_eq!((vent.min, vent.max), (0.0, 1.0));
+    }
+
+    #[test]
+    fn test_samples() {
+        let registry = example_registry();
+        let start = Instant::now();
+        let mut store = FieldStore::with_history(2);
+        for i in 0..3 {
+            let at = start + Duration::from_millis(i);
+            store
+                .ingest(&registry, 3, &group_update(i as f32, true), at)
+                .unwrap();
+        }
+        let samples = store.samples(&registry, start + Duration::from_millis(2));
+        let values: Vec<_> = samples
+            .iter()
+            .map(|s| (s.timestamp, s.name.as_str(), s.value))
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                (Duration::ZERO, "tank_pressure", FieldValue::Float32(2.0)),
+                (Duration::ZERO, "vent_open", FieldValue::Boolean(true)),
+            ]
+        );
+        assert_eq!(store.samples(&registry, start).len(), 4);
+
+        let mut store = FieldStore::new();
+        store
+            .ingest(&registry, 3, &group_update(1.0, false), start)
+            .unwrap();
+        let samples = store.samples(&registry, start);
+        assert_eq!(samples.len(), 2);
+        assert_eq!((samples[0].node, samples[0].field_id), (3, 0x81));
+    }
+
+    #[test]
+    fn test_forget_node() {
+        let registry = example_registry();
+        let mut store = FieldStore::with_history(2);
+        let now = Instant::now();
+        store
+            .ingest(&registry, 3, &group_update(1.0, true), now)
+            .unwrap();
+        assert_eq!(store.keys().count(), 2);
+
+        store.forget_node(4);
+        assert_eq!(store.keys().count(), 2);
+        store.forget_node(3);
+        assert_eq!(store.keys().count(), 0);
+        assert_eq!(store.latest(3, 0x81), None);
+        assert_eq!(store.history(3, 0x81).count(), 0);
+    }
+}
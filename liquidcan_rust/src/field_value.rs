@@ -0,0 +1,272 @@
+//! Typed values of telemetry values and parameters, and the packing of telemetry groups.
+
+use crate::payloads::{CanDataType, TelemetryGroupUpdatePayload};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Number of value bytes in a [`TelemetryGroupUpdatePayload`].
+pub const GROUP_VALUES_LEN: usize = 62;
+
+impl CanDataType {
+    /// Number of bytes a value of this type takes up on the bus.
+    pub const fn size(self) -> usize {
+        match self {
+            CanDataType::Float32 | CanDataType::Int32 | CanDataType::UInt32 => 4,
+            CanDataType::Int16 | CanDataType::UInt16 => 2,
+            CanDataType::Int8 | CanDataType::UInt8 | CanDataType::Boolean => 1,
+        }
+    }
+}
+
+/// A value of one of the [`CanDataType`]s.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldValue {
+    Float32(f32),
+    Int32(i32),
+    Int16(i16),
+    Int8(i8),
+    UInt32(u32),
+    UInt16(u16),
+    UInt8(u8),
+    Boolean(bool),
+}
+
+impl FieldValue {
+    pub fn data_type(&self) -> CanDataType {
+        match self {
+            FieldValue::Float32(_) => CanDataType::Float32,
+            FieldValue::Int32(_) => CanDataType::Int32,
+            FieldValue::Int16(_) => CanDataType::Int16,
+            FieldValue::Int8(_) => CanDataType::Int8,
+            FieldValue::UInt32(_) => CanDataType::UInt32,
+            FieldValue::UInt16(_) => CanDataType::UInt16,
+            FieldValue::UInt8(_) => CanDataType::UInt8,
+            FieldValue::Boolean(_) => CanDataType::Boolean,
+        }
+    }
+
+    /// Converts the value to a number, with `true` as 1 and `false` as 0.
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            FieldValue::Float32(v) => v.into(),
+            FieldValue::Int32(v) => v.into(),
+            FieldValue::Int16(v) => v.into(),
+            FieldValue::Int8(v) => v.into(),
+            FieldValue::UInt32(v) => v.into(),
+            FieldValue::UInt16(v) => v.into(),
+            FieldValue::UInt8(v) => v.into(),
+            FieldValue::Boolean(v) => u8::from(v).into(),
+        }
+    }
+
+    /// Decodes a value from the start of `bytes`. Returns `None` if `bytes` is too short.
+    pub fn decode(data_type: CanDataType, bytes: &[u8]) -> Option<Self> {
+        let bytes = bytes.get(..data_type.size())?;
+        Some(match data_type {
+            CanDataType::Float32 => FieldValue::Float32(f32::from_le_bytes(bytes.try_into().ok()?)),
+            CanDataType::Int32 => FieldValue::Int32(i32::from_le_bytes(bytes.try_into().ok()?)),
+            CanDataType::Int16 => FieldValue::Int16(i16::from_le_bytes(bytes.try_into().ok()?)),
+            CanDataType::Int8 => FieldValue::Int8(bytes[0] as i8),
+            CanDataType::UInt32 => FieldValue::UInt32(u32::from_le_bytes(bytes.try_into().ok()?)),
+            CanDataType::UInt16 => FieldValue::UInt16(u16::from_le_bytes(bytes.try_into().ok()?)),
+            CanDataType::UInt8 => FieldValue::UInt8(bytes[0]),
+            // Any non-zero value means true.
+            CanDataType::Boolean => FieldValue::Boolean(bytes[0] != 0),
+        })
+    }
+
+    /// Encodes the value into the start of `buf` and returns the number of bytes written, or
+    /// `None` if `buf` is too short.
+    pub fn encode(&self, buf: &mut [u8]) -> Option<usize> {
+        let len = self.data_type().size();
+        let buf = buf.get_mut(..len)?;
+        match *self {
+            FieldValue::Float32(v) => buf.copy_from_slice(&v.to_le_bytes()),
+            FieldValue::Int32(v) => buf.copy_from_slice(&v.to_le_bytes()),
+            FieldValue::Int16(v) => buf.copy_from_slice(&v.to_le_bytes()),
+            FieldValue::Int8(v) => buf.copy_from_slice(&v.to_le_bytes()),
+            FieldValue::UInt32(v) => buf.copy_from_slice(&v.to_le_bytes()),
+            FieldValue::UInt16(v) => buf.copy_from_slice(&v.to_le_bytes()),
+            FieldValue::UInt8(v) => buf[0] = v,
+            FieldValue::Boolean(v) => buf[0] = v as u8,
+        }
+        Some(len)
+    }
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Float32(v) => write!(f, "{v}"),
+            FieldValue::Int32(v) => write!(f, "{v}"),
+            FieldValue::Int16(v) => write!(f, "{v}"),
+            FieldValue::Int8(v) => write!(f, "{v}"),
+            FieldValue::UInt32(v) => write!(f, "{v}"),
+            FieldValue::UInt16(v) => write!(f, "{v}"),
+            FieldValue::UInt8(v) => write!(f, "{v}"),
+            FieldValue::Boolean(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+/// Rust types that correspond to one of the [`CanDataType`]s.
+pub trait FieldType: Copy {
+    const DATA_TYPE: CanDataType;
+
+    fn into_value(self) -> FieldValue;
+
+    /// Returns `None` if the value has a different type.
+    fn from_value(value: FieldValue) -> Option<Self>;
+}
+
+macro_rules! impl_field_type {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl FieldType for $ty {
+                const DATA_TYPE: CanDataType = CanDataType::$variant;
+
+                fn into_value(self) -> FieldValue {
+                    FieldValue::$variant(self)
+                }
+
+                fn from_value(value: FieldValue) -> Option<Self> {
+                    match value {
+                        FieldValue::$variant(v) => Some(v),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_field_type! {
+    f32 => Float32,
+    i32 => Int32,
+    i16 => Int16,
+    i8 => Int8,
+    u32 => UInt32,
+    u16 => UInt16,
+    u8 => UInt8,
+    bool => Boolean,
+}
+
+/// The values of a telemetry group need more than [`GROUP_VALUES_LEN`] bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GroupTooLarge {
+    pub len: usize,
+}
+
+impl fmt::Display for GroupTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "telemetry group needs {} bytes, at most {GROUP_VALUES_LEN} are available",
+            self.len
+        )
+    }
+}
+
+impl core::error::Error for GroupTooLarge {}
+
+/// Returns the number of bytes the values of a group with the given types take up.
+pub fn group_len(types: &[CanDataType]) -> usize {
+    types.iter().map(|t| t.size()).sum()
+}
+
+impl TelemetryGroupUpdatePayload {
+    /// Packs the values back to back, in the order of the group definition.
+    pub fn pack(group_id: u8, values: &[FieldValue]) -> Result<Self, GroupTooLarge> {
+        let len = values.iter().map(|v| v.data_type().size()).sum();
+        if len > GROUP_VALUES_LEN {
+            return Err(GroupTooLarge { len });
+        }
+        let mut payload = TelemetryGroupUpdatePayload {
+            group_id,
+            values: [0; GROUP_VALUES_LEN],
+        };
+        let mut offset = 0;
+        for value in values {
+            offset += value
+                .encode(&mut payload.values[offset..])
+                .expect("length checked above");
+        }
+        Ok(payload)
+    }
+
+    /// Unpacks the values, given the types of the group's fields in definition order.
+    pub fn unpack(&self, types: &[CanDataType]) -> Result<Vec<FieldValue>, GroupTooLarge> {
+        let len = group_len(types);
+        if len > GROUP_VALUES_LEN {
+            return Err(GroupTooLarge { len });
+        }
+        let values = self.values;
+        let mut offset = 0;
+        Ok(types
+            .iter()
+            .map(|&data_type| {
+                let value =
+                    FieldValue::decode(data_type, &values[offset..]).expect("length checked above");
+                offset += data_type.size();
+                value
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_vectors::data_type_edge_values;
+
+    #[test]
+    fn test_edge_values_round_trip() {
+        for (data_type, name, bytes) in data_type_edge_values() {
+            let value = FieldValue::decode(data_type, &bytes).unwrap();
+            assert_eq!(value.data_type(), data_type, "{data_type:?} {name}");
+            let mut buf = [0u8; 4];
+            let len = value.encode(&mut buf).unwrap();
+            assert_eq!(&buf[..len], bytes, "{data_type:?} {name}");
+        }
+    }
+
+    #[test]
+    fn test_nonzero_booleans_are_true() {
+        assert_eq!(
+            FieldValue::decode(CanDataType::Boolean, &[0x80]),
+            Some(FieldValue::Boolean(true))
+        );
+        assert_eq!(FieldValue::decode(CanDataType::Int16, &[1]), None);
+    }
+
+    #[test]
+    fn test_group_pack_unpack() {
+        let values = [
+            FieldValue::Float32(1.5),
+            FieldValue::Boolean(true),
+            FieldValue::Int16(-2),
+            FieldValue::UInt8(7),
+        ];
+        let payload = TelemetryGroupUpdatePayload::pack(4, &values).unwrap();
+        let packed = payload.values;
+        assert_eq!(&packed[..8], &[0x00, 0x00, 0xC0, 0x3F, 1, 0xFE, 0xFF, 7]);
+        assert!(packed[8..].iter().all(|&b| b == 0));
+
+        let types: Vec<_> = values.iter().map(|v| v.data_type()).collect();
+        assert_eq!(payload.unpack(&types).unwrap(), values);
+    }
+
+    #[test]
+    fn test_group_too_large() {
+        let values = [FieldValue::UInt32(0); 16];
+        assert_eq!(
+            TelemetryGroupUpdatePayload::pack(0, &values),
+            Err(GroupTooLarge { len: 64 })
+        );
+        let payload = TelemetryGroupUpdatePayload::pack(0, &values[..15]).unwrap();
+        assert_eq!(
+            payload.unpack(&[CanDataType::Int32; 16]),
+            Err(GroupTooLarge { len: 64 })
+        );
+    }
+}
@@ -0,0 +1,760 @@
+//! Filter expressions for selecting messages when monitoring or querying logs.
+//!
+//! ```text
+//! sender == 3 && type in (FieldGetRes, ParameterSetConfirmation) && field_id == 12
+//! type == ErrorStatus && text ~ "valve"
+//! ```
+//!
+//! A filter compares the properties of a message with `==`, `!=`, `<`, `<=`, `>`, `>=`, `in`
+//! and `~` (contains, ignoring case), and combines comparisons with `&&`, `||`, `!` and
+//! parentheses, nested at most [`MAX_DEPTH`] levels deep. Values are numbers (decimal, `0x`
+//! hex, or with a fraction), `true`/`false`, quoted strings or bare names like message types.
+//!
+//! | Property | Value |
+//! |---|---|
+//! | `sender`, `receiver` | Node IDs from the CAN ID |
+//! | `priority` | `Low` or `High` |
+//! | `type` | Message type name, e.g. `FieldGetRes` |
+//! | `field_id` | Field or parameter ID in the payload |
+//! | `field_name` | Name in the payload, or as registered by the node owning the field |
+//! | `field_type` | Data type in the payload, or as registered |
+//! | `value` | Value of a `FieldGetRes` or parameter set message, decoded as registered |
+//! | `group_id` | Telemetry group ID |
+//! | `text` | Status message text |
+//! | `device_name`, `tel_count`, `par_count`, `firmware_hash`, `liquid_hash` | Node info |
+//! | `counter` | Heartbeat counter |
+//! | `status` | `ParameterSetStatus` name |
+//! | `lock` | `Locked` or `Unlocked` |
+//!
+//! Comparisons with a property the message doesn't have, e.g. `field_id` of a heartbeat, are
+//! false, also for `!=`.
+
+use crate::field_value::FieldValue;
+use crate::registry::{FieldId, NodeId, NodeRegistry};
+use crate::transport::BusFrame;
+use crate::{CanMessage, CanMessageId, CanMessageType, strings};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterError {
+    /// The expression ends where more was expected.
+    UnexpectedEnd,
+    /// `position` is the byte offset in the expression.
+    Unexpected {
+        position: usize,
+        found: String,
+    },
+    UnterminatedString {
+        position: usize,
+    },
+    UnknownProperty {
+        position: usize,
+        name: String,
+    },
+    UnknownMessageType {
+        position: usize,
+        name: String,
+    },
+    /// `!` and parentheses are nested more than [`MAX_DEPTH`] levels deep.
+    TooDeep {
+        position: usize,
+    },
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterError::UnexpectedEnd => write!(f, "Unexpected end of filter"),
+            FilterError::Unexpected { position, found } => {
+                write!(f, "Unexpected `{found}` at {position}")
+            }
+            FilterError::UnterminatedString { position } => {
+                write!(f, "String starting at {position} isn't terminated")
+            }
+            FilterError::UnknownProperty { position, name } => {
+                write!(f, "Unknown property `{name}` at {position}")
+            }
+            FilterError::UnknownMessageType { position, name } => {
+                write!(f, "Unknown message type `{name}` at {position}")
+            }
+            FilterError::TooDeep { position } => {
+                write!(f, "Filter is nested too deeply at {position}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Property {
+    Sender,
+    Receiver,
+    Priority,
+    Type,
+    FieldId,
+    FieldName,
+    FieldType,
+    Value,
+    GroupId,
+    Text,
+    DeviceName,
+    TelCount,
+    ParCount,
+    FirmwareHash,
+    LiquidHash,
+    Counter,
+    Status,
+    Lock,
+}
+
+impl Property {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "sender" => Property::Sender,
+            "receiver" => Property::Receiver,
+            "priority" => Property::Priority,
+            "type" => Property::Type,
+            "field_id" => Property::FieldId,
+            "field_name" => Property::FieldName,
+            "field_type" => Property::FieldType,
+            "value" => Property::Value,
+            "group_id" => Property::GroupId,
+            "text" => Property::Text,
+            "device_name" => Property::DeviceName,
+            "tel_count" => Property::TelCount,
+            "par_count" => Property::ParCount,
+            "firmware_hash" => Property::FirmwareHash,
+            "liquid_hash" => Property::LiquidHash,
+            "counter" => Property::Counter,
+            "status" => Property::Status,
+            "lock" => Property::Lock,
+            _ => return None,
+        })
+    }
+}
+
+/// A literal in a filter or a property of a message.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Number(f64),
+    Text(String),
+}
+
+impl From<FieldValue> for Value {
+    fn from(value: FieldValue) -> Self {
+        Value::Number(value.as_f64())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+}
+
+impl Op {
+    fn apply(self, left: &Value, right: &Value) -> bool {
+        match (self, left, right) {
+            (Op::Eq, left, right) => left == right,
+            (Op::Ne, left, right) => left != right,
+            (Op::Lt, Value::Number(l), Value::Number(r)) => l < r,
+            (Op::Le, Value::Number(l), Value::Number(r)) => l <= r,
+            (Op::Gt, Value::Number(l), Value::Number(r)) => l > r,
+            (Op::Ge, Value::Number(l), Value::Number(r)) => l >= r,
+            (Op::Contains, Value::Text(l), Value::Text(r)) => {
+                l.to_ascii_lowercase().contains(&r.to_ascii_lowercase())
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    /// Chains are kept flat, so only nesting makes the tree deeper.
+    Or(Vec<Expr>),
+    And(Vec<Expr>),
+    Not(Box<Expr>),
+    Compare(Property, Op, Value),
+    In(Property, Vec<Value>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Number(f64),
+    Text(String),
+    Symbol(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Name(name) => f.write_str(name),
+            Token::Number(number) => write!(f, "{number}"),
+            Token::Text(text) => write!(f, "{text:?}"),
+            Token::Symbol(symbol) => f.write_str(symbol),
+        }
+    }
+}
+
+const SYMBOLS: [&str; 13] = [
+    "&&", "||", "==", "!=", "<=", ">=", "<", ">", "~", "!", "(", ")", ",",
+];
+
+/// Splits an expression into tokens with their byte offsets.
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, FilterError> {
+    let mut tokens = Vec::new();
+    let mut rest = input.trim_start();
+    while !rest.is_empty() {
+        let position = input.len() - rest.len();
+        let unexpected = || FilterError::Unexpected {
+            position,
+            found: rest.chars().next().unwrap().to_string(),
+        };
+        let (token, len) = if let Some(symbol) = SYMBOLS.iter().find(|s| rest.starts_with(**s)) {
+            (Token::Symbol(symbol), symbol.len())
+        } else if let Some(quoted) = rest.strip_prefix('"') {
+            let mut text = String::new();
+            let mut chars = quoted.char_indices();
+            let end = loop {
+                match chars.next() {
+                    Some((i, '"')) => break i,
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, c)) => text.push(c),
+                        None => return Err(FilterError::UnterminatedString { position }),
+                    },
+                    Some((_, c)) => text.push(c),
+                    None => return Err(FilterError::UnterminatedString { position }),
+                }
+            };
+            (Token::Text(text), end + 2)
+        } else {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-'))
+                .unwrap_or(rest.len());
+            let word = &rest[..len];
+            let token = if word.starts_with(|c: char| c.is_ascii_digit() || c == '-') {
+                let number = match word.strip_prefix("0x") {
+                    Some(hex) => u64::from_str_radix(hex, 16).ok().map(|n| n as f64),
+                    None => word.parse().ok(),
+                };
+                Token::Number(number.ok_or_else(|| FilterError::Unexpected {
+                    position,
+                    found: word.to_string(),
+                })?)
+            } else if word.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+                Token::Name(word.to_string())
+            } else {
+                return Err(unexpected());
+            };
+            (token, len)
+        };
+        tokens.push((position, token));
+        rest = rest[len..].trim_start();
+    }
+    Ok(tokens)
+}
+
+/// How deeply `!` and parentheses can be nested, which bounds the recursion of the parser and
+/// of the evaluation.
+pub const MAX_DEPTH: usize = 64;
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(_, token)| token)
+    }
+
+    fn advance(&mut self) -> Result<(usize, Token), FilterError> {
+        let token = self
+            .tokens
+            .get(self.next)
+            .cloned()
+            .ok_or(FilterError::UnexpectedEnd)?;
+        self.next += 1;
+        Ok(token)
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol);
+        if found {
+            self.next += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), FilterError> {
+        let (position, token) = self.advance()?;
+        match token {
+            Token::Symbol(s) if s == symbol => Ok(()),
+            token => Err(FilterError::Unexpected {
+                position,
+                found: token.to_string(),
+            }),
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, FilterError> {
+        let mut exprs = vec![self.and()?];
+        while self.eat("||") {
+            exprs.push(self.and()?);
+        }
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            Expr::Or(exprs)
+        })
+    }
+
+    fn and(&mut self) -> Result<Expr, FilterError> {
+        let mut exprs = vec![self.unary()?];
+        while self.eat("&&") {
+            exprs.push(self.unary()?);
+        }
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            Expr::And(exprs)
+        })
+    }
+
+    fn unary(&mut self) -> Result<Expr, FilterError> {
+        let Some(&(position, Token::Symbol(symbol @ ("!" | "(")))) = self.tokens.get(self.next)
+        else {
+            return self.comparison();
+        };
+        if self.depth == MAX_DEPTH {
+            return Err(FilterError::TooDeep { position });
+        }
+        self.next += 1;
+        self.depth += 1;
+        let expr = if symbol == "!" {
+            Expr::Not(Box::new(self.unary()?))
+        } else {
+            let expr = self.or()?;
+            self.expect(")")?;
+            expr
+        };
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, FilterError> {
+        let (position, token) = self.advance()?;
+        let property = match &token {
+            Token::Name(name) => {
+                Property::from_name(name).ok_or_else(|| FilterError::UnknownProperty {
+                    position,
+                    name: name.clone(),
+                })?
+            }
+            token => {
+                return Err(FilterError::Unexpected {
+                    position,
+                    found: token.to_string(),
+                });
+            }
+        };
+        if matches!(self.peek(), Some(Token::Name(name)) if name == "in") {
+            self.next += 1;
+            self.expect("(")?;
+            let mut values = vec![self.literal(property)?];
+            while self.eat(",") {
+                values.push(self.literal(property)?);
+            }
+            self.expect(")")?;
+            return Ok(Expr::In(property, values));
+        }
+        let (position, token) = self.advance()?;
+        let op = match token {
+            Token::Symbol("==") => Op::Eq,
+            Token::Symbol("!=") => Op::Ne,
+            Token::Symbol("<") => Op::Lt,
+            Token::Symbol("<=") => Op::Le,
+            Token::Symbol(">") => Op::Gt,
+            Token::Symbol(">=") => Op::Ge,
+            Token::Symbol("~") => Op::Contains,
+            token => {
+                return Err(FilterError::Unexpected {
+                    position,
+                    found: token.to_string(),
+                });
+            }
+        };
+        Ok(Expr::Compare(property, op, self.literal(property)?))
+    }
+
+    fn literal(&mut self, property: Property) -> Result<Value, FilterError> {
+        let (position, token) = self.advance()?;
+        let value = match token {
+            Token::Number(number) => Value::Number(number),
+            Token::Name(name) if name == "true" => Value::Number(1.0),
+            Token::Name(name) if name == "false" => Value::Number(0.0),
+            Token::Name(name) | Token::Text(name) => Value::Text(name),
+            token => {
+                return Err(FilterError::Unexpected {
+                    position,
+                    found: token.to_string(),
+                });
+            }
+        };
+        // Catch typos in message types, which would otherwise silently match nothing.
+        if let (Property::Type, Value::Text(name)) = (property, &value)
+            && !CanMessageType::iter().any(|t| t.name() == name)
+        {
+            return Err(FilterError::UnknownMessageType {
+                position,
+                name: name.clone(),
+            });
+        }
+        Ok(value)
+    }
+}
+
+/// The message a filter is evaluated against.
+struct Subject<'a> {
+    id: CanMessageId,
+    msg: &'a CanMessage,
+    registry: &'a NodeRegistry,
+}
+
+impl Subject<'_> {
+    /// The node that registered the field a message refers to: the receiver of requests, the
+    /// sender otherwise.
+    fn field_owner(&self) -> NodeId {
+        if self.msg.metadata().expects_response() {
+            self.id.receiver_id()
+        } else {
+            self.id.sender_id()
+        }
+    }
+
+    fn field_id(&self) -> Option<FieldId> {
+        use CanMessage::*;
+        Some(match self.msg {
+            TelemetryValueRegistration { payload } | ParameterRegistration { payload } => {
+                payload.field_id
+            }
+            FieldGetReq { payload } => payload.field_id,
+            FieldGetRes { payload } => payload.field_id,
+            FieldIDLookupRes { payload } => payload.field_id,
+            ParameterSetReq { payload } => payload.parameter_id,
+            ParameterSetConfirmation { payload } => payload.parameter_id,
+            ParameterSetLockReq { payload } | ParameterSetLockConfirmation { payload } => {
+                payload.parameter_id
+            }
+            _ => return None,
+        })
+    }
+
+    fn get(&self, property: Property) -> Option<Value> {
+        use CanMessage::*;
+        let number = |n: u32| Some(Value::Number(n.into()));
+        let text = |bytes: &[u8]| Some(Value::Text(strings::decode_lossy(bytes)));
+        let name = |name: &str| Some(Value::Text(name.to_string()));
+        let registered = || self.registry.field(self.field_owner(), self.field_id()?);
+        match (property, self.msg) {
+            (Property::Sender, _) => number(self.id.sender_id().into()),
+            (Property::Receiver, _) => number(self.id.receiver_id().into()),
+            (Property::Priority, _) => name(&format!("{:?}", self.id.priority())),
+            (Property::Type, msg) => name(msg.message_type().name()),
+            (Property::FieldId, _) => number(self.field_id()?.into()),
+            (
+                Property::FieldName,
+                TelemetryValueRegistration { payload } | ParameterRegistration { payload },
+            ) => text(&payload.field_name),
+            (Property::FieldName, FieldIDLookupReq { payload }) => text(&payload.field_name),
+            (Property::FieldName, _) => name(registered()?.name()),
+            (
+                Property::FieldType,
+                TelemetryValueRegistration { payload } | ParameterRegistration { payload },
+            ) => name(&format!("{:?}", payload.field_type)),
+            (Property::FieldType, FieldIDLookupRes { payload }) => {
+                name(&format!("{:?}", payload.field_type))
+            }
+            (Property::FieldType, _) => name(&format!("{:?}", registered()?.data_type())),
+            (Property::Value, FieldGetRes { payload }) => {
+                FieldValue::decode(registered()?.data_type(), &payload.value).map(Value::from)
+            }
+            (Property::Value, ParameterSetReq { payload }) => {
+                FieldValue::decode(registered()?.data_type(), &payload.value).map(Value::from)
+            }
+            (Property::Value, ParameterSetConfirmation { payload }) => {
+                FieldValue::decode(registered()?.data_type(), &payload.value).map(Value::from)
+            }
+            (Property::GroupId, TelemetryGroupDefinition { payload }) => {
+                number(payload.group_id.into())
+            }
+            (Property::GroupId, TelemetryGroupUpdate { payload }) => {
+                number(payload.group_id.into())
+            }
+            (
+                Property::Text,
+                InfoStatus { payload } | WarningStatus { payload } | ErrorStatus { payload },
+            ) => text(&payload.msg),
+            (Property::DeviceName, NodeInfoAnnouncement { payload }) => text(&payload.device_name),
+            (Property::TelCount, NodeInfoAnnouncement { payload }) => {
+                number(payload.tel_count.into())
+            }
+            (Property::ParCount, NodeInfoAnnouncement { payload }) => {
+                number(payload.par_count.into())
+            }
+            (Property::FirmwareHash, NodeInfoAnnouncement { payload }) => {
+                number(payload.firmware_hash())
+            }
+            (Property::LiquidHash, NodeInfoAnnouncement { payload }) => {
+                number(payload.liquid_hash())
+            }
+            (Property::Counter, HeartbeatReq { payload } | HeartbeatRes { payload }) => {
+                number(payload.counter())
+            }
+            (Property::Status, ParameterSetConfirmation { payload }) => {
+                name(&format!("{:?}", payload.status))
+            }
+            (
+                Property::Lock,
+                ParameterSetLockReq { payload } | ParameterSetLockConfirmation { payload },
+            ) => name(&format!("{:?}", payload.parameter_lock)),
+            _ => None,
+        }
+    }
+
+    fn eval(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Or(exprs) => exprs.iter().any(|expr| self.eval(expr)),
+            Expr::And(exprs) => exprs.iter().all(|expr| self.eval(expr)),
+            Expr::Not(expr) => !self.eval(expr),
+            Expr::Compare(property, op, value) => self
+                .get(*property)
+                .is_some_and(|actual| op.apply(&actual, value)),
+            Expr::In(property, values) => self
+                .get(*property)
+                .is_some_and(|actual| values.contains(&actual)),
+        }
+    }
+}
+
+/// A parsed filter expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    expr: Expr,
+}
+
+impl Filter {
+    pub fn parse(input: &str) -> Result<Self, FilterError> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            next: 0,
+            depth: 0,
+        };
+        let expr = parser.or()?;
+        if let Some((position, token)) = parser.tokens.get(parser.next) {
+            return Err(FilterError::Unexpected {
+                position: *position,
+                found: token.to_string(),
+            });
+        }
+        Ok(Filter { expr })
+    }
+
+    /// Whether the message matches. Field names, types and values that aren't in the payload are
+    /// looked up in `registry`.
+    pub fn matches(&self, id: CanMessageId, msg: &CanMessage, registry: &NodeRegistry) -> bool {
+        Subject { id, msg, registry }.eval(&self.expr)
+    }
+
+    /// Whether the frame matches. Frames that can't be decoded never match.
+    pub fn matches_frame(&self, frame: &BusFrame, registry: &NodeRegistry) -> bool {
+        frame
+            .decode()
+            .is_ok_and(|msg| self.matches(frame.id, &msg, registry))
+    }
+}
+
+impl FromStr for Filter {
+    type Err = FilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Filter::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payloads::{
+        FieldGetResPayload, HeartbeatPayload, ParameterSetConfirmationPayload, ParameterSetStatus,
+        StatusPayload,
+    };
+    use crate::registry::tests::example_registry;
+
+    fn matches(filter: &str, sender: NodeId, receiver: NodeId, msg: &CanMessage) -> bool {
+        let filter: Filter = filter.parse().unwrap();
+        filter.matches(msg.id(sender, receiver), msg, &example_registry())
+    }
+
+    fn field_get_res(field_id: FieldId, value: &[u8]) -> CanMessage {
+        let mut payload = FieldGetResPayload {
+            field_id,
+            value: [0; 62],
+        };
+        payload.value[..value.len()].copy_from_slice(value);
+        CanMessage::FieldGetRes { payload }
+    }
+
+    fn error_status(text: &str) -> CanMessage {
+        CanMessage::ErrorStatus {
+            payload: StatusPayload {
+                msg: strings::encode_truncated(text),
+            },
+        }
+    }
+
+    #[test]
+    fn test_ids_types_and_fields() {
+        let filter = "sender == 3 && type in (FieldGetRes, ParameterSetConfirmation) \
+                      && field_id in (0x01, 0x81)";
+        let res = field_get_res(0x81, &1.5f32.to_le_bytes());
+        assert!(matches(filter, 3, 0, &res));
+        assert!(!matches(filter, 4, 0, &res));
+        assert!(!matches(filter, 3, 0, &field_get_res(0x82, &[1])));
+
+        let confirmation = CanMessage::ParameterSetConfirmation {
+            payload: ParameterSetConfirmationPayload {
+                parameter_id: 0x01,
+                status: ParameterSetStatus::Success,
+                value: [0; 61],
+            },
+        };
+        assert!(matches(filter, 3, 0, &confirmation));
+        assert!(matches("status == Success", 3, 0, &confirmation));
+        assert!(matches("priority == High", 3, 0, &confirmation));
+    }
+
+    #[test]
+    fn test_registry_lookups() {
+        let res = field_get_res(0x81, &1.5f32.to_le_bytes());
+        assert!(matches("field_name == tank_pressure", 3, 0, &res));
+        assert!(matches("field_type == Float32 && value > 1", 3, 0, &res));
+        assert!(!matches("value >= 2", 3, 0, &res));
+        // Requests refer to the fields of the receiver.
+        let req = CanMessage::FieldGetReq {
+            payload: crate::payloads::FieldGetReqPayload { field_id: 0x82 },
+        };
+        assert!(matches("field_name == \"vent_open\"", 0, 3, &req));
+        assert!(!matches("field_name == \"vent_open\"", 3, 0, &req));
+        assert!(matches("value == true", 3, 0, &field_get_res(0x82, &[1])));
+    }
+
+    #[test]
+    fn test_text_and_missing_properties() {
+        let status = error_status("Valve 2 stuck");
+        assert!(matches(
+            "type == ErrorStatus && text ~ \"valve\"",
+            3,
+            0,
+            &status
+        ));
+        assert!(!matches("text ~ \"pump\"", 3, 0, &status));
+
+        let heartbeat = CanMessage::HeartbeatRes {
+            payload: HeartbeatPayload::new(7),
+        };
+        assert!(matches("counter >= 7", 3, 0, &heartbeat));
+        assert!(!matches("field_id == 1", 3, 0, &heartbeat));
+        assert!(!matches("field_id != 1", 3, 0, &heartbeat));
+        assert!(matches("!(field_id == 1)", 3, 0, &heartbeat));
+        assert!(matches(
+            "type == InfoStatus || !(sender != 3 || receiver == 1)",
+            3,
+            0,
+            &heartbeat
+        ));
+    }
+
+    #[test]
+    fn test_matches_frame() {
+        let filter = Filter::parse("text ~ \"VALVE\"").unwrap();
+        let msg = error_status("valve");
+        let frame = BusFrame::encode(msg.id(3, 0), msg);
+        assert!(filter.matches_frame(&frame, &NodeRegistry::new()));
+        let invalid = BusFrame::new(CanMessageId::new(), &[2]).unwrap();
+        assert!(!filter.matches_frame(&invalid, &NodeRegistry::new()));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(Filter::parse("sender =="), Err(FilterError::UnexpectedEnd));
+        assert_eq!(
+            Filter::parse("type == FieldGetResponse"),
+            Err(FilterError::UnknownMessageType {
+                position: 8,
+                name: "FieldGetResponse".to_string()
+            })
+        );
+        assert_eq!(
+            Filter::parse("sendr == 3"),
+            Err(FilterError::UnknownProperty {
+                position: 0,
+                name: "sendr".to_string()
+            })
+        );
+        assert_eq!(
+            Filter::parse("text ~ \"valve"),
+            Err(FilterError::UnterminatedString { position: 7 })
+        );
+        assert_eq!(
+            Filter::parse("sender == 3 receiver"),
+            Err(FilterError::Unexpected {
+                position: 12,
+                found: "receiver".to_string()
+            })
+        );
+        assert_eq!(
+            Filter::parse("(sender == 3"),
+            Err(FilterError::UnexpectedEnd)
+        );
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let nested = |depth| {
+            format!(
+                "{}sender == 3{}",
+                "!(".repeat(depth / 2),
+                ")".repeat(depth / 2)
+            )
+        };
+        assert!(Filter::parse(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(
+            Filter::parse(&nested(MAX_DEPTH + 2)),
+            Err(FilterError::TooDeep {
+                position: MAX_DEPTH
+            })
+        );
+        assert!(matches!(
+            Filter::parse(&"(".repeat(100_000)),
+            Err(FilterError::TooDeep { .. })
+        ));
+
+        // Long chains don't nest.
+        let chain = vec!["sender == 3"; 100_000].join(" && ");
+        let heartbeat = CanMessage::HeartbeatRes {
+            payload: HeartbeatPayload::new(7),
+        };
+        assert!(matches(&chain, 3, 0, &heartbeat));
+    }
+}
@@ -0,0 +1,667 @@
+//! The `.lcanlog` container for recordings of a bus.
+//!
+//! Telemetry values can only be decoded with the registrations of their nodes, which are usually
+//! sent long before a recording starts. A log therefore carries snapshots of the
+//! [`NodeRegistry`] next to the frames, so every part of it can be decoded on its own:
+//!
+//! ```text
+//! header:  "LCANLOG\0" | version: u16 | started_at: u64 | interface: str | session: str
+//! records: tag: u8 | len: u32 | body
+//!   frame:    timestamp: u64 | id: u16 | data (up to 64 bytes)
+//!   snapshot: timestamp: u64 | count: u16 | count * (node: u8 | announcement frame: 64 bytes)
+//!   index:    n * (timestamp: u64 | offset: u64), one entry per snapshot
+//! trailer: index offset: u64 | "LCANIDX\0"
+//! ```
+//!
+//! All integers are little-endian, strings are prefixed with their length as `u16`, `started_at`
+//! is in microseconds since the Unix epoch and timestamps in microseconds since `started_at`.
+//! Readers skip records with unknown tags. A log without trailer, e.g. because the recording was
+//! interrupted, can still be read, but not seeked.
+
+use crate::field_store::{self, IngestError};
+use crate::field_value::FieldValue;
+use crate::filter::Filter;
+use crate::registry::{FieldId, NodeId, NodeRegistry};
+use crate::transport::BusFrame;
+use crate::{CanMessage, CanMessageFrame, CanMessageId, DecodeError};
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zerocopy::IntoBytes;
+
+/// The file extension of logs, without the dot.
+pub const EXTENSION: &str = "lcanlog";
+
+/// Bumped whenever the layout changes incompatibly.
+pub const FORMAT_VERSION: u16 = 1;
+
+const MAGIC: &[u8; 8] = b"LCANLOG\0";
+const TRAILER_MAGIC: &[u8; 8] = b"LCANIDX\0";
+const TRAILER_LEN: u64 = 16;
+
+const TAG_FRAME: u8 = 1;
+const TAG_SNAPSHOT: u8 = 2;
+const TAG_INDEX: u8 = 3;
+
+const FRAME_LEN: usize = size_of::<CanMessageFrame>();
+
+#[derive(Debug)]
+pub enum LogError {
+    Io(io::Error),
+    /// The file doesn't start with the `.lcanlog` magic.
+    NotALog,
+    UnsupportedVersion(u16),
+    /// A record is cut off or inconsistent.
+    Corrupt(&'static str),
+    Decode(DecodeError),
+    Values(IngestError),
+}
+
+impl fmt::Display for LogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogError::Io(e) => write!(f, "I/O error: {e}"),
+            LogError::NotALog => write!(f, "Not a LiquidCAN log"),
+            LogError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported log format version {version}")
+            }
+            LogError::Corrupt(reason) => write!(f, "Corrupt log: {reason}"),
+            LogError::Decode(e) => write!(f, "{e}"),
+            LogError::Values(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for LogError {}
+
+impl From<io::Error> for LogError {
+    fn from(e: io::Error) -> Self {
+        LogError::Io(e)
+    }
+}
+
+/// Describes the recording.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogHeader {
+    /// The CAN interface the frames were received on, e.g. `can0`.
+    pub interface: String,
+    /// Free-form description of the session, e.g. the test that was run.
+    pub session: String,
+    pub started_at: SystemTime,
+}
+
+/// A frame and when it was received, relative to [`LogHeader::started_at`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFrame {
+    pub timestamp: Duration,
+    pub frame: BusFrame,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogRecord {
+    Frame(LogFrame),
+    Snapshot {
+        timestamp: Duration,
+        registry: NodeRegistry,
+    },
+}
+
+/// A decoded field value together with the name its node registered for it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedValue {
+    pub node: NodeId,
+    pub field_id: FieldId,
+    pub name: String,
+    pub value: FieldValue,
+}
+
+fn micros(duration: Duration) -> u64 {
+    duration.as_micros().try_into().unwrap_or(u64::MAX)
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) -> io::Result<()> {
+    let len = u16::try_from(s.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "string too long for a log"))?;
+    out.extend_from_slice(&len.to_le_bytes());
+    out.extend_from_slice(s.as_bytes());
+    Ok(())
+}
+
+/// Writes a log. Call [`finish`](Self::finish) at the end, otherwise the index is missing.
+pub struct LogWriter<W: Write> {
+    writer: W,
+    /// Number of bytes written so far.
+    offset: u64,
+    registry: NodeRegistry,
+    snapshot_interval: Duration,
+    last_snapshot: Option<Duration>,
+    index: Vec<(Duration, u64)>,
+}
+
+impl<W: Write> LogWriter<W> {
+    /// Writes the header. Snapshots are taken every 10 seconds of log time by default.
+    pub fn new(mut writer: W, header: &LogHeader) -> io::Result<Self> {
+        let started_at = header
+            .started_at
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "start before 1970"))?;
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        out.extend_from_slice(&micros(started_at).to_le_bytes());
+        write_str(&mut out, &header.interface)?;
+        write_str(&mut out, &header.session)?;
+        writer.write_all(&out)?;
+        Ok(LogWriter {
+            writer,
+            offset: out.len() as u64,
+            registry: NodeRegistry::new(),
+            snapshot_interval: Duration::from_secs(10),
+            last_snapshot: None,
+            index: Vec::new(),
+        })
+    }
+
+    /// Starts with what the server already knows, so values of nodes that registered before
+    /// the recording can be decoded.
+    pub fn with_registry(mut self, registry: NodeRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    pub fn snapshot_interval(mut self, interval: Duration) -> Self {
+        self.snapshot_interval = interval;
+        self
+    }
+
+    /// The registry as of the last written frame.
+    pub fn registry(&self) -> &NodeRegistry {
+        &self.registry
+    }
+
+    fn write_record(&mut self, tag: u8, body: &[u8]) -> io::Result<()> {
+        let len = u32::try_from(body.len()).expect("records are small");
+        self.writer.write_all(&[tag])?;
+        self.writer.write_all(&len.to_le_bytes())?;
+        self.writer.write_all(body)?;
+        self.offset += 5 + u64::from(len);
+        Ok(())
+    }
+
+    /// Writes a received frame, preceded by a snapshot if one is due. Announcements in the
+    /// frame are added to the registry of the following snapshots.
+    pub fn write_frame(&mut self, timestamp: Duration, frame: &BusFrame) -> io::Result<()> {
+        let due = self
+            .last_snapshot
+            .is_none_or(|last| timestamp >= last + self.snapshot_interval);
+        if due {
+            self.write_snapshot(timestamp)?;
+        }
+        if let Ok(msg) = frame.decode() {
+            // Invalid announcements are recorded as frames, but can't be part of a snapshot.
+            let _ = self.registry.observe(frame.id.sender_id(), &msg);
+        }
+        let mut body = Vec::with_capacity(10 + frame.data().len());
+        body.extend_from_slice(&micros(timestamp).to_le_bytes());
+        body.extend_from_slice(&u16::from(frame.id).to_le_bytes());
+        body.extend_from_slice(frame.data());
+        self.write_record(TAG_FRAME, &body)
+    }
+
+    pub fn write_message(
+        &mut self,
+        timestamp: Duration,
+        id: CanMessageId,
+        msg: CanMessage,
+    ) -> io::Result<()> {
+        self.write_frame(timestamp, &BusFrame::encode(id, msg))
+    }
+
+    /// Writes a snapshot of the current registry now, independent of the interval.
+    pub fn write_snapshot(&mut self, timestamp: Duration) -> io::Result<()> {
+        let announcements = self.registry.announcements();
+        let mut body = Vec::with_capacity(10 + announcements.len() * (1 + FRAME_LEN));
+        body.extend_from_slice(&micros(timestamp).to_le_bytes());
+        let count = u16::try_from(announcements.len()).expect("at most 32 nodes of 255 fields");
+        body.extend_from_slice(&count.to_le_bytes());
+        for (node, msg) in announcements {
+            body.push(node);
+            body.extend_from_slice(CanMessageFrame::from(msg).as_bytes());
+        }
+        self.index.push((timestamp, self.offset));
+        self.last_snapshot = Some(timestamp);
+        self.write_record(TAG_SNAPSHOT, &body)
+    }
+
+    /// Writes the index and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let index_offset = self.offset;
+        let mut body = Vec::with_capacity(self.index.len() * 16);
+        for &(timestamp, offset) in &self.index {
+            body.extend_from_slice(&micros(timestamp).to_le_bytes());
+            body.extend_from_slice(&offset.to_le_bytes());
+        }
+        self.write_record(TAG_INDEX, &body)?;
+        self.writer.write_all(&index_offset.to_le_bytes())?;
+        self.writer.write_all(TRAILER_MAGIC)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+fn read_array<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut buf = [0; N];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn read_str(reader: &mut impl Read) -> Result<String, LogError> {
+    let len = u16::from_le_bytes(read_array(reader)?);
+    let mut buf = vec![0; len.into()];
+    reader.read_exact(&mut buf)?;
+    String::from_utf8(buf).map_err(|_| LogError::Corrupt("string isn't UTF-8"))
+}
+
+fn timestamp(body: &[u8]) -> Result<Duration, LogError> {
+    let bytes = body.get(..8).ok_or(LogError::Corrupt("record too short"))?;
+    Ok(Duration::from_micros(u64::from_le_bytes(
+        bytes.try_into().unwrap(),
+    )))
+}
+
+/// Reads a log and keeps track of the registry at the current position.
+pub struct LogReader<R: Read + Seek> {
+    reader: R,
+    header: LogHeader,
+    /// Offset of the first record.
+    data_start: u64,
+    /// Offset of the index record, or the end of the file if there is none.
+    data_end: u64,
+    /// Timestamps and offsets of the snapshots, empty if the log has no index.
+    index: Vec<(Duration, u64)>,
+    registry: NodeRegistry,
+    /// A frame read ahead by [`seek`](Self::seek).
+    pending: Option<LogFrame>,
+}
+
+impl<R: Read + Seek> LogReader<R> {
+    pub fn open(mut reader: R) -> Result<Self, LogError> {
+        reader.seek(SeekFrom::Start(0))?;
+        if read_array::<8>(&mut reader).ok().as_ref() != Some(MAGIC) {
+            return Err(LogError::NotALog);
+        }
+        let version = u16::from_le_bytes(read_array(&mut reader)?);
+        if version != FORMAT_VERSION {
+            return Err(LogError::UnsupportedVersion(version));
+        }
+        let started_at =
+            UNIX_EPOCH + Duration::from_micros(u64::from_le_bytes(read_array(&mut reader)?));
+        let header = LogHeader {
+            interface: read_str(&mut reader)?,
+            session: read_str(&mut reader)?,
+            started_at,
+        };
+        let data_start = reader.stream_position()?;
+
+        let len = reader.seek(SeekFrom::End(0))?;
+        let mut log = LogReader {
+            reader,
+            header,
+            data_start,
+            data_end: len,
+            index: Vec::new(),
+            registry: NodeRegistry::new(),
+            pending: None,
+        };
+        if len >= data_start + TRAILER_LEN {
+            log.reader.seek(SeekFrom::End(-(TRAILER_LEN as i64)))?;
+            let index_offset = u64::from_le_bytes(read_array(&mut log.reader)?);
+            if &read_array::<8>(&mut log.reader)? == TRAILER_MAGIC {
+                log.data_end = len - TRAILER_LEN;
+                // The frames are still readable without the index, only seeking gets slower.
+                if let Ok(index) = log.read_index(index_offset) {
+                    log.index = index;
+                    log.data_end = index_offset;
+                }
+            }
+        }
+        log.reader.seek(SeekFrom::Start(data_start))?;
+        Ok(log)
+    }
+
+    /// Reads the index record at `offset`, which ends the data records.
+    fn read_index(&mut self, offset: u64) -> Result<Vec<(Duration, u64)>, LogError> {
+        if !(self.data_start..=self.data_end).contains(&offset) {
+            return Err(LogError::Corrupt("trailer points outside of the log"));
+        }
+        self.reader.seek(SeekFrom::Start(offset))?;
+        let Some((TAG_INDEX, body)) = self.read_raw_record()? else {
+            return Err(LogError::Corrupt("trailer doesn't point to the index"));
+        };
+        if body.len() % 16 != 0 {
+            return Err(LogError::Corrupt("index has the wrong length"));
+        }
+        let index: Vec<_> = body
+            .chunks_exact(16)
+            .map(|entry| {
+                let (timestamp, offset) = entry.split_at(8);
+                (
+                    Duration::from_micros(u64::from_le_bytes(timestamp.try_into().unwrap())),
+                    u64::from_le_bytes(offset.try_into().unwrap()),
+                )
+            })
+            .collect();
+        if index
+            .iter()
+            .any(|&(_, snapshot)| !(self.data_start..offset).contains(&snapshot))
+        {
+            return Err(LogError::Corrupt("index points outside of the data"));
+        }
+        Ok(index)
+    }
+
+    pub fn header(&self) -> &LogHeader {
+        &self.header
+    }
+
+    /// The registry as of the last frame or snapshot that was read.
+    pub fn registry(&self) -> &NodeRegistry {
+        &self.registry
+    }
+
+    /// Timestamps of the snapshots in the index, in the order they were written.
+    pub fn snapshot_times(&self) -> impl Iterator<Item = Duration> + '_ {
+        self.index.iter().map(|&(timestamp, _)| timestamp)
+    }
+
+    /// Returns `None` at the end of the records, or at a cut-off record at the end of a log
+    /// without index.
+    fn read_raw_record(&mut self) -> Result<Option<(u8, Vec<u8>)>, LogError> {
+        let mut head = [0u8; 5];
+        match self.reader.read_exact(&mut head) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+        let len = u32::from_le_bytes(head[1..].try_into().unwrap());
+        let mut body = Vec::new();
+        let read = (&mut self.reader).take(len.into()).read_to_end(&mut body)?;
+        if read < len as usize {
+            return Ok(None);
+        }
+        Ok(Some((head[0], body)))
+    }
+
+    /// Reads the next frame or snapshot and applies it to the registry.
+    pub fn next_record(&mut self) -> Result<Option<LogRecord>, LogError> {
+        if let Some(frame) = self.pending.take() {
+            return Ok(Some(LogRecord::Frame(frame)));
+        }
+        loop {
+            if self.reader.stream_position()? >= self.data_end {
+                return Ok(None);
+            }
+            let Some((tag, body)) = self.read_raw_record()? else {
+                return Ok(None);
+            };
+            match tag {
+                TAG_FRAME => {
+                    let timestamp = timestamp(&body)?;
+                    let id = body
+                        .get(8..10)
+                        .ok_or(LogError::Corrupt("frame too short"))?;
+                    let id = CanMessageId::from(u16::from_le_bytes(id.try_into().unwrap()));
+                    let frame = BusFrame::new(id, &body[10..])
+                        .ok_or(LogError::Corrupt("frame too long"))?;
+                    if let Ok(msg) = frame.decode() {
+                        let _ = self.registry.observe(id.sender_id(), &msg);
+                    }
+                    return Ok(Some(LogRecord::Frame(LogFrame { timestamp, frame })));
+                }
+                TAG_SNAPSHOT => {
+                    let timestamp = timestamp(&body)?;
+                    let registry = parse_snapshot(&body[8..])?;
+                    self.registry = registry.clone();
+                    return Ok(Some(LogRecord::Snapshot {
+                        timestamp,
+                        registry,
+                    }));
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    /// Reads the next frame, applying snapshots on the way.
+    pub fn next_frame(&mut self) -> Result<Option<LogFrame>, LogError> {
+        while let Some(record) = self.next_record()? {
+            if let LogRecord::Frame(frame) = record {
+                return Ok(Some(frame));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Reads the next frame that matches `filter`, with field names resolved by the registry at
+    /// its position.
+    pub fn next_matching(&mut self, filter: &Filter) -> Result<Option<LogFrame>, LogError> {
+        while let Some(frame) = self.next_frame()? {
+            if filter.matches_frame(&frame.frame, &self.registry) {
+                return Ok(Some(frame));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Continues reading at the first frame at or after `timestamp`, starting from the last
+    /// snapshot before it. Without an index, the log is read from the start.
+    pub fn seek(&mut self, timestamp: Duration) -> Result<(), LogError> {
+        let start = self
+            .index
+            .iter()
+            .rev()
+            .find(|&&(snapshot, _)| snapshot <= timestamp)
+            .map_or(self.data_start, |&(_, offset)| offset);
+        self.reader.seek(SeekFrom::Start(start))?;
+        self.registry = NodeRegistry::new();
+        self.pending = None;
+        while let Some(frame) = self.next_frame()? {
+            if frame.timestamp >= timestamp {
+                self.pending = Some(frame);
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Decodes the field values in a frame with the registry at the current position, so call
+    /// it right after reading the frame.
+    pub fn decode_values(&self, frame: &LogFrame) -> Result<Vec<NamedValue>, LogError> {
+        let msg = frame.frame.decode().map_err(LogError::Decode)?;
+        let node = frame.frame.id.sender_id();
+        let values =
+            field_store::field_values(&self.registry, node, &msg).map_err(LogError::Values)?;
+        Ok(values
+            .into_iter()
+            .map(|(field_id, value)| NamedValue {
+                node,
+                field_id,
+                name: self
+                    .registry
+                    .field(node, field_id)
+                    .expect("decoded with this registry")
+                    .name()
+                    .to_string(),
+                value,
+            })
+            .collect())
+    }
+}
+
+fn parse_snapshot(body: &[u8]) -> Result<NodeRegistry, LogError> {
+    let (count, entries) = body
+        .split_first_chunk::<2>()
+        .ok_or(LogError::Corrupt("snapshot too short"))?;
+    let count = usize::from(u16::from_le_bytes(*count));
+    if entries.len() != count * (1 + FRAME_LEN) {
+        return Err(LogError::Corrupt("snapshot has the wrong length"));
+    }
+    let mut registry = NodeRegistry::new();
+    for entry in entries.chunks_exact(1 + FRAME_LEN) {
+        let msg = CanMessage::decode(&entry[1..]).map_err(LogError::Decode)?;
+        registry
+            .observe(entry[0], &msg)
+            .map_err(|_| LogError::Corrupt("invalid announcement in snapshot"))?;
+    }
+    Ok(registry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payloads::TelemetryGroupUpdatePayload;
+    use crate::registry::tests::example_registry;
+    use std::io::Cursor;
+
+    fn header() -> LogHeader {
+        LogHeader {
+            interface: "can0".to_string(),
+            session: "cold flow".to_string(),
+            started_at: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+        }
+    }
+
+    fn group_update(pressure: f32) -> CanMessage {
+        CanMessage::TelemetryGroupUpdate {
+            payload: TelemetryGroupUpdatePayload::pack(
+                1,
+                &[FieldValue::Float32(pressure), FieldValue::Boolean(true)],
+            )
+            .unwrap(),
+        }
+    }
+
+    /// One group update from node 3 per second, with a snapshot every 10 seconds.
+    fn example_log() -> Vec<u8> {
+        let mut writer = LogWriter::new(Vec::new(), &header())
+            .unwrap()
+            .with_registry(example_registry());
+        let id = CanMessageId::new().with_sender_id(3);
+        for second in 0..25 {
+            let timestamp = Duration::from_secs(second);
+            writer
+                .write_message(timestamp, id, group_update(second as f32))
+                .unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut reader = LogReader::open(Cursor::new(example_log())).unwrap();
+        assert_eq!(reader.header(), &header());
+        assert_eq!(
+            reader.snapshot_times().collect::<Vec<_>>(),
+            [0, 10, 20].map(Duration::from_secs)
+        );
+
+        let Some(LogRecord::Snapshot { registry, .. }) = reader.next_record().unwrap() else {
+            panic!("log doesn't start with a snapshot");
+        };
+        assert_eq!(registry, example_registry());
+        let mut count = 0;
+        while let Some(frame) = reader.next_frame().unwrap() {
+            assert_eq!(frame.timestamp, Duration::from_secs(count));
+            let values = reader.decode_values(&frame).unwrap();
+            assert_eq!(values.len(), 2);
+            assert_eq!(values[0].name, "tank_pressure");
+            assert_eq!(values[0].value, FieldValue::Float32(count as f32));
+            assert_eq!(values[1].name, "vent_open");
+            count += 1;
+        }
+        assert_eq!(count, 25);
+    }
+
+    #[test]
+    fn test_seek_decodes_with_snapshot() {
+        let mut reader = LogReader::open(Cursor::new(example_log())).unwrap();
+        reader.seek(Duration::from_millis(13_500)).unwrap();
+        let frame = reader.next_frame().unwrap().unwrap();
+        assert_eq!(frame.timestamp, Duration::from_secs(14));
+        let values = reader.decode_values(&frame).unwrap();
+        assert_eq!(values[0].value, FieldValue::Float32(14.0));
+    }
+
+    #[test]
+    fn test_next_matching() {
+        let mut reader = LogReader::open(Cursor::new(example_log())).unwrap();
+        reader.seek(Duration::from_secs(20)).unwrap();
+        let filter = Filter::parse("sender == 3 && group_id == 1").unwrap();
+        let mut count = 0;
+        while reader.next_matching(&filter).unwrap().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 5);
+
+        reader.seek(Duration::ZERO).unwrap();
+        let filter = Filter::parse("sender == 4").unwrap();
+        assert_eq!(reader.next_matching(&filter).unwrap(), None);
+    }
+
+    #[test]
+    fn test_log_without_index() {
+        let mut log = example_log();
+        // Cut off the trailer, the index with three entries and half of the last frame.
+        let len = log.len() - 16 - (5 + 3 * 16) - 10;
+        log.truncate(len);
+        let mut reader = LogReader::open(Cursor::new(log)).unwrap();
+        assert_eq!(reader.snapshot_times().count(), 0);
+        let mut count = 0;
+        while reader.next_frame().unwrap().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 24);
+
+        reader.seek(Duration::from_secs(22)).unwrap();
+        let frame = reader.next_frame().unwrap().unwrap();
+        assert!(reader.decode_values(&frame).is_ok());
+    }
+
+    #[test]
+    fn test_ignores_corrupt_index() {
+        let log = example_log();
+        let index_offset = log.len() - 16 - (5 + 3 * 16);
+        let mut short_index = log.clone();
+        // An index whose length isn't a multiple of an entry.
+        short_index[index_offset + 1] = 3 * 16 - 1;
+        let mut bad_offset = log.clone();
+        let trailer = bad_offset.len() - 16;
+        bad_offset[trailer..trailer + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        for log in [short_index, bad_offset] {
+            let mut reader = LogReader::open(Cursor::new(log)).unwrap();
+            assert_eq!(reader.snapshot_times().count(), 0);
+            let mut count = 0;
+            while reader.next_frame().unwrap().is_some() {
+                count += 1;
+            }
+            assert_eq!(count, 25);
+            reader.seek(Duration::from_secs(22)).unwrap();
+            let frame = reader.next_frame().unwrap().unwrap();
+            assert_eq!(frame.timestamp, Duration::from_secs(22));
+        }
+    }
+
+    #[test]
+    fn test_rejects_other_files() {
+        assert!(matches!(
+            LogReader::open(Cursor::new(b"candump".to_vec())),
+            Err(LogError::NotALog)
+        ));
+        let mut log = example_log();
+        log[8] = 2;
+        assert!(matches!(
+            LogReader::open(Cursor::new(log)),
+            Err(LogError::UnsupportedVersion(2))
+        ));
+    }
+}
@@ -1,8 +1,71 @@
+//! The messages, their wire format and the [`Transport`](transport::Transport) trait only need
+//! `core`, so they run in `no_std` firmware, e.g. with the `embedded-can` adapter. Builders,
+//! field values and the node registry need the `alloc` feature, everything that runs on a host,
+//! like logs, exports and the host transports, the default `std` feature.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+// Lets derive macros refer to this crate as `::liquidcan_rust` from inside it as well.
+extern crate self as liquidcan_rust;
+
+#[cfg(feature = "alloc")]
+pub mod builders;
+pub mod c_header;
 pub mod can_message;
+#[cfg(feature = "std")]
+pub mod candump;
+#[cfg(feature = "std")]
+pub mod export;
+#[cfg(feature = "std")]
+pub mod field_store;
+#[cfg(feature = "alloc")]
+pub mod field_value;
+#[cfg(feature = "std")]
+pub mod filter;
+#[cfg(feature = "std")]
+pub mod lcanlog;
+#[cfg(feature = "alloc")]
+pub mod liquid_fields;
+#[cfg(feature = "std")]
+pub mod log_analyzer;
 pub mod message_conversion;
+#[cfg(feature = "alloc")]
+pub mod message_ref;
 pub mod payloads;
 pub mod raw_can_message;
+#[cfg(feature = "alloc")]
+pub mod registry;
+#[cfg(feature = "std")]
+pub mod replay;
+#[cfg(feature = "std")]
+pub mod resolver;
+#[cfg(feature = "std")]
+pub mod resync;
+#[cfg(test)]
+mod spec_consistency;
+#[cfg(feature = "std")]
+pub mod status;
+pub mod strings;
+#[cfg(feature = "alloc")]
+pub mod telemetry_group;
+#[cfg(any(test, feature = "test-vectors"))]
+pub mod test_vectors;
+pub mod transport;
 
 pub use can_message::CanMessage;
+pub use can_message::CanMessageType;
+pub use message_conversion::DecodeError;
+#[cfg(feature = "alloc")]
+pub use message_ref::CanMessageRef;
 pub use raw_can_message::CanMessageFrame;
 pub use raw_can_message::CanMessageId;
+
+/// Used by the derive macros, which can't name `alloc` in crates that don't declare it.
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::vec;
+    pub use alloc::vec::Vec;
+}
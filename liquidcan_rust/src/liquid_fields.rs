@@ -0,0 +1,254 @@
+//! Node definitions from plain Rust structs.
+//!
+//! `#[derive(LiquidFields)]` turns the annotated fields of a struct into the fields of a node:
+//!
+//! ```
+//! use liquidcan_rust::liquid_fields::LiquidFields;
+//!
+//! #[derive(LiquidFields, Default)]
+//! struct Ecu {
+//!     #[telemetry(group = 1)]
+//!     tank_pressure: f32,
+//!     #[telemetry(group = 1, name = "vent")]
+//!     vent_open: bool,
+//!     #[parameter]
+//!     fill_target: u16,
+//!     // Fields without an attribute aren't visible on the bus.
+//!     fills: usize,
+//! }
+//!
+//! assert_eq!(Ecu::field_id("tank_pressure"), Some(0x81));
+//! assert_eq!(Ecu::field_id("vent"), Some(0x82));
+//! assert_eq!(Ecu::field_id("fill_target"), Some(0x01));
+//! let update = Ecu::default().pack_group(1).unwrap();
+//! ```
+//!
+//! Field IDs are assigned in declaration order, starting at `0x81` for telemetry values and at
+//! `0x01` for parameters, so they stay the same between reboots as the spec requires. Fields can
+//! have any type implementing [`FieldType`], and the derive checks at compile time that the
+//! values of every group fit into a `TelemetryGroupUpdate`.
+
+use crate::builders::{FieldKind, FieldRegistration, NodeInfoBuilder};
+use crate::field_value::{FieldType, FieldValue};
+use crate::payloads::{CanDataType, TelemetryGroupDefinitionPayload, TelemetryGroupUpdatePayload};
+use alloc::vec::Vec;
+use core::fmt;
+
+pub use liquidcan_rust_macros_derive::LiquidFields;
+
+/// A field of a node, as generated by `#[derive(LiquidFields)]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldDef {
+    pub id: u8,
+    pub kind: FieldKind,
+    pub data_type: CanDataType,
+    pub name: &'static str,
+    /// The telemetry group the field's value is sent in, if any.
+    pub group: Option<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetFieldError {
+    UnknownField(u8),
+    WrongType { field_id: u8, expected: CanDataType },
+}
+
+impl fmt::Display for SetFieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetFieldError::UnknownField(field_id) => {
+                write!(f, "No field with ID {field_id:#04x}")
+            }
+            SetFieldError::WrongType { field_id, expected } => {
+                write!(f, "Field {field_id:#04x} has type {expected:?}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for SetFieldError {}
+
+/// The fields of a node. Implement it with `#[derive(LiquidFields)]`.
+pub trait LiquidFields {
+    /// All fields, in declaration order.
+    const FIELDS: &'static [FieldDef];
+
+    fn get_field(&self, field_id: u8) -> Option<FieldValue>;
+
+    /// Sets a field to `value`, which must have the field's type.
+    fn set_field(&mut self, field_id: u8, value: FieldValue) -> Result<(), SetFieldError>;
+
+    fn field_def(field_id: u8) -> Option<&'static FieldDef> {
+        Self::FIELDS.iter().find(|field| field.id == field_id)
+    }
+
+    fn field_id(name: &str) -> Option<u8> {
+        Self::FIELDS
+            .iter()
+            .find(|field| field.name == name)
+            .map(|field| field.id)
+    }
+
+    /// Returns a builder for the node info with the numbers of fields already set.
+    fn node_info() -> NodeInfoBuilder {
+        let count = |kind| Self::FIELDS.iter().filter(|f| f.kind == kind).count() as u8;
+        NodeInfoBuilder::default()
+            .tel_count(count(FieldKind::Telemetry))
+            .par_count(count(FieldKind::Parameter))
+    }
+
+    /// Returns the registrations to send after the node info, one per field.
+    fn registrations() -> Vec<FieldRegistration> {
+        Self::FIELDS
+            .iter()
+            .map(|field| {
+                let registration = match field.kind {
+                    FieldKind::Telemetry => FieldRegistration::telemetry,
+                    FieldKind::Parameter => FieldRegistration::parameter,
+                };
+                registration(field.id, field.data_type, field.name).expect("checked by the derive")
+            })
+            .collect()
+    }
+
+    /// Returns the IDs of all telemetry groups, in ascending order.
+    fn group_ids() -> Vec<u8> {
+        let mut ids: Vec<_> = Self::FIELDS.iter().filter_map(|f| f.group).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    fn group_definitions() -> Vec<TelemetryGroupDefinitionPayload> {
+        Self::group_ids()
+            .into_iter()
+            .map(|group_id| {
+                let field_ids: Vec<_> =
+                    group_fields(Self::FIELDS, group_id).map(|f| f.id).collect();
+                TelemetryGroupDefinitionPayload::new(group_id, &field_ids)
+                    .expect("checked by the derive")
+            })
+            .collect()
+    }
+
+    /// Packs the current values of a group, or returns `None` if there is no such group.
+    fn pack_group(&self, group_id: u8) -> Option<TelemetryGroupUpdatePayload> {
+        let values: Vec<_> = group_fields(Self::FIELDS, group_id)
+            .map(|f| self.get_field(f.id).expect("field of this struct"))
+            .collect();
+        if values.is_empty() {
+            return None;
+        }
+        Some(TelemetryGroupUpdatePayload::pack(group_id, &values).expect("checked by the derive"))
+    }
+}
+
+fn group_fields(fields: &[FieldDef], group_id: u8) -> impl Iterator<Item = &FieldDef> {
+    fields.iter().filter(move |f| f.group == Some(group_id))
+}
+
+/// Used by `#[derive(LiquidFields)]` to convert field values.
+#[doc(hidden)]
+pub fn set<T: FieldType>(
+    field: &mut T,
+    field_id: u8,
+    value: FieldValue,
+) -> Result<(), SetFieldError> {
+    *field = T::from_value(value).ok_or(SetFieldError::WrongType {
+        field_id,
+        expected: T::DATA_TYPE,
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(LiquidFields, Debug, Default, PartialEq)]
+    struct Ecu {
+        #[telemetry(group = 2)]
+        tank_pressure: f32,
+        #[parameter]
+        fill_target: u16,
+        #[telemetry(group = 1)]
+        vent_open: bool,
+        #[telemetry]
+        uptime: u32,
+        #[telemetry(group = 2, name = "temp")]
+        temperature: i16,
+        #[parameter(name = "valve")]
+        valve_open: bool,
+        internal: Vec<u8>,
+    }
+
+    #[test]
+    fn test_field_ids() {
+        let ids: Vec<_> = Ecu::FIELDS.iter().map(|f| (f.name, f.id)).collect();
+        assert_eq!(
+            ids,
+            vec![
+                ("tank_pressure", 0x81),
+                ("fill_target", 0x01),
+                ("vent_open", 0x82),
+                ("uptime", 0x83),
+                ("temp", 0x84),
+                ("valve", 0x02),
+            ]
+        );
+        assert_eq!(Ecu::field_def(0x84).unwrap().data_type, CanDataType::Int16);
+        assert_eq!(Ecu::field_def(0x84).unwrap().group, Some(2));
+        assert_eq!(Ecu::field_id("internal"), None);
+    }
+
+    #[test]
+    fn test_registrations() {
+        let info = Ecu::node_info().name("ecu").build().unwrap();
+        assert_eq!((info.tel_count(), info.par_count()), (4, 2));
+
+        let registrations = Ecu::registrations();
+        assert_eq!(registrations.len(), 6);
+        assert_eq!(registrations[1].kind(), FieldKind::Parameter);
+        assert_eq!(registrations[1].name(), "fill_target");
+        assert_eq!(registrations[1].data_type(), CanDataType::UInt16);
+
+        let groups = Ecu::group_definitions();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].group_id, 1);
+        assert_eq!(groups[0].field_ids(), vec![0x82]);
+        assert_eq!(groups[1].group_id, 2);
+        assert_eq!(groups[1].field_ids(), vec![0x81, 0x84]);
+    }
+
+    #[test]
+    fn test_get_set_and_pack() {
+        let mut ecu = Ecu::default();
+        ecu.set_field(0x81, FieldValue::Float32(2.5)).unwrap();
+        ecu.set_field(0x84, FieldValue::Int16(-3)).unwrap();
+        ecu.set_field(0x02, FieldValue::Boolean(true)).unwrap();
+        assert_eq!(ecu.tank_pressure, 2.5);
+        assert!(ecu.valve_open);
+        assert_eq!(ecu.get_field(0x84), Some(FieldValue::Int16(-3)));
+        assert_eq!(ecu.get_field(0x7F), None);
+
+        assert_eq!(
+            ecu.set_field(0x81, FieldValue::UInt8(1)),
+            Err(SetFieldError::WrongType {
+                field_id: 0x81,
+                expected: CanDataType::Float32
+            })
+        );
+        assert_eq!(
+            ecu.set_field(0x90, FieldValue::UInt8(1)),
+            Err(SetFieldError::UnknownField(0x90))
+        );
+
+        let update = ecu.pack_group(2).unwrap();
+        let types = [CanDataType::Float32, CanDataType::Int16];
+        assert_eq!(
+            update.unpack(&types).unwrap(),
+            vec![FieldValue::Float32(2.5), FieldValue::Int16(-3)]
+        );
+        assert!(ecu.pack_group(3).is_none());
+    }
+}
@@ -0,0 +1,282 @@
+//! Offline analysis of raw captures.
+//!
+//! A [`LogAnalyzer`] replays the frames of a capture in order: announcements build up a
+//! [`NodeRegistry`] the way the server would have, and every value carried by a
+//! `TelemetryGroupUpdate`, `FieldGetRes` or `ParameterSetConfirmation` is added to the timeline
+//! of its field. Frames that can't be decoded with what was announced up to that point, e.g.
+//! group updates sent before the group definition, are reported instead.
+
+use crate::builders::BuildError;
+use crate::candump::{self, CandumpError};
+use crate::export::Sample;
+use crate::field_store::{self, IngestError};
+use crate::field_value::FieldValue;
+use crate::registry::{FieldId, NodeId, NodeRegistry};
+use crate::transport::BusFrame;
+use crate::{CanMessageId, DecodeError};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::BufRead;
+use std::time::Duration;
+
+/// Why a frame didn't contribute to the timelines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Undecodable {
+    /// The frame isn't a valid message.
+    Frame(DecodeError),
+    /// The message is an announcement that violates the spec.
+    Announcement(BuildError),
+    /// The values refer to fields or groups that weren't announced before.
+    Values(IngestError),
+    /// The line of the capture isn't a LiquidCAN frame, e.g. a remote or error frame or one with
+    /// an extended identifier.
+    Line { line: usize },
+}
+
+impl fmt::Display for Undecodable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Undecodable::Frame(e) => write!(f, "{e}"),
+            Undecodable::Announcement(e) => write!(f, "Invalid announcement: {e}"),
+            Undecodable::Values(e) => write!(f, "{e}"),
+            Undecodable::Line { line } => write!(f, "Line {line} isn't a LiquidCAN frame"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UndecodableFrame {
+    pub timestamp: Duration,
+    /// `None` for lines of a capture that couldn't be parsed.
+    pub id: Option<CanMessageId>,
+    pub reason: Undecodable,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LogAnalyzer {
+    registry: NodeRegistry,
+    timelines: BTreeMap<(NodeId, FieldId), Vec<(Duration, FieldValue)>>,
+    undecodable: Vec<UndecodableFrame>,
+    frames: usize,
+}
+
+impl LogAnalyzer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Processes the next frame of the capture. Frames must be passed in the order they were
+    /// received.
+    pub fn process(&mut self, timestamp: Duration, frame: &BusFrame) {
+        self.frames += 1;
+        let sender = frame.id.sender_id();
+        let result = frame.decode().map_err(Undecodable::Frame).and_then(|msg| {
+            self.registry
+                .observe(sender, &msg)
+                .map_err(Undecodable::Announcement)?;
+            field_store::field_values(&self.registry, sender, &msg).map_err(Undecodable::Values)
+        });
+        match result {
+            Ok(values) => {
+                for (field_id, value) in values {
+                    self.timelines
+                        .entry((sender, field_id))
+                        .or_default()
+                        .push((timestamp, value));
+                }
+            }
+            Err(reason) => self.undecodable.push(UndecodableFrame {
+                timestamp,
+                id: Some(frame.id),
+                reason,
+            }),
+        }
+    }
+
+    /// Processes all frames of a `candump` capture. Lines without timestamp get the one of the
+    /// line before. Lines that can't be parsed are reported as undecodable with the timestamp
+    /// of the line before, only reading errors stop the analysis.
+    pub fn process_candump(&mut self, reader: impl BufRead) -> Result<(), CandumpError> {
+        for (timestamp, frame) in candump::parse_timed(reader) {
+            match frame {
+                Ok(frame) => self.process(timestamp, &frame),
+                Err(CandumpError::Io(e)) => return Err(CandumpError::Io(e)),
+                Err(CandumpError::Malformed { line } | CandumpError::ExtendedId { line }) => {
+                    self.frames += 1;
+                    self.undecodable.push(UndecodableFrame {
+                        timestamp,
+                        id: None,
+                        reason: Undecodable::Line { line },
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// What the nodes announced in the capture.
+    pub fn registry(&self) -> &NodeRegistry {
+        &self.registry
+    }
+
+    /// Number of frames processed.
+    pub fn frame_count(&self) -> usize {
+        self.frames
+    }
+
+    /// Returns the values of a field in the order they were received.
+    pub fn timeline(&self, node: NodeId, field_id: FieldId) -> &[(Duration, FieldValue)] {
+        self.timelines
+            .get(&(node, field_id))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the fields that have values, ordered by node and field ID.
+    pub fn fields(&self) -> impl Iterator<Item = (NodeId, FieldId)> + '_ {
+        self.timelines.keys().copied()
+    }
+
+    pub fn undecodable(&self) -> &[UndecodableFrame] {
+        &self.undecodable
+    }
+
+    /// Returns all values ordered by timestamp, named as registered at the end of the capture.
+    pub fn samples(&self) -> Vec<Sample> {
+        let mut samples = Vec::new();
+        for (&(node, field_id), timeline) in &self.timelines {
+            let name = self
+                .registry
+                .field(node, field_id)
+                .map_or_else(|| format!("{field_id:#04x}"), |f| f.name().to_string());
+            samples.extend(timeline.iter().map(|&(timestamp, value)| Sample {
+                timestamp,
+                node,
+                field_id,
+                name: name.clone(),
+                value,
+            }));
+        }
+        samples.sort_by_key(|sample| sample.timestamp);
+        samples
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CanMessage;
+    use crate::registry::tests::example_registry;
+    use std::fmt::Write;
+
+    /// Writes a message from node 3 to the server as a line of `candump -L`.
+    fn line(capture: &mut String, seconds: f64, msg: CanMessage) {
+        let id = msg.id(3, 0);
+        let frame = BusFrame::encode(id, msg);
+        let data: String = frame.data().iter().map(|b| format!("{b:02X}")).collect();
+        writeln!(
+            capture,
+            "({seconds:.6}) can0 {:03X}##0{data}",
+            u16::from(id)
+        )
+        .unwrap();
+    }
+
+    fn group_update(pressure: f32) -> CanMessage {
+        CanMessage::TelemetryGroupUpdate {
+            payload: crate::payloads::TelemetryGroupUpdatePayload::pack(
+                1,
+                &[FieldValue::Float32(pressure), FieldValue::Boolean(false)],
+            )
+            .unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_reconstructs_registry_and_timelines() {
+        let mut capture = String::new();
+        // The recording starts while node 3 is already sending.
+        line(&mut capture, 1.0, group_update(0.5));
+        for (i, (_, msg)) in example_registry().announcements().into_iter().enumerate() {
+            line(&mut capture, 2.0 + i as f64 * 0.001, msg);
+        }
+        line(&mut capture, 3.0, group_update(1.5));
+        line(&mut capture, 4.0, group_update(2.5));
+
+        let mut analyzer = LogAnalyzer::new();
+        analyzer.process_candump(capture.as_bytes()).unwrap();
+        assert_eq!(analyzer.frame_count(), 8);
+        assert_eq!(analyzer.registry(), &example_registry());
+        assert_eq!(
+            analyzer.timeline(3, 0x81),
+            &[
+                (Duration::from_secs(3), FieldValue::Float32(1.5)),
+                (Duration::from_secs(4), FieldValue::Float32(2.5)),
+            ]
+        );
+        assert_eq!(
+            analyzer.fields().collect::<Vec<_>>(),
+            vec![(3, 0x81), (3, 0x82)]
+        );
+        let samples = analyzer.samples();
+        assert_eq!(samples.len(), 4);
+        assert_eq!(samples[1].name, "vent_open");
+        assert_eq!(samples[2].timestamp, Duration::from_secs(4));
+
+        let undecodable = analyzer.undecodable();
+        assert_eq!(undecodable.len(), 1);
+        assert_eq!(undecodable[0].timestamp, Duration::from_secs(1));
+        assert_eq!(
+            undecodable[0].reason,
+            Undecodable::Values(IngestError::UnknownGroup {
+                node: 3,
+                group_id: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_skips_unparseable_lines() {
+        let mut capture = String::new();
+        line(&mut capture, 1.0, group_update(0.5));
+        // An error frame, a remote frame and garbage.
+        capture.push_str("(1.500000) can0 20000080#0000000000000000\n");
+        capture.push_str("(1.600000) can0 023#R\n");
+        capture.push_str("not a frame\n");
+        for (i, (_, msg)) in example_registry().announcements().into_iter().enumerate() {
+            line(&mut capture, 2.0 + i as f64 * 0.001, msg);
+        }
+        line(&mut capture, 3.0, group_update(1.5));
+
+        let mut analyzer = LogAnalyzer::new();
+        analyzer.process_candump(capture.as_bytes()).unwrap();
+        assert_eq!(analyzer.frame_count(), 10);
+        assert_eq!(analyzer.registry(), &example_registry());
+        assert_eq!(analyzer.timeline(3, 0x81).len(), 1);
+
+        let undecodable = analyzer.undecodable();
+        assert_eq!(undecodable.len(), 4);
+        let lines: Vec<_> = undecodable[1..]
+            .iter()
+            .map(|u| (u.timestamp.as_millis(), u.id, u.reason.clone()))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                (1000, None, Undecodable::Line { line: 2 }),
+                (1000, None, Undecodable::Line { line: 3 }),
+                (1000, None, Undecodable::Line { line: 4 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reports_invalid_frames() {
+        let mut analyzer = LogAnalyzer::new();
+        let frame = BusFrame::new(CanMessageId::new(), &[2]).unwrap();
+        analyzer.process(Duration::ZERO, &frame);
+        assert_eq!(
+            analyzer.undecodable()[0].reason,
+            Undecodable::Frame(DecodeError::UnknownMessageType(2))
+        );
+    }
+}
@@ -1,39 +1,103 @@
 use crate::CanMessageFrame;
 use crate::can_message::{CanMessage, CanMessagePadded};
-use anyhow::anyhow;
+use core::fmt;
 use zerocopy::{FromZeros, IntoBytes, TryFromBytes};
 
+/// Reasons why bytes received from the bus could not be decoded into a [`CanMessage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The frame has no data at all, so not even the message type is present.
+    Empty,
+    /// The frame has more data than fits into a CAN FD frame.
+    TooLong { len: usize },
+    /// The message type byte does not belong to any known message.
+    UnknownMessageType(u8),
+    /// The message type is known, but the payload contains an invalid value (e.g. an undefined
+    /// [`CanDataType`](crate::payloads::CanDataType)).
+    InvalidPayload { message_type: u8 },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to convert message: ")?;
+        match self {
+            DecodeError::Empty => write!(f, "frame is empty"),
+            DecodeError::TooLong { len } => write!(
+                f,
+                "frame has {len} bytes, at most {} are allowed",
+                size_of::<CanMessageFrame>()
+            ),
+            DecodeError::UnknownMessageType(message_type) => {
+                write!(f, "unknown message type {message_type}")
+            }
+            DecodeError::InvalidPayload { message_type } => {
+                write!(f, "invalid payload for message type {message_type}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+impl CanMessageFrame {
+    /// Builds a frame from the data field of a received CAN FD frame.
+    ///
+    /// Senders may omit trailing padding, so shorter inputs are filled up with zeros.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (&message_type, _) = bytes.split_first().ok_or(DecodeError::Empty)?;
+        let mut buf = [0u8; size_of::<CanMessageFrame>()];
+        buf.get_mut(..bytes.len())
+            .ok_or(DecodeError::TooLong { len: bytes.len() })?
+            .copy_from_slice(bytes);
+        CanMessageFrame::try_read_from_bytes(&buf)
+            .map_err(|_| DecodeError::UnknownMessageType(message_type))
+    }
+}
+
+impl CanMessage {
+    /// Decodes the data field of a received CAN FD frame, see [`CanMessageFrame::from_bytes`].
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        CanMessageFrame::from_bytes(bytes)?.try_into()
+    }
+}
+
 impl TryFrom<CanMessageFrame> for CanMessage {
-    type Error = anyhow::Error;
+    type Error = DecodeError;
 
     fn try_from(frame: CanMessageFrame) -> Result<Self, Self::Error> {
         let frame_data = frame.as_bytes();
-        let padded_msg = CanMessagePadded::try_read_from_bytes(frame_data)
-            .map_err(|e| anyhow!("Failed to convert message: {}", e))?;
-        let msg: CanMessage = padded_msg.into();
-        Ok(msg)
+        match CanMessagePadded::try_read_from_bytes(frame_data) {
+            Ok(padded_msg) => Ok(padded_msg.into()),
+            Err(_) => Err(DecodeError::InvalidPayload {
+                message_type: frame.message_type.into(),
+            }),
+        }
     }
 }
 
 impl From<CanMessage> for CanMessageFrame {
     fn from(msg: CanMessage) -> Self {
         let mut msg_frame = CanMessageFrame::new_zeroed();
-        let discriminant = msg.discriminant();
+        let message_type = msg.message_type();
         let padded_msg: CanMessagePadded = msg.into();
         // The first byte is the discriminant, which is set separately.
         let bytes: &[u8] = &padded_msg.as_bytes()[1..];
         msg_frame.data[..bytes.len()].copy_from_slice(bytes);
-        msg_frame.message_type = discriminant;
+        msg_frame.message_type = message_type;
         msg_frame
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
+    use super::DecodeError;
     use crate::CanMessageFrame;
-    use crate::can_message::CanMessage;
+    use crate::can_message::{CanMessage, CanMessageType};
     use crate::payloads;
-    use zerocopy::FromZeros;
+    use crate::raw_can_message::CanMessageId;
+    use arbitrary::{Arbitrary, Unstructured};
+    use proptest::prelude::*;
+    use zerocopy::{FromZeros, IntoBytes};
 
     fn test_round_trip(msg: CanMessage) {
         let can_data: CanMessageFrame = msg.clone().into();
@@ -54,8 +118,8 @@ mod tests {
         let payload = payloads::NodeInfoResPayload {
             tel_count: 7,
             par_count: 5,
-            firmware_hash: 1234,
-            liquid_hash: 5678,
+            firmware_hash: 1234.into(),
+            liquid_hash: 5678.into(),
             device_name: [0xAA; 53],
         };
         let msg = CanMessage::NodeInfoAnnouncement { payload };
@@ -127,14 +191,14 @@ mod tests {
 
     #[test]
     fn test_heartbeat_req() {
-        let payload = payloads::HeartbeatPayload { counter: 17 };
+        let payload = payloads::HeartbeatPayload::new(17);
         let msg = CanMessage::HeartbeatReq { payload };
         test_round_trip(msg);
     }
 
     #[test]
     fn test_heartbeat_res() {
-        let payload = payloads::HeartbeatPayload { counter: 18 };
+        let payload = payloads::HeartbeatPayload::new(18);
         let msg = CanMessage::HeartbeatRes { payload };
         test_round_trip(msg);
     }
@@ -218,12 +282,13 @@ mod tests {
 
     #[test]
     fn test_invalid_message_type() {
-        // Create a frame with an invalid message type (255 is not defined)
-        let mut frame = CanMessageFrame::new_zeroed();
-        frame.message_type = 255;
-
-        let result: Result<CanMessage, _> = frame.try_into();
-        assert!(result.is_err(), "Expected error for invalid message type");
+        // 255 is not a defined message type
+        let result = CanMessageFrame::from_bytes(&[255, 1, 2]).map(|_| ());
+        assert_eq!(result, Err(DecodeError::UnknownMessageType(255)));
+        assert_eq!(
+            CanMessage::decode(&[255]),
+            Err(DecodeError::UnknownMessageType(255))
+        );
         let err_msg = result.unwrap_err().to_string();
         assert!(
             err_msg.contains("Failed to convert message"),
@@ -232,24 +297,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_message_type() {
+        let msg = CanMessage::HeartbeatReq {
+            payload: payloads::HeartbeatPayload::new(1),
+        };
+        assert_eq!(msg.message_type(), CanMessageType::HeartbeatReq);
+        assert_eq!(msg.discriminant(), 40);
+        assert_eq!(CanMessageType::HeartbeatReq.name(), "HeartbeatReq");
+
+        assert_eq!(
+            CanMessageType::try_from(63),
+            Ok(CanMessageType::FieldIDLookupRes)
+        );
+        assert_eq!(CanMessageType::try_from(2), Err(2));
+        assert_eq!(u8::from(CanMessageType::InfoStatus), 10);
+
+        // The companion enum lists the same variants as the wire format metadata.
+        let types: Vec<(u8, &str)> = CanMessageType::iter()
+            .map(|t| (t.into(), t.name()))
+            .collect();
+        let variants: Vec<(u8, &str)> = CanMessage::VARIANTS
+            .iter()
+            .map(|&(discriminant, name, _)| (discriminant, name))
+            .collect();
+        assert_eq!(types, variants);
+    }
+
     #[test]
     fn test_invalid_can_data_type() {
         // Create a FieldRegistration with invalid CanDataType (255 is out of range)
         let mut frame = CanMessageFrame::new_zeroed();
-        frame.message_type = 20; // TelemetryValueRegistration
+        frame.message_type = CanMessageType::TelemetryValueRegistration;
         frame.data[0] = 5; // field_id
         frame.data[1] = 255; // Invalid CanDataType
         // Rest is field_name
 
         let result: Result<CanMessage, _> = frame.try_into();
         assert!(result.is_err(), "Expected error for invalid CanDataType");
+        assert_eq!(
+            result.unwrap_err(),
+            DecodeError::InvalidPayload { message_type: 20 }
+        );
     }
 
     #[test]
     fn test_invalid_parameter_set_status() {
         // Create a ParameterSetConfirmation with invalid status
         let mut frame = CanMessageFrame::new_zeroed();
-        frame.message_type = 51; // ParameterSetConfirmation
+        frame.message_type = CanMessageType::ParameterSetConfirmation;
         frame.data[0] = 10; // parameter_id
         frame.data[1] = 255; // Invalid ParameterSetStatus
         // Rest is value
@@ -265,7 +361,7 @@ mod tests {
     fn test_invalid_parameter_lock_status() {
         // Create a ParameterSetLockReq with invalid lock status
         let mut frame = CanMessageFrame::new_zeroed();
-        frame.message_type = 52; // ParameterSetLockReq
+        frame.message_type = CanMessageType::ParameterSetLockReq;
         frame.data[0] = 12; // parameter_id
         frame.data[1] = 255; // Invalid ParameterLockStatus
 
@@ -275,4 +371,111 @@ mod tests {
             "Expected error for invalid ParameterLockStatus"
         );
     }
+
+    #[test]
+    fn test_frame_from_short_bytes() {
+        let frame = CanMessageFrame::from_bytes(&[40, 0x11, 0x22]).unwrap();
+        let msg: CanMessage = frame.try_into().unwrap();
+        assert_eq!(
+            msg,
+            CanMessage::HeartbeatReq {
+                payload: payloads::HeartbeatPayload::new(0x2211)
+            }
+        );
+    }
+
+    #[test]
+    fn test_frame_from_invalid_length() {
+        assert_eq!(
+            CanMessageFrame::from_bytes(&[]).unwrap_err(),
+            DecodeError::Empty
+        );
+        assert_eq!(
+            CanMessageFrame::from_bytes(&[0; 65]).unwrap_err(),
+            DecodeError::TooLong { len: 65 }
+        );
+    }
+
+    #[test]
+    fn test_decode_ignores_padding() {
+        let msg = CanMessage::HeartbeatReq {
+            payload: payloads::HeartbeatPayload::new(0x2211),
+        };
+        let mut buf = [0u8; 64];
+        let len = msg.clone().to_bytes(&mut buf).len();
+        let mut bytes = buf;
+        bytes[len..].fill(0xAA);
+
+        // Non-zero padding is accepted and dropped when encoding again.
+        let decoded = CanMessage::decode(&bytes).unwrap();
+        assert_eq!(decoded, msg);
+        let frame: CanMessageFrame = decoded.into();
+        assert_eq!(frame.as_bytes(), &buf);
+    }
+
+    /// Builds arbitrary messages from random bytes, so proptest can shrink the input bytes.
+    pub(crate) fn arbitrary_message() -> impl Strategy<Value = CanMessage> {
+        prop::collection::vec(any::<u8>(), 0..256).prop_map(|bytes| {
+            CanMessage::arbitrary_take_rest(Unstructured::new(&bytes))
+                .expect("Arbitrary never runs out of data for CanMessage")
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_frame_round_trip(msg in arbitrary_message()) {
+            let frame: CanMessageFrame = msg.clone().into();
+            let msg_back: CanMessage = frame.try_into().unwrap();
+            prop_assert_eq!(msg, msg_back);
+        }
+
+        #[test]
+        fn prop_padded_bytes_round_trip(msg in arbitrary_message()) {
+            let mut buf = [0u8; 64];
+            let bytes = msg.clone().to_bytes(&mut buf);
+            prop_assert_eq!(&CanMessage::from_bytes(bytes).unwrap(), &msg);
+            prop_assert_eq!(&CanMessage::decode(bytes).unwrap(), &msg);
+
+            // Both encoders agree, the frame just keeps the padding.
+            let frame: CanMessageFrame = msg.into();
+            prop_assert_eq!(&frame.as_bytes()[..bytes.len()], bytes);
+            prop_assert!(frame.as_bytes()[bytes.len()..].iter().all(|&b| b == 0));
+        }
+
+        #[test]
+        fn prop_variant_metadata_matches_encoding(msg in arbitrary_message()) {
+            let mut buf = [0u8; 64];
+            let len = msg.clone().to_bytes(&mut buf).len();
+            let discriminant = msg.discriminant();
+            prop_assert_eq!(len, 1 + msg.payload_len());
+            prop_assert_eq!(CanMessage::pad_len(discriminant), Some(64 - len));
+            let name = CanMessage::discriminant_name(discriminant).unwrap();
+            prop_assert_eq!(CanMessage::from_discriminant_name(name), Some(discriminant));
+        }
+
+        #[test]
+        fn prop_decode_arbitrary_bytes(bytes in prop::collection::vec(any::<u8>(), 0..=64)) {
+            // Decoding must never panic. Whatever decodes successfully re-encodes identically up
+            // to the end of the payload. The bytes after it are padding, which receivers ignore
+            // and encoders zero (see `test_decode_ignores_padding`), so the re-encoded frame is
+            // the input with its padding zeroed.
+            if let Ok(msg) = CanMessage::decode(&bytes) {
+                let mut buf = [0u8; 64];
+                let len = msg.clone().to_bytes(&mut buf).len();
+                let mut expected = bytes.clone();
+                expected.resize(64, 0);
+                expected[len..].fill(0);
+                let frame: CanMessageFrame = msg.into();
+                prop_assert_eq!(frame.as_bytes(), &expected[..]);
+            }
+        }
+
+        #[test]
+        fn prop_can_message_id_round_trip(bytes in prop::collection::vec(any::<u8>(), 0..8)) {
+            let id = CanMessageId::arbitrary_take_rest(Unstructured::new(&bytes)).unwrap();
+            let raw: u16 = id.into();
+            prop_assert!(raw < 1 << 11);
+            prop_assert_eq!(CanMessageId::from(raw), id);
+        }
+    }
 }
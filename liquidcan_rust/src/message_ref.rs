@@ -0,0 +1,206 @@
+//! Zero-copy views of received messages.
+
+use crate::payloads::*;
+use crate::{CanMessage, CanMessageFrame, CanMessageType, DecodeError};
+use alloc::borrow::Cow;
+use zerocopy::{Immutable, IntoBytes, KnownLayout, TryFromBytes};
+
+/// Borrows the payload of type `T` from the start of `bytes`, validating only its enum fields.
+/// A payload cut short is copied and filled up with zeros, like [`CanMessage::decode`] does.
+fn payload<T>(message_type: CanMessageType, bytes: &[u8]) -> Result<Cow<'_, T>, DecodeError>
+where
+    T: TryFromBytes + KnownLayout + Immutable + Clone,
+{
+    let invalid = || DecodeError::InvalidPayload {
+        message_type: message_type.into(),
+    };
+    match bytes.get(..size_of::<T>()) {
+        Some(payload_bytes) => T::try_ref_from_bytes(payload_bytes)
+            .map(Cow::Borrowed)
+            .map_err(|_| invalid()),
+        None => {
+            let mut buf = [0u8; size_of::<CanMessageFrame>()];
+            buf[..bytes.len()].copy_from_slice(bytes);
+            T::try_read_from_bytes(&buf[..size_of::<T>()])
+                .map(Cow::Owned)
+                .map_err(|_| invalid())
+        }
+    }
+}
+
+macro_rules! message_ref {
+    ($($Unit:ident),*; $($Variant:ident($Payload:ty)),* $(,)?) => {
+        /// A message borrowed from a receive buffer.
+        ///
+        /// Unlike [`CanMessage::decode`], which copies the frame into the padded wire enum and
+        /// converts it, building a view only checks the message type and the enum-typed payload
+        /// bytes and hands out references into the buffer. Only payloads whose trailing zeros
+        /// were omitted by the sender are copied.
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum CanMessageRef<'a> {
+            $($Unit,)*
+            $($Variant { payload: Cow<'a, $Payload> },)*
+        }
+
+        impl<'a> CanMessageRef<'a> {
+            /// Borrows a message from the data field of a received CAN FD frame. Accepts the same
+            /// input as [`CanMessage::decode`].
+            pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, DecodeError> {
+                let (&message_type, rest) = bytes.split_first().ok_or(DecodeError::Empty)?;
+                if bytes.len() > size_of::<CanMessageFrame>() {
+                    return Err(DecodeError::TooLong { len: bytes.len() });
+                }
+                let message_type = CanMessageType::try_from(message_type)
+                    .map_err(DecodeError::UnknownMessageType)?;
+                Ok(match message_type {
+                    $(CanMessageType::$Unit => CanMessageRef::$Unit,)*
+                    $(CanMessageType::$Variant => CanMessageRef::$Variant {
+                        payload: payload::<$Payload>(message_type, rest)?,
+                    },)*
+                })
+            }
+
+            pub fn from_frame(frame: &'a CanMessageFrame) -> Result<Self, DecodeError> {
+                Self::from_bytes(frame.as_bytes())
+            }
+
+            pub fn message_type(&self) -> CanMessageType {
+                match self {
+                    $(CanMessageRef::$Unit => CanMessageType::$Unit,)*
+                    $(CanMessageRef::$Variant { .. } => CanMessageType::$Variant,)*
+                }
+            }
+
+            /// Copies the message out of the buffer.
+            pub fn to_message(&self) -> CanMessage {
+                match self {
+                    $(CanMessageRef::$Unit => CanMessage::$Unit,)*
+                    $(CanMessageRef::$Variant { payload } => CanMessage::$Variant {
+                        payload: payload.clone().into_owned(),
+                    },)*
+                }
+            }
+        }
+    };
+}
+
+message_ref! {
+    NodeInfoReq;
+    NodeInfoAnnouncement(NodeInfoResPayload),
+    InfoStatus(StatusPayload),
+    WarningStatus(StatusPayload),
+    ErrorStatus(StatusPayload),
+    TelemetryValueRegistration(FieldRegistrationPayload),
+    ParameterRegistration(FieldRegistrationPayload),
+    TelemetryGroupDefinition(TelemetryGroupDefinitionPayload),
+    TelemetryGroupUpdate(TelemetryGroupUpdatePayload),
+    HeartbeatReq(HeartbeatPayload),
+    HeartbeatRes(HeartbeatPayload),
+    ParameterSetReq(ParameterSetReqPayload),
+    ParameterSetConfirmation(ParameterSetConfirmationPayload),
+    ParameterSetLockReq(ParameterSetLockPayload),
+    ParameterSetLockConfirmation(ParameterSetLockPayload),
+    FieldGetReq(FieldGetReqPayload),
+    FieldGetRes(FieldGetResPayload),
+    FieldIDLookupReq(FieldIDLookupReqPayload),
+    FieldIDLookupRes(FieldIDLookupResPayload),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::BusFrame;
+    use crate::{CanMessageId, message_conversion::tests::arbitrary_message};
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_borrows_payload() {
+        let msg = CanMessage::FieldGetRes {
+            payload: FieldGetResPayload {
+                field_id: 0x81,
+                value: [7; 62],
+            },
+        };
+        let frame = CanMessageFrame::from(msg.clone());
+        let view = CanMessageRef::from_frame(&frame).unwrap();
+        let CanMessageRef::FieldGetRes { payload } = &view else {
+            panic!("unexpected view {view:?}");
+        };
+        assert!(std::ptr::eq(payload.as_bytes().as_ptr(), &frame.data[0]));
+        assert_eq!(view.message_type(), CanMessageType::FieldGetRes);
+        assert_eq!(view.to_message(), msg);
+    }
+
+    #[test]
+    fn test_omitted_padding() {
+        let bytes = [40, 1, 0, 0, 0];
+        let view = CanMessageRef::from_bytes(&bytes).unwrap();
+        assert!(matches!(
+            view,
+            CanMessageRef::HeartbeatReq {
+                payload: Cow::Borrowed(_)
+            }
+        ));
+        assert_eq!(view.to_message(), CanMessage::decode(&bytes).unwrap());
+
+        // The sender may omit trailing zeros of the payload as well.
+        let view = CanMessageRef::from_bytes(&[40, 1]).unwrap();
+        assert!(matches!(
+            view,
+            CanMessageRef::HeartbeatReq {
+                payload: Cow::Owned(_)
+            }
+        ));
+        assert_eq!(view.to_message(), CanMessage::decode(&[40, 1]).unwrap());
+        assert_eq!(
+            CanMessageRef::from_bytes(&[0]).unwrap(),
+            CanMessageRef::NodeInfoReq
+        );
+    }
+
+    // The table passed to `message_ref!` is kept in sync with [`CanMessage`] by hand, so check
+    // that every message type maps to its own variant and payload.
+    #[test]
+    fn test_covers_every_message_type() {
+        for message_type in CanMessageType::iter() {
+            let bytes = [u8::from(message_type)];
+            let view = CanMessageRef::from_bytes(&bytes).unwrap();
+            assert_eq!(view.message_type(), message_type);
+            assert_eq!(view.to_message(), CanMessage::decode(&bytes).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(CanMessageRef::from_bytes(&[]), Err(DecodeError::Empty));
+        assert_eq!(
+            CanMessageRef::from_bytes(&[0; 65]),
+            Err(DecodeError::TooLong { len: 65 })
+        );
+        assert_eq!(
+            CanMessageRef::from_bytes(&[255]),
+            Err(DecodeError::UnknownMessageType(255))
+        );
+        // FieldIDLookupRes with an undefined CanDataType
+        assert_eq!(
+            CanMessageRef::from_bytes(&[63, 1, 255]),
+            Err(DecodeError::InvalidPayload { message_type: 63 })
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn prop_view_matches_decode(msg in arbitrary_message()) {
+            let frame = BusFrame::encode(CanMessageId::new(), msg.clone());
+            let view = CanMessageRef::from_bytes(frame.data()).unwrap();
+            prop_assert_eq!(view.message_type(), msg.message_type());
+            prop_assert_eq!(view.to_message(), msg);
+        }
+
+        #[test]
+        fn prop_view_agrees_with_decode(bytes in prop::collection::vec(any::<u8>(), 0..=64)) {
+            let view = CanMessageRef::from_bytes(&bytes).map(|view| view.to_message());
+            prop_assert_eq!(view, CanMessage::decode(&bytes));
+        }
+    }
+}
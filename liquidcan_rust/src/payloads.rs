@@ -1,7 +1,23 @@
+use liquidcan_rust_macros_derive::CType;
 use modular_bitfield::{Specifier, private::static_assertions};
-use zerocopy_derive::{FromBytes, Immutable, IntoBytes, TryFromBytes};
+use zerocopy::little_endian::U32;
+use zerocopy_derive::{FromBytes, Immutable, IntoBytes, KnownLayout, TryFromBytes, Unaligned};
 
-#[derive(Specifier, Debug, Copy, Clone, PartialEq, Eq, Immutable, TryFromBytes, IntoBytes)]
+#[derive(
+    Specifier,
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Immutable,
+    TryFromBytes,
+    IntoBytes,
+    KnownLayout,
+    Unaligned,
+    CType,
+)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[repr(u8)]
 pub enum CanDataType {
     Float32 = 0,
@@ -14,7 +30,21 @@ pub enum CanDataType {
     Boolean = 7,
 }
 
-#[derive(Specifier, Debug, Copy, Clone, PartialEq, Eq, Immutable, TryFromBytes, IntoBytes)]
+#[derive(
+    Specifier,
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Immutable,
+    TryFromBytes,
+    IntoBytes,
+    KnownLayout,
+    Unaligned,
+    CType,
+)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[repr(u8)]
 pub enum ParameterSetStatus {
     Success = 0,                // Parameter was successfully set
@@ -23,31 +53,56 @@ pub enum ParameterSetStatus {
     NodeToNodeModification = 3, // The parameter was modified by another node
 }
 
-#[derive(Specifier, Debug, Copy, Clone, PartialEq, Eq, Immutable, TryFromBytes, IntoBytes)]
+#[derive(
+    Specifier,
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Immutable,
+    TryFromBytes,
+    IntoBytes,
+    KnownLayout,
+    Unaligned,
+    CType,
+)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[repr(u8)]
 pub enum ParameterLockStatus {
     Unlocked = 0,
     Locked = 1,
 }
 
-#[derive(Debug, Clone, FromBytes, IntoBytes, Immutable, PartialEq)]
+#[derive(
+    Debug, Clone, FromBytes, IntoBytes, Immutable, KnownLayout, Unaligned, PartialEq, CType,
+)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[repr(C, packed)]
 pub struct NodeInfoResPayload {
-    pub tel_count: u8,         // Number of telemetryValues on this node
-    pub par_count: u8,         // Number of parameters on this node
-    pub firmware_hash: u32,    // Hash of the firmware version
-    pub liquid_hash: u32,      // Hash of the LiquidCan protocol version
+    pub tel_count: u8, // Number of telemetryValues on this node
+    pub par_count: u8, // Number of parameters on this node
+    #[cfg_attr(any(test, feature = "arbitrary"), arbitrary(with = arbitrary_u32))]
+    pub firmware_hash: U32, // Hash of the firmware version
+    #[cfg_attr(any(test, feature = "arbitrary"), arbitrary(with = arbitrary_u32))]
+    pub liquid_hash: U32, // Hash of the LiquidCan protocol version
     pub device_name: [u8; 53], // Human-readable device name
 }
 
-#[derive(Debug, Clone, FromBytes, IntoBytes, Immutable, PartialEq)]
+#[derive(
+    Debug, Clone, FromBytes, IntoBytes, Immutable, KnownLayout, Unaligned, PartialEq, CType,
+)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[repr(C, packed)]
 pub struct StatusPayload {
     pub msg: [u8; 63], // Status message text
 }
 
 // Important: only derives TryFromBytes because enum CanDataType doesn't cover all possible enum variants for u8
-#[derive(Debug, Clone, TryFromBytes, IntoBytes, Immutable, PartialEq)]
+#[derive(
+    Debug, Clone, TryFromBytes, IntoBytes, Immutable, KnownLayout, Unaligned, PartialEq, CType,
+)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[repr(C, packed)]
 pub struct FieldRegistrationPayload {
     pub field_id: u8,            // Unique identifier for this field
@@ -55,27 +110,40 @@ pub struct FieldRegistrationPayload {
     pub field_name: [u8; 61],    // Human-readable field name
 }
 
-#[derive(Debug, Clone, FromBytes, IntoBytes, Immutable, PartialEq)]
+#[derive(
+    Debug, Clone, FromBytes, IntoBytes, Immutable, KnownLayout, Unaligned, PartialEq, CType,
+)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[repr(C, packed)]
 pub struct TelemetryGroupDefinitionPayload {
     pub group_id: u8,        // Unique identifier for this group
     pub field_ids: [u8; 62], // Array of field IDs in this group
 }
 
-#[derive(Debug, Clone, FromBytes, IntoBytes, Immutable, PartialEq)]
+#[derive(
+    Debug, Clone, FromBytes, IntoBytes, Immutable, KnownLayout, Unaligned, PartialEq, CType,
+)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[repr(C, packed)]
 pub struct TelemetryGroupUpdatePayload {
     pub group_id: u8,     // Group identifier
     pub values: [u8; 62], // Packed values of all telemetry values in the group
 }
 
-#[derive(Debug, Clone, FromBytes, IntoBytes, Immutable, PartialEq)]
+#[derive(
+    Debug, Clone, FromBytes, IntoBytes, Immutable, KnownLayout, Unaligned, PartialEq, CType,
+)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[repr(C, packed)]
 pub struct HeartbeatPayload {
-    pub counter: u32, // Incrementing counter value
+    #[cfg_attr(any(test, feature = "arbitrary"), arbitrary(with = arbitrary_u32))]
+    pub counter: U32, // Incrementing counter value
 }
 
-#[derive(Debug, Clone, FromBytes, IntoBytes, Immutable, PartialEq)]
+#[derive(
+    Debug, Clone, FromBytes, IntoBytes, Immutable, KnownLayout, Unaligned, PartialEq, CType,
+)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[repr(C, packed)]
 pub struct ParameterSetReqPayload {
     pub parameter_id: u8, // Parameter identifier
@@ -83,7 +151,10 @@ pub struct ParameterSetReqPayload {
 }
 
 // Important: only derives TryFromBytes because enum ParameterSetStatus doesn't cover all possible enum variants for u8
-#[derive(Debug, Clone, TryFromBytes, IntoBytes, Immutable, PartialEq)]
+#[derive(
+    Debug, Clone, TryFromBytes, IntoBytes, Immutable, KnownLayout, Unaligned, PartialEq, CType,
+)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[repr(C, packed)]
 pub struct ParameterSetConfirmationPayload {
     pub parameter_id: u8,           // Parameter identifier
@@ -91,27 +162,39 @@ pub struct ParameterSetConfirmationPayload {
     pub value: [u8; 61],            // Confirmed value after set operation
 }
 
-#[derive(Debug, Clone, FromBytes, IntoBytes, Immutable, PartialEq)]
+#[derive(
+    Debug, Clone, FromBytes, IntoBytes, Immutable, KnownLayout, Unaligned, PartialEq, CType,
+)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[repr(C, packed)]
 pub struct FieldGetReqPayload {
     pub field_id: u8, // Field identifier
 }
 
-#[derive(Debug, Clone, FromBytes, IntoBytes, Immutable, PartialEq)]
+#[derive(
+    Debug, Clone, FromBytes, IntoBytes, Immutable, KnownLayout, Unaligned, PartialEq, CType,
+)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[repr(C, packed)]
 pub struct FieldGetResPayload {
     pub field_id: u8,    // Field identifier
     pub value: [u8; 62], // Field value
 }
 
-#[derive(Debug, Clone, FromBytes, IntoBytes, Immutable, PartialEq)]
+#[derive(
+    Debug, Clone, FromBytes, IntoBytes, Immutable, KnownLayout, Unaligned, PartialEq, CType,
+)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[repr(C, packed)]
 pub struct FieldIDLookupReqPayload {
     pub field_name: [u8; 61], // Field name
 }
 
 // Important: only derives TryFromBytes because enum CanDataType doesn't cover all possible enum variants for u8
-#[derive(Debug, Clone, TryFromBytes, IntoBytes, Immutable, PartialEq)]
+#[derive(
+    Debug, Clone, TryFromBytes, IntoBytes, Immutable, KnownLayout, Unaligned, PartialEq, CType,
+)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[repr(C, packed)]
 pub struct FieldIDLookupResPayload {
     pub field_id: u8,            // Field ID
@@ -119,7 +202,10 @@ pub struct FieldIDLookupResPayload {
 }
 
 // Important: only derives TryFromBytes because bool doesn't derive FromBytes
-#[derive(Debug, Clone, TryFromBytes, IntoBytes, Immutable, PartialEq)]
+#[derive(
+    Debug, Clone, TryFromBytes, IntoBytes, Immutable, KnownLayout, Unaligned, PartialEq, CType,
+)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[repr(C, packed)]
 pub struct ParameterSetLockPayload {
     pub parameter_id: u8,                    // Parameter identifier to lock
@@ -139,3 +225,73 @@ static_assertions::const_assert_eq!(size_of::<FieldGetResPayload>(), 63);
 static_assertions::const_assert_eq!(size_of::<FieldIDLookupReqPayload>(), 61);
 static_assertions::const_assert_eq!(size_of::<FieldIDLookupResPayload>(), 2);
 static_assertions::const_assert_eq!(size_of::<ParameterSetLockPayload>(), 2);
+
+#[cfg(any(test, feature = "arbitrary"))]
+fn arbitrary_u32(u: &mut arbitrary::Unstructured) -> arbitrary::Result<U32> {
+    Ok(U32::new(u.arbitrary()?))
+}
+
+impl NodeInfoResPayload {
+    pub fn firmware_hash(&self) -> u32 {
+        self.firmware_hash.get()
+    }
+
+    pub fn set_firmware_hash(&mut self, firmware_hash: u32) {
+        self.firmware_hash.set(firmware_hash);
+    }
+
+    pub fn liquid_hash(&self) -> u32 {
+        self.liquid_hash.get()
+    }
+
+    pub fn set_liquid_hash(&mut self, liquid_hash: u32) {
+        self.liquid_hash.set(liquid_hash);
+    }
+}
+
+impl HeartbeatPayload {
+    pub fn new(counter: u32) -> Self {
+        HeartbeatPayload {
+            counter: counter.into(),
+        }
+    }
+
+    pub fn counter(&self) -> u32 {
+        self.counter.get()
+    }
+
+    pub fn set_counter(&mut self, counter: u32) {
+        self.counter.set(counter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zerocopy::IntoBytes;
+
+    #[test]
+    fn test_multi_byte_fields_are_little_endian() {
+        let mut heartbeat = HeartbeatPayload::new(0x11223344);
+        assert_eq!(heartbeat.as_bytes(), &[0x44, 0x33, 0x22, 0x11]);
+        heartbeat.set_counter(0xA0B0C0D0);
+        assert_eq!(heartbeat.counter(), 0xA0B0C0D0);
+        assert_eq!(heartbeat.as_bytes(), &[0xD0, 0xC0, 0xB0, 0xA0]);
+
+        let mut info = NodeInfoResPayload {
+            tel_count: 1,
+            par_count: 2,
+            firmware_hash: 0.into(),
+            liquid_hash: 0.into(),
+            device_name: [0; 53],
+        };
+        info.set_firmware_hash(0xDEADBEEF);
+        info.set_liquid_hash(0x01020304);
+        assert_eq!(
+            &info.as_bytes()[..10],
+            &[1, 2, 0xEF, 0xBE, 0xAD, 0xDE, 0x04, 0x03, 0x02, 0x01]
+        );
+        assert_eq!(info.firmware_hash(), 0xDEADBEEF);
+        assert_eq!(info.liquid_hash(), 0x01020304);
+    }
+}
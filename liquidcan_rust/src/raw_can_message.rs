@@ -1,10 +1,16 @@
+use crate::can_message::CanMessageType;
+use core::mem::size_of;
+use liquidcan_rust_macros_derive::CType;
 use modular_bitfield::prelude::B5;
 use modular_bitfield::private::static_assertions;
 use modular_bitfield::{Specifier, bitfield};
-use std::mem::size_of;
-use zerocopy_derive::{FromBytes, Immutable, IntoBytes, KnownLayout};
+use zerocopy_derive::{FromZeros, Immutable, IntoBytes, KnownLayout};
 
-#[derive(Specifier, Debug, PartialEq, Eq)]
+/// The highest node ID the 5 bits of a [`CanMessageId`] can address.
+pub const MAX_NODE_ID: u8 = 0x1F;
+
+#[derive(Specifier, Debug, Copy, Clone, PartialEq, Eq, CType)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 pub enum CanMessagePriority {
     Low = 0,
     High = 1,
@@ -21,10 +27,20 @@ pub struct CanMessageId {
     __: B5,
 }
 
-#[derive(Debug, IntoBytes, FromBytes, Immutable, KnownLayout)]
+#[cfg(any(test, feature = "arbitrary"))]
+impl<'a> arbitrary::Arbitrary<'a> for CanMessageId {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(CanMessageId::new()
+            .with_receiver_id(u.int_in_range(0..=0x1F)?)
+            .with_sender_id(u.int_in_range(0..=0x1F)?)
+            .with_priority(u.arbitrary()?))
+    }
+}
+
+#[derive(Debug, Clone, IntoBytes, FromZeros, Immutable, KnownLayout, CType)]
 #[repr(C, packed)]
 pub struct CanMessageFrame {
-    pub message_type: u8,
+    pub message_type: CanMessageType,
     pub data: [u8; 63],
 }
 
@@ -0,0 +1,252 @@
+//! What the nodes on the bus have announced about themselves.
+//!
+//! Nodes announce their info, register their telemetry values and parameters, and define
+//! telemetry groups. The [`NodeRegistry`] records these announcements, so that received values
+//! can be interpreted with the right [`CanDataType`].
+
+use crate::CanMessage;
+use crate::builders::{BuildError, FieldKind, FieldRegistration, NodeInfo};
+use crate::payloads::{CanDataType, TelemetryGroupDefinitionPayload};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// A node's address on the bus, the sender or receiver ID of a [`CanMessageId`](crate::CanMessageId).
+pub type NodeId = u8;
+
+/// The ID of a telemetry value or parameter, unique within its node.
+pub type FieldId = u8;
+
+/// Everything a single node has announced.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeEntry {
+    info: Option<NodeInfo>,
+    fields: BTreeMap<FieldId, FieldRegistration>,
+    groups: BTreeMap<u8, Vec<FieldId>>,
+}
+
+impl NodeEntry {
+    pub fn info(&self) -> Option<&NodeInfo> {
+        self.info.as_ref()
+    }
+
+    pub fn field(&self, field_id: FieldId) -> Option<&FieldRegistration> {
+        self.fields.get(&field_id)
+    }
+
+    /// Returns the registered fields, ordered by ID.
+    pub fn fields(&self) -> impl Iterator<Item = &FieldRegistration> {
+        self.fields.values()
+    }
+
+    /// Whether the node announced its info and registered as many fields as it announced.
+    pub fn is_complete(&self) -> bool {
+        let Some(info) = &self.info else {
+            return false;
+        };
+        let count = |kind| self.fields().filter(|f| f.kind() == kind).count();
+        count(FieldKind::Telemetry) == usize::from(info.tel_count())
+            && count(FieldKind::Parameter) == usize::from(info.par_count())
+    }
+
+    /// Returns the IDs of the fields in a telemetry group, in the order of their values.
+    pub fn group(&self, group_id: u8) -> Option<&[FieldId]> {
+        self.groups.get(&group_id).map(Vec::as_slice)
+    }
+
+    /// Returns the types of the fields in a telemetry group, or `None` if the group or one of
+    /// its fields isn't known.
+    pub fn group_types(&self, group_id: u8) -> Option<Vec<CanDataType>> {
+        self.group(group_id)?
+            .iter()
+            .map(|id| self.field(*id).map(FieldRegistration::data_type))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeRegistry {
+    nodes: BTreeMap<NodeId, NodeEntry>,
+}
+
+impl NodeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the announcement in `msg`, if it is one. Other messages are ignored.
+    ///
+    /// A node that announces its info again has restarted, so everything it registered before
+    /// is forgotten. Values stored for it in a [`FieldStore`](crate::field_store::FieldStore)
+    /// must then be dropped with
+    /// [`FieldStore::forget_node`](crate::field_store::FieldStore::forget_node).
+    pub fn observe(&mut self, sender: NodeId, msg: &CanMessage) -> Result<(), BuildError> {
+        match msg {
+            CanMessage::NodeInfoAnnouncement { payload } => {
+                let info = NodeInfo::try_from(payload)?;
+                self.nodes.insert(
+                    sender,
+                    NodeEntry {
+                        info: Some(info),
+                        ..Default::default()
+                    },
+                );
+            }
+            CanMessage::TelemetryValueRegistration { payload } => {
+                self.register(
+                    sender,
+                    FieldRegistration::from_payload(FieldKind::Telemetry, payload)?,
+                );
+            }
+            CanMessage::ParameterRegistration { payload } => {
+                self.register(
+                    sender,
+                    FieldRegistration::from_payload(FieldKind::Parameter, payload)?,
+                );
+            }
+            CanMessage::TelemetryGroupDefinition { payload } => {
+                for field_id in payload.field_ids() {
+                    FieldKind::Telemetry.check(field_id)?;
+                }
+                self.nodes
+                    .entry(sender)
+                    .or_default()
+                    .groups
+                    .insert(payload.group_id, payload.field_ids());
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub fn register(&mut self, node: NodeId, field: FieldRegistration) {
+        self.nodes
+            .entry(node)
+            .or_default()
+            .fields
+            .insert(field.field_id(), field);
+    }
+
+    pub fn node(&self, node: NodeId) -> Option<&NodeEntry> {
+        self.nodes.get(&node)
+    }
+
+    /// Returns the IDs of all nodes that announced anything, in ascending order.
+    pub fn node_ids(&self) -> Vec<NodeId> {
+        let mut ids: Vec<_> = self.nodes.keys().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    pub fn field(&self, node: NodeId, field_id: FieldId) -> Option<&FieldRegistration> {
+        self.node(node)?.field(field_id)
+    }
+
+    /// Returns the announcements that rebuild this registry when passed to
+    /// [`observe`](Self::observe), ordered by node.
+    pub fn announcements(&self) -> Vec<(NodeId, CanMessage)> {
+        let mut messages = Vec::new();
+        for node in self.node_ids() {
+            let entry = &self.nodes[&node];
+            if let Some(info) = &entry.info {
+                messages.push((node, info.to_message()));
+            }
+            for field in entry.fields() {
+                messages.push((node, field.to_message()));
+            }
+            for (&group_id, field_ids) in &entry.groups {
+                let payload = TelemetryGroupDefinitionPayload::new(group_id, field_ids)
+                    .expect("fit into a definition before");
+                messages.push((node, CanMessage::TelemetryGroupDefinition { payload }));
+            }
+        }
+        messages
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A registry with node 3, which has two telemetry values in group 1 and a parameter.
+    pub(crate) fn example_registry() -> NodeRegistry {
+        let mut registry = NodeRegistry::new();
+        let messages = [
+            NodeInfo::builder()
+                .name("tank")
+                .tel_count(2)
+                .par_count(1)
+                .build()
+                .unwrap()
+                .to_message(),
+            FieldRegistration::telemetry(0x81, CanDataType::Float32, "tank_pressure")
+                .unwrap()
+                .to_message(),
+            FieldRegistration::telemetry(0x82, CanDataType::Boolean, "vent_open")
+                .unwrap()
+                .to_message(),
+            FieldRegistration::parameter(0x01, CanDataType::UInt16, "fill_target")
+                .unwrap()
+                .to_message(),
+            CanMessage::TelemetryGroupDefinition {
+                payload: TelemetryGroupDefinitionPayload::new(1, &[0x81, 0x82]).unwrap(),
+            },
+        ];
+        for msg in &messages {
+            registry.observe(3, msg).unwrap();
+        }
+        registry
+    }
+
+    #[test]
+    fn test_observe() {
+        let registry = example_registry();
+        assert_eq!(registry.node_ids(), vec![3]);
+        let node = registry.node(3).unwrap();
+        assert_eq!(node.info().unwrap().device_name(), "tank");
+        assert_eq!(node.fields().count(), 3);
+        assert!(node.is_complete());
+        assert_eq!(registry.field(3, 0x81).unwrap().name(), "tank_pressure");
+        assert_eq!(
+            node.group_types(1),
+            Some(vec![CanDataType::Float32, CanDataType::Boolean])
+        );
+        assert_eq!(node.group_types(2), None);
+        assert!(registry.field(4, 0x81).is_none());
+    }
+
+    #[test]
+    fn test_node_info_resets_node() {
+        let mut registry = example_registry();
+        let info = NodeInfo::builder().name("tank").build().unwrap();
+        registry.observe(3, &info.to_message()).unwrap();
+        assert_eq!(registry.node(3).unwrap().fields().count(), 0);
+        assert!(registry.node(3).unwrap().is_complete());
+    }
+
+    #[test]
+    fn test_announcements_rebuild_registry() {
+        let registry = example_registry();
+        let mut rebuilt = NodeRegistry::new();
+        for (node, msg) in registry.announcements() {
+            rebuilt.observe(node, &msg).unwrap();
+        }
+        assert_eq!(rebuilt, registry);
+    }
+
+    #[test]
+    fn test_rejects_invalid_announcements() {
+        let mut registry = NodeRegistry::new();
+        let mut payload = FieldRegistration::telemetry(0x81, CanDataType::UInt8, "x")
+            .unwrap()
+            .to_payload();
+        payload.field_id = 0x01;
+        assert_eq!(
+            registry.observe(3, &CanMessage::TelemetryValueRegistration { payload }),
+            Err(BuildError::WrongFieldIdKind {
+                field_id: 0x01,
+                expected: FieldKind::Telemetry
+            })
+        );
+        assert!(registry.node(3).is_none());
+    }
+}
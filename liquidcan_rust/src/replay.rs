@@ -0,0 +1,283 @@
+//! Replaying recorded captures onto a bus.
+//!
+//! A [`Replay`] sends the frames of a `.lcanlog` or `candump` capture over any [`Transport`],
+//! e.g. a SocketCAN interface like `vcan0` or a [`VirtualBus`](crate::transport::virtual_bus)
+//! endpoint, keeping the original gaps between frames scaled by a speed factor. Frames can be
+//! filtered by node and their sender and receiver IDs rewritten, e.g. to replay a recording of
+//! node 3 as node 5. The data bytes are sent exactly as recorded.
+//!
+//! The replay isn't identical at the frame level, though. Captures don't keep the frame format,
+//! so the transport decides it, e.g. SocketCAN sends every frame as a CAN FD frame with bit rate
+//! switching like LiquidCAN nodes do, also frames that were recorded as classic CAN frames.
+
+use crate::candump::{self, CandumpError};
+use crate::lcanlog::{LogError, LogFrame, LogReader};
+use crate::raw_can_message::MAX_NODE_ID;
+use crate::registry::NodeId;
+use crate::transport::{BusFrame, Transport};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufRead, Read, Seek};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Reads all frames of a log from its current position.
+pub fn frames_from_log<R: Read + Seek>(
+    reader: &mut LogReader<R>,
+) -> Result<Vec<LogFrame>, LogError> {
+    let mut frames = Vec::new();
+    while let Some(frame) = reader.next_frame()? {
+        frames.push(frame);
+    }
+    Ok(frames)
+}
+
+/// Reads all frames of a `candump` capture, timestamped as by [`candump::parse_timed`].
+pub fn frames_from_candump(reader: impl BufRead) -> Result<Vec<LogFrame>, CandumpError> {
+    candump::parse_timed(reader)
+        .map(|(timestamp, frame)| {
+            Ok(LogFrame {
+                timestamp,
+                frame: frame?,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplayStats {
+    pub sent: usize,
+    /// Frames dropped by the node filters.
+    pub skipped: usize,
+}
+
+/// Settings for replaying a capture.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    speed: f64,
+    senders: Option<BTreeSet<NodeId>>,
+    receivers: Option<BTreeSet<NodeId>>,
+    sender_rewrites: BTreeMap<NodeId, NodeId>,
+    receiver_rewrites: BTreeMap<NodeId, NodeId>,
+}
+
+impl Default for Replay {
+    fn default() -> Self {
+        Replay {
+            speed: 1.0,
+            senders: None,
+            receivers: None,
+            sender_rewrites: BTreeMap::new(),
+            receiver_rewrites: BTreeMap::new(),
+        }
+    }
+}
+
+impl Replay {
+    /// Replays all frames at the original speed.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Scales the playback speed, 2.0 replays twice as fast. `f64::INFINITY` sends all frames
+    /// without waiting. Gaps that get too long to represent when slowed down are capped at
+    /// [`Duration::MAX`].
+    pub fn speed(mut self, factor: f64) -> Self {
+        assert!(factor > 0.0, "the speed factor must be positive");
+        self.speed = factor;
+        self
+    }
+
+    /// Only replays frames sent by one of these nodes.
+    pub fn senders(mut self, nodes: impl IntoIterator<Item = NodeId>) -> Self {
+        self.senders = Some(nodes.into_iter().collect());
+        self
+    }
+
+    /// Only replays frames addressed to one of these nodes.
+    pub fn receivers(mut self, nodes: impl IntoIterator<Item = NodeId>) -> Self {
+        self.receivers = Some(nodes.into_iter().collect());
+        self
+    }
+
+    /// Sends frames from node `from` as node `to`. Filters apply to the recorded IDs.
+    ///
+    /// # Panics
+    ///
+    /// If `from` or `to` is above [`MAX_NODE_ID`].
+    pub fn rewrite_sender(mut self, from: NodeId, to: NodeId) -> Self {
+        check_node_ids(from, to);
+        self.sender_rewrites.insert(from, to);
+        self
+    }
+
+    /// Sends frames addressed to node `from` to node `to`. Filters apply to the recorded IDs.
+    ///
+    /// # Panics
+    ///
+    /// If `from` or `to` is above [`MAX_NODE_ID`].
+    pub fn rewrite_receiver(mut self, from: NodeId, to: NodeId) -> Self {
+        check_node_ids(from, to);
+        self.receiver_rewrites.insert(from, to);
+        self
+    }
+
+    /// Applies the filters and rewrites to a recorded frame, returns `None` if it is skipped.
+    pub fn map_frame(&self, frame: &BusFrame) -> Option<BusFrame> {
+        let sender = frame.id.sender_id();
+        let receiver = frame.id.receiver_id();
+        let selected = |filter: &Option<BTreeSet<NodeId>>, node| {
+            filter.as_ref().is_none_or(|nodes| nodes.contains(&node))
+        };
+        if !selected(&self.senders, sender) || !selected(&self.receivers, receiver) {
+            return None;
+        }
+        let mut mapped = frame.clone();
+        mapped.id = frame
+            .id
+            .with_sender_id(*self.sender_rewrites.get(&sender).unwrap_or(&sender))
+            .with_receiver_id(*self.receiver_rewrites.get(&receiver).unwrap_or(&receiver));
+        Some(mapped)
+    }
+
+    /// Returns the frames to send together with when to send them, relative to the first
+    /// recorded frame and scaled by the speed factor. Frames must be ordered by timestamp.
+    pub fn schedule(
+        &self,
+        frames: impl IntoIterator<Item = LogFrame>,
+    ) -> impl Iterator<Item = Option<(Duration, BusFrame)>> {
+        let mut start = None;
+        frames.into_iter().map(move |frame| {
+            let start = *start.get_or_insert(frame.timestamp);
+            let offset = frame.timestamp.saturating_sub(start).as_secs_f64() / self.speed;
+            let offset = Duration::try_from_secs_f64(offset).unwrap_or(Duration::MAX);
+            self.map_frame(&frame.frame).map(|mapped| (offset, mapped))
+        })
+    }
+
+    /// Sends the frames over the transport, sleeping between them to keep the recorded timing.
+    /// Frames must be ordered by timestamp.
+    pub fn run<T: Transport>(
+        &self,
+        transport: &mut T,
+        frames: impl IntoIterator<Item = LogFrame>,
+    ) -> Result<ReplayStats, T::Error> {
+        let started = Instant::now();
+        let mut stats = ReplayStats::default();
+        for scheduled in self.schedule(frames) {
+            let Some((offset, frame)) = scheduled else {
+                stats.skipped += 1;
+                continue;
+            };
+            let wait = offset.saturating_sub(started.elapsed());
+            if !wait.is_zero() {
+                thread::sleep(wait);
+            }
+            transport.send_frame(&frame)?;
+            stats.sent += 1;
+        }
+        Ok(stats)
+    }
+}
+
+fn check_node_ids(from: NodeId, to: NodeId) {
+    for node in [from, to] {
+        assert!(node <= MAX_NODE_ID, "Node ID {node} is out of range");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CanMessageId;
+    use crate::transport::virtual_bus::VirtualBus;
+
+    fn frame(millis: u64, sender: NodeId, receiver: NodeId, data: &[u8]) -> LogFrame {
+        let id = CanMessageId::new()
+            .with_sender_id(sender)
+            .with_receiver_id(receiver);
+        LogFrame {
+            timestamp: Duration::from_millis(millis),
+            frame: BusFrame::new(id, data).unwrap(),
+        }
+    }
+
+    fn capture() -> Vec<LogFrame> {
+        vec![
+            frame(1000, 3, 0, &[0x1F, 1, 2]),
+            frame(1010, 4, 0, &[0x1F, 3]),
+            frame(1040, 3, 0, &[0x1F, 4, 5, 6]),
+            frame(1060, 0, 3, &[0x05]),
+        ]
+    }
+
+    #[test]
+    fn test_schedule() {
+        let replay = Replay::new().speed(2.0).senders([3]);
+        let scheduled: Vec<_> = replay.schedule(capture()).collect();
+        assert_eq!(scheduled.len(), 4);
+        assert_eq!(
+            scheduled[0],
+            Some((Duration::ZERO, capture()[0].frame.clone()))
+        );
+        assert_eq!(scheduled[1], None);
+        assert_eq!(scheduled[2].as_ref().unwrap().0, Duration::from_millis(20));
+        assert_eq!(scheduled[3], None);
+
+        let scheduled: Vec<_> = Replay::new().speed(1e-300).schedule(capture()).collect();
+        assert_eq!(scheduled[0].as_ref().unwrap().0, Duration::ZERO);
+        assert_eq!(scheduled[1].as_ref().unwrap().0, Duration::MAX);
+    }
+
+    #[test]
+    fn test_rewrites_ids_only() {
+        let replay = Replay::new()
+            .receivers([0])
+            .rewrite_sender(3, 5)
+            .rewrite_receiver(0, 1);
+        let original = &capture()[2].frame;
+        let mapped = replay.map_frame(original).unwrap();
+        assert_eq!(mapped.id.sender_id(), 5);
+        assert_eq!(mapped.id.receiver_id(), 1);
+        assert_eq!(mapped.id.priority(), original.id.priority());
+        assert_eq!(mapped.data(), original.data());
+        assert_eq!(replay.map_frame(&capture()[3].frame), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Node ID 32 is out of range")]
+    fn test_rejects_invalid_rewrite() {
+        let _ = Replay::new().rewrite_sender(3, 32);
+    }
+
+    #[test]
+    fn test_replays_onto_bus() {
+        let bus = VirtualBus::new();
+        let mut player = bus.connect();
+        let mut listener = bus.connect();
+
+        let started = Instant::now();
+        let stats = Replay::new()
+            .speed(4.0)
+            .run(&mut player, capture())
+            .unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(15));
+        assert_eq!(
+            stats,
+            ReplayStats {
+                sent: 4,
+                skipped: 0
+            }
+        );
+        for recorded in capture() {
+            assert_eq!(listener.receive_frame().unwrap(), recorded.frame);
+        }
+    }
+
+    #[test]
+    fn test_frames_from_candump() {
+        let capture = "(1.5) vcan0 023#1F00\n  vcan0  023  [1]  1F\n(2.0) vcan0 023#1F\n";
+        let frames = frames_from_candump(capture.as_bytes()).unwrap();
+        let timestamps: Vec<_> = frames.iter().map(|f| f.timestamp.as_millis()).collect();
+        assert_eq!(timestamps, vec![1500, 1500, 2000]);
+    }
+}
@@ -0,0 +1,456 @@
+//! Resolving field names to IDs with `FieldIDLookupReq`.
+//!
+//! A node that wants to read or set a field of another node only knows the field's name. It asks
+//! the other node with a `FieldIDLookupReq` and gets the ID and type in the `FieldIDLookupRes`,
+//! where ID 0 means that there's no field with that name. The response doesn't repeat the name,
+//! so the [`FieldNameResolver`] only has one lookup per node in flight and queues the others
+//! until the response arrives.
+
+use crate::builders::BuildError;
+use crate::payloads::{CanDataType, FieldIDLookupReqPayload};
+use crate::raw_can_message::MAX_NODE_ID;
+use crate::registry::{FieldId, NodeId};
+use crate::{CanMessage, CanMessageId};
+use std::collections::{HashMap, VecDeque};
+use std::task::Poll;
+
+/// The ID and type of a field, as returned by a lookup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResolvedField {
+    pub field_id: FieldId,
+    pub data_type: CanDataType,
+}
+
+/// Caches the results of name lookups in both directions.
+///
+/// Lookups that found nothing are cached as well. The cache of a node is cleared when it
+/// announces its info again, because it may have restarted with different firmware.
+#[derive(Debug, Clone)]
+pub struct FieldNameResolver {
+    own_id: NodeId,
+    names: HashMap<(NodeId, String), Option<ResolvedField>>,
+    ids: HashMap<(NodeId, FieldId), String>,
+    /// Lookups per node in the order they were started, with their requests. Only the first one
+    /// has been requested.
+    pending: HashMap<NodeId, VecDeque<(String, CanMessage)>>,
+    requests: VecDeque<(CanMessageId, CanMessage)>,
+}
+
+impl FieldNameResolver {
+    /// Creates a resolver that sends its requests from the node `own_id`.
+    ///
+    /// # Panics
+    ///
+    /// If `own_id` is above [`MAX_NODE_ID`].
+    pub fn new(own_id: NodeId) -> Self {
+        assert!(own_id <= MAX_NODE_ID, "Node ID {own_id} is out of range");
+        FieldNameResolver {
+            own_id,
+            names: HashMap::new(),
+            ids: HashMap::new(),
+            pending: HashMap::new(),
+            requests: VecDeque::new(),
+        }
+    }
+
+    /// Returns the cached result of looking up `name` on `node`, or starts the lookup.
+    ///
+    /// When this returns [`Poll::Pending`], a request may have been queued, which has to be
+    /// sent with [`take_requests`](Self::take_requests). The result becomes available once the
+    /// response is passed to [`handle`](Self::handle). Lookups on a node that is still
+    /// answering another one are only requested after that response arrived. A `node` above
+    /// [`MAX_NODE_ID`] is rejected with [`BuildError::InvalidNodeId`].
+    pub fn poll_lookup(
+        &mut self,
+        node: NodeId,
+        name: &str,
+    ) -> Result<Poll<Option<ResolvedField>>, BuildError> {
+        if node > MAX_NODE_ID {
+            return Err(BuildError::InvalidNodeId(node));
+        }
+        if let Some(result) = self.names.get(&(node, name.to_string())) {
+            return Ok(Poll::Ready(*result));
+        }
+        let pending = self.pending.entry(node).or_default();
+        if !pending.iter().any(|(pending, _)| pending == name) {
+            let payload = FieldIDLookupReqPayload::new(name)?;
+            pending.push_back((name.to_string(), CanMessage::FieldIDLookupReq { payload }));
+            if pending.len() == 1 {
+                self.request_next(node);
+            }
+        }
+        Ok(Poll::Pending)
+    }
+
+    /// Queues the request of the first pending lookup on `node`.
+    fn request_next(&mut self, node: NodeId) {
+        if let Some((_, msg)) = self.pending.get(&node).and_then(VecDeque::front) {
+            self.requests
+                .push_back((msg.id(self.own_id, node), msg.clone()));
+        }
+    }
+
+    /// Gives up on looking up `name` on `node`, e.g. because the response got lost, and
+    /// requests the next lookup on that node.
+    ///
+    /// A response that still arrives for a cancelled request can't be told apart from the
+    /// response to the next one, so only cancel after the node had plenty of time to answer.
+    pub fn cancel(&mut self, node: NodeId, name: &str) {
+        let Some(pending) = self.pending.get_mut(&node) else {
+            return;
+        };
+        let Some(index) = pending.iter().position(|(pending, _)| pending == name) else {
+            return;
+        };
+        pending.remove(index);
+        if index == 0 {
+            self.requests.retain(|(id, _)| id.receiver_id() != node);
+            self.request_next(node);
+        }
+    }
+
+    /// Removes and returns the requests that have to be sent.
+    pub fn take_requests(&mut self) -> impl Iterator<Item = (CanMessageId, CanMessage)> + '_ {
+        self.requests.drain(..)
+    }
+
+    /// Updates the cache with a received message. Messages that aren't relevant are ignored.
+    pub fn handle(&mut self, id: CanMessageId, msg: &CanMessage) {
+        let node = id.sender_id();
+        match msg {
+            CanMessage::FieldIDLookupRes { payload } if id.receiver_id() == self.own_id => {
+                let Some((name, _)) = self.pending.get_mut(&node).and_then(VecDeque::pop_front)
+                else {
+                    return;
+                };
+                self.request_next(node);
+                let result = (payload.field_id != 0).then_some(ResolvedField {
+                    field_id: payload.field_id,
+                    data_type: payload.field_type,
+                });
+                if let Some(field) = result {
+                    self.ids.insert((node, field.field_id), name.clone());
+                }
+                self.names.insert((node, name), result);
+            }
+            CanMessage::NodeInfoAnnouncement { .. } => self.invalidate(node),
+            _ => {}
+        }
+    }
+
+    /// Forgets everything known about `node`, including lookups still waiting for a response.
+    pub fn invalidate(&mut self, node: NodeId) {
+        self.names.retain(|(n, _), _| *n != node);
+        self.ids.retain(|(n, _), _| *n != node);
+        self.pending.remove(&node);
+        self.requests.retain(|(id, _)| id.receiver_id() != node);
+    }
+
+    /// Returns the name of a field that was resolved before.
+    pub fn name_of(&self, node: NodeId, field_id: FieldId) -> Option<&str> {
+        self.ids.get(&(node, field_id)).map(String::as_str)
+    }
+
+    /// Resolves `name` on `node`, sending the request and waiting for the response if the
+    /// result isn't cached.
+    ///
+    /// All messages received while waiting are passed to [`handle`](Self::handle) and then
+    /// dropped, frames that fail to decode are skipped. Use `tokio::time::timeout` to give up on
+    /// nodes that don't respond, dropping the future [cancels](Self::cancel) the lookup.
+    #[cfg(feature = "tokio")]
+    pub async fn lookup<T: crate::transport::AsyncTransport>(
+        &mut self,
+        transport: &mut T,
+        node: NodeId,
+        name: &str,
+    ) -> Result<Option<ResolvedField>, LookupError<T::Error>> {
+        use crate::transport::TransportError;
+
+        /// Cancels the lookup when it finishes or the future is dropped. Finished lookups
+        /// aren't pending anymore, so only unfinished ones are affected.
+        struct CancelOnDrop<'a> {
+            resolver: &'a mut FieldNameResolver,
+            node: NodeId,
+            name: &'a str,
+        }
+
+        impl Drop for CancelOnDrop<'_> {
+            fn drop(&mut self) {
+                self.resolver.cancel(self.node, self.name);
+            }
+        }
+
+        let guard = CancelOnDrop {
+            resolver: self,
+            node,
+            name,
+        };
+        loop {
+            if let Poll::Ready(result) = guard.resolver.poll_lookup(node, name)? {
+                return Ok(result);
+            }
+            let requests: Vec<_> = guard.resolver.take_requests().collect();
+            for (id, msg) in requests {
+                transport.send(id, msg).await.map_err(LookupError::Bus)?;
+            }
+            match transport.receive().await {
+                Ok((id, msg)) => guard.resolver.handle(id, &msg),
+                Err(TransportError::Decode { .. }) => {}
+                Err(TransportError::Bus(e)) => return Err(LookupError::Bus(e)),
+            }
+        }
+    }
+}
+
+/// Errors returned by [`FieldNameResolver::lookup`].
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub enum LookupError<E> {
+    /// The name can't be sent in a request.
+    InvalidName(BuildError),
+    /// The underlying bus failed.
+    Bus(E),
+}
+
+#[cfg(feature = "tokio")]
+impl<E> From<BuildError> for LookupError<E> {
+    fn from(error: BuildError) -> Self {
+        LookupError::InvalidName(error)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<E: std::fmt::Display> std::fmt::Display for LookupError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LookupError::InvalidName(e) => write!(f, "{e}"),
+            LookupError::Bus(e) => write!(f, "Bus error: {e}"),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<E: std::fmt::Debug + std::fmt::Display> std::error::Error for LookupError<E> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builders::NodeInfo;
+    use crate::payloads::FieldIDLookupResPayload;
+
+    fn response(from: NodeId, to: NodeId, field_id: FieldId) -> (CanMessageId, CanMessage) {
+        let id = CanMessageId::new()
+            .with_sender_id(from)
+            .with_receiver_id(to);
+        let msg = CanMessage::FieldIDLookupRes {
+            payload: FieldIDLookupResPayload {
+                field_id,
+                field_type: CanDataType::Float32,
+            },
+        };
+        (id, msg)
+    }
+
+    #[test]
+    fn test_poll_lookup() {
+        let mut resolver = FieldNameResolver::new(1);
+        assert_eq!(resolver.poll_lookup(3, "tank_pressure"), Ok(Poll::Pending));
+        assert_eq!(resolver.poll_lookup(3, "vent_open"), Ok(Poll::Pending));
+        // A lookup that is already waiting isn't requested again.
+        assert_eq!(resolver.poll_lookup(3, "tank_pressure"), Ok(Poll::Pending));
+
+        // Only the first lookup on the node is requested...
+        let requests: Vec<_> = resolver.take_requests().collect();
+        assert_eq!(requests.len(), 1);
+        let (id, CanMessage::FieldIDLookupReq { payload }) = &requests[0] else {
+            panic!("unexpected request {:?}", requests[0]);
+        };
+        assert_eq!((id.sender_id(), id.receiver_id()), (1, 3));
+        assert_eq!(&payload.field_name[..14], b"tank_pressure\0");
+
+        // ...and the next one once it is answered.
+        let (id, msg) = response(3, 1, 0x81);
+        resolver.handle(id, &msg);
+        let requests: Vec<_> = resolver.take_requests().collect();
+        assert_eq!(requests.len(), 1);
+        let (_, CanMessage::FieldIDLookupReq { payload }) = &requests[0] else {
+            panic!("unexpected request {:?}", requests[0]);
+        };
+        assert_eq!(&payload.field_name[..10], b"vent_open\0");
+        let (id, msg) = response(3, 1, 0);
+        resolver.handle(id, &msg);
+
+        let found = ResolvedField {
+            field_id: 0x81,
+            data_type: CanDataType::Float32,
+        };
+        assert_eq!(
+            resolver.poll_lookup(3, "tank_pressure"),
+            Ok(Poll::Ready(Some(found)))
+        );
+        assert_eq!(resolver.poll_lookup(3, "vent_open"), Ok(Poll::Ready(None)));
+        assert_eq!(resolver.name_of(3, 0x81), Some("tank_pressure"));
+        assert_eq!(resolver.take_requests().count(), 0);
+    }
+
+    #[test]
+    fn test_lookups_on_different_nodes() {
+        let mut resolver = FieldNameResolver::new(1);
+        let _ = resolver.poll_lookup(3, "a");
+        let _ = resolver.poll_lookup(3, "b");
+        let _ = resolver.poll_lookup(4, "a");
+        let receivers: Vec<_> = resolver
+            .take_requests()
+            .map(|(id, _)| id.receiver_id())
+            .collect();
+        assert_eq!(receivers, vec![3, 4]);
+    }
+
+    #[test]
+    fn test_lost_response() {
+        let mut resolver = FieldNameResolver::new(1);
+        let _ = resolver.poll_lookup(3, "a");
+        let _ = resolver.poll_lookup(3, "b");
+        assert_eq!(resolver.take_requests().count(), 1);
+
+        // The response for "a" never arrives, so the caller gives up on it.
+        resolver.cancel(3, "a");
+        let requests: Vec<_> = resolver.take_requests().collect();
+        assert_eq!(requests.len(), 1);
+        let (_, CanMessage::FieldIDLookupReq { payload }) = &requests[0] else {
+            panic!("unexpected request {:?}", requests[0]);
+        };
+        assert_eq!(&payload.field_name[..2], b"b\0");
+
+        let (id, msg) = response(3, 1, 0x82);
+        resolver.handle(id, &msg);
+        assert_eq!(
+            resolver.poll_lookup(3, "b"),
+            Ok(Poll::Ready(Some(ResolvedField {
+                field_id: 0x82,
+                data_type: CanDataType::Float32,
+            })))
+        );
+        assert_eq!(resolver.name_of(3, 0x82), Some("b"));
+        // "a" isn't cached and is requested again.
+        assert_eq!(resolver.poll_lookup(3, "a"), Ok(Poll::Pending));
+        assert_eq!(resolver.take_requests().count(), 1);
+    }
+
+    #[test]
+    fn test_cancel_queued() {
+        let mut resolver = FieldNameResolver::new(1);
+        let _ = resolver.poll_lookup(3, "a");
+        let _ = resolver.poll_lookup(3, "b");
+        resolver.cancel(3, "b");
+        resolver.cancel(3, "c");
+        assert_eq!(resolver.take_requests().count(), 1);
+        let (id, msg) = response(3, 1, 0x81);
+        resolver.handle(id, &msg);
+        assert_eq!(resolver.take_requests().count(), 0);
+
+        // Cancelling before the request was taken drops the request.
+        let _ = resolver.poll_lookup(3, "b");
+        resolver.cancel(3, "b");
+        assert_eq!(resolver.take_requests().count(), 0);
+    }
+
+    #[test]
+    fn test_ignores_responses_for_others() {
+        let mut resolver = FieldNameResolver::new(1);
+        assert_eq!(resolver.poll_lookup(3, "a"), Ok(Poll::Pending));
+        let (id, msg) = response(3, 2, 0x81);
+        resolver.handle(id, &msg);
+        let (id, msg) = response(4, 1, 0x81);
+        resolver.handle(id, &msg);
+        assert_eq!(resolver.poll_lookup(3, "a"), Ok(Poll::Pending));
+    }
+
+    #[test]
+    fn test_node_info_invalidates() {
+        let mut resolver = FieldNameResolver::new(1);
+        let _ = resolver.poll_lookup(3, "a");
+        let _ = resolver.poll_lookup(4, "a");
+        assert_eq!(resolver.take_requests().count(), 2);
+        for (id, msg) in [response(3, 1, 0x81), response(4, 1, 0x81)] {
+            resolver.handle(id, &msg);
+        }
+        let _ = resolver.poll_lookup(3, "b");
+
+        let info = NodeInfo::builder().name("tank").build().unwrap();
+        resolver.handle(CanMessageId::new().with_sender_id(3), &info.to_message());
+        assert_eq!(resolver.name_of(3, 0x81), None);
+        assert_eq!(resolver.name_of(4, 0x81), Some("a"));
+        // The request for "b" was dropped together with the rest of node 3.
+        assert_eq!(resolver.take_requests().count(), 0);
+        assert_eq!(resolver.poll_lookup(3, "a"), Ok(Poll::Pending));
+        assert_eq!(resolver.take_requests().count(), 1);
+    }
+
+    #[test]
+    fn test_invalid_name() {
+        let mut resolver = FieldNameResolver::new(1);
+        assert!(resolver.poll_lookup(3, "träge").is_err());
+        assert_eq!(resolver.take_requests().count(), 0);
+    }
+
+    #[test]
+    fn test_invalid_node() {
+        let mut resolver = FieldNameResolver::new(1);
+        assert_eq!(
+            resolver.poll_lookup(32, "temp"),
+            Err(BuildError::InvalidNodeId(32))
+        );
+        assert_eq!(resolver.take_requests().count(), 0);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_async_lookup() {
+        use crate::transport::AsyncTransport;
+        use crate::transport::virtual_bus::VirtualBus;
+
+        let bus = VirtualBus::new();
+        let mut server = bus.connect_async();
+        let mut node = bus.connect_async();
+        let mut resolver = FieldNameResolver::new(1);
+
+        let respond = async {
+            let (id, msg) = node.receive().await.unwrap();
+            assert!(matches!(msg, CanMessage::FieldIDLookupReq { .. }));
+            assert_eq!(id.receiver_id(), 3);
+            let (id, msg) = response(3, 1, 0x81);
+            node.send(id, msg).await.unwrap();
+        };
+        let (result, ()) = tokio::join!(resolver.lookup(&mut server, 3, "tank_pressure"), respond);
+        assert_eq!(result.unwrap().map(|field| field.field_id), Some(0x81));
+        // The second lookup is answered from the cache.
+        let result = resolver.lookup(&mut server, 3, "tank_pressure").await;
+        assert_eq!(result.unwrap().map(|field| field.field_id), Some(0x81));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_dropped_lookup_is_cancelled() {
+        use crate::transport::AsyncTransport;
+        use crate::transport::virtual_bus::VirtualBus;
+
+        let bus = VirtualBus::new();
+        let mut server = bus.connect_async();
+        let mut node = bus.connect_async();
+        let mut resolver = FieldNameResolver::new(1);
+
+        // Gives up as soon as the lookup waits for the response.
+        tokio::select! {
+            biased;
+            _ = resolver.lookup(&mut server, 3, "a") => panic!("lookup finished"),
+            _ = std::future::ready(()) => {}
+        }
+        assert!(matches!(
+            node.receive().await.unwrap().1,
+            CanMessage::FieldIDLookupReq { .. }
+        ));
+        assert_eq!(resolver.poll_lookup(3, "b"), Ok(Poll::Pending));
+        assert_eq!(resolver.take_requests().count(), 1);
+    }
+}
@@ -0,0 +1,360 @@
+//! Re-collecting the registrations of all nodes, e.g. after the server restarted.
+//!
+//! Nodes only register their fields once after coming online, or when they receive a
+//! `NodeInfoReq`. A [`Resync`] sends these requests, records the nodes' announcements in a fresh
+//! [`NodeRegistry`] and reports what's missing once all nodes are done or the timeout is over.
+//! Telemetry groups aren't counted in the node info, so a node is only done once it registered
+//! all fields and then stayed quiet for a while. It doesn't send or receive by itself, so it
+//! works with any transport:
+//!
+//! ```
+//! # use liquidcan_rust::resync::Resync;
+//! # use liquidcan_rust::transport::{Transport, virtual_bus::VirtualBus};
+//! # use std::time::{Duration, Instant};
+//! # let bus = VirtualBus::new();
+//! # let mut server = bus.connect();
+//! let mut resync = Resync::targeted(0, [3, 4], Instant::now(), Duration::from_millis(10));
+//! for (id, msg) in resync.requests() {
+//!     server.send(id, msg).unwrap();
+//! }
+//! while !resync.is_done(Instant::now()) {
+//!     if let Some(frame) = server.receive_frame_timeout(Duration::from_millis(1)) {
+//!         if let Ok(msg) = frame.decode() {
+//!             let _ = resync.handle(frame.id, &msg, Instant::now());
+//!         }
+//!     }
+//! }
+//! let report = resync.finish();
+//! assert_eq!(report.missing, vec![3, 4]);
+//! ```
+
+use crate::builders::BuildError;
+use crate::registry::{NodeId, NodeRegistry};
+use crate::{CanMessage, CanMessageId};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+pub use crate::raw_can_message::MAX_NODE_ID;
+
+/// How long a node has to stay quiet after registering all fields by default, to give it time
+/// to define its telemetry groups.
+pub const DEFAULT_QUIET_PERIOD: Duration = Duration::from_millis(100);
+
+/// A node whose firmware hash differs from the one known before the resync.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FirmwareChange {
+    pub node: NodeId,
+    pub previous: u32,
+    pub current: u32,
+}
+
+/// The outcome of a [`Resync`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResyncReport {
+    /// Everything the nodes announced.
+    pub registry: NodeRegistry,
+    /// Nodes that were expected to answer, but didn't announce their info.
+    pub missing: Vec<NodeId>,
+    /// Nodes that announced their info, but registered fewer fields than announced.
+    pub incomplete: Vec<NodeId>,
+    pub firmware_changes: Vec<FirmwareChange>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Resync {
+    server_id: NodeId,
+    targets: Vec<NodeId>,
+    /// Nodes that have to answer, with their firmware hash from before, if known.
+    expected: BTreeMap<NodeId, Option<u32>>,
+    deadline: Instant,
+    quiet_period: Duration,
+    /// When each node last sent something that is recorded in the registry.
+    last_heard: BTreeMap<NodeId, Instant>,
+    registry: NodeRegistry,
+}
+
+impl Resync {
+    /// Asks every node ID on the bus. Only nodes added with
+    /// [`expect_firmware`](Self::expect_firmware) are reported as missing.
+    ///
+    /// # Panics
+    ///
+    /// If `server_id` is above [`MAX_NODE_ID`].
+    pub fn broadcast(server_id: NodeId, now: Instant, timeout: Duration) -> Self {
+        let targets = (0..=MAX_NODE_ID).filter(|&id| id != server_id);
+        Self::new(server_id, targets.collect(), BTreeMap::new(), now, timeout)
+    }
+
+    /// Asks only the given nodes, which are all reported as missing if they don't answer.
+    ///
+    /// # Panics
+    ///
+    /// If `server_id` or one of the `nodes` is above [`MAX_NODE_ID`].
+    pub fn targeted(
+        server_id: NodeId,
+        nodes: impl IntoIterator<Item = NodeId>,
+        now: Instant,
+        timeout: Duration,
+    ) -> Self {
+        let expected: BTreeMap<_, _> = nodes.into_iter().map(|node| (node, None)).collect();
+        let targets = expected.keys().copied().collect();
+        Self::new(server_id, targets, expected, now, timeout)
+    }
+
+    fn new(
+        server_id: NodeId,
+        targets: Vec<NodeId>,
+        expected: BTreeMap<NodeId, Option<u32>>,
+        now: Instant,
+        timeout: Duration,
+    ) -> Self {
+        for node in targets.iter().chain([&server_id]) {
+            assert!(*node <= MAX_NODE_ID, "Node ID {node} is out of range");
+        }
+        Resync {
+            server_id,
+            targets,
+            expected,
+            deadline: now + timeout,
+            quiet_period: DEFAULT_QUIET_PERIOD,
+            last_heard: BTreeMap::new(),
+            registry: NodeRegistry::new(),
+        }
+    }
+
+    /// Expects `node` to answer with the firmware hash it had before, so changes are reported.
+    ///
+    /// # Panics
+    ///
+    /// If `node` is above [`MAX_NODE_ID`].
+    pub fn expect_firmware(mut self, node: NodeId, firmware_hash: u32) -> Self {
+        assert!(node <= MAX_NODE_ID, "Node ID {node} is out of range");
+        self.expected.insert(node, Some(firmware_hash));
+        if !self.targets.contains(&node) {
+            self.targets.push(node);
+        }
+        self
+    }
+
+    /// Sets how long a node has to stay quiet after registering all fields before it is
+    /// complete.
+    pub fn quiet_period(mut self, quiet_period: Duration) -> Self {
+        self.quiet_period = quiet_period;
+        self
+    }
+
+    /// Returns the `NodeInfoReq`s to send.
+    pub fn requests(&self) -> impl Iterator<Item = (CanMessageId, CanMessage)> + '_ {
+        self.targets.iter().map(|&node| {
+            let msg = CanMessage::NodeInfoReq;
+            (msg.id(self.server_id, node), msg)
+        })
+    }
+
+    /// Records a message received at `now`. Announcements that violate the spec are returned as
+    /// errors and otherwise ignored.
+    pub fn handle(
+        &mut self,
+        id: CanMessageId,
+        msg: &CanMessage,
+        now: Instant,
+    ) -> Result<(), BuildError> {
+        if matches!(
+            msg,
+            CanMessage::NodeInfoAnnouncement { .. }
+                | CanMessage::TelemetryValueRegistration { .. }
+                | CanMessage::ParameterRegistration { .. }
+                | CanMessage::TelemetryGroupDefinition { .. }
+        ) {
+            self.last_heard.insert(id.sender_id(), now);
+        }
+        self.registry.observe(id.sender_id(), msg)
+    }
+
+    /// Whether all expected nodes registered all their fields and didn't announce anything
+    /// during the quiet period before `now`, so they probably defined all their groups too.
+    ///
+    /// Nodes that aren't expected can't be waited for, so for a broadcast it's best to wait
+    /// for the timeout.
+    pub fn is_complete(&self, now: Instant) -> bool {
+        self.expected.keys().all(|&node| {
+            let quiet = self
+                .last_heard
+                .get(&node)
+                .is_some_and(|&heard| now >= heard + self.quiet_period);
+            quiet
+                && self
+                    .registry
+                    .node(node)
+                    .is_some_and(|entry| entry.is_complete())
+        })
+    }
+
+    /// Whether the resync can be finished, because it's complete or timed out.
+    pub fn is_done(&self, now: Instant) -> bool {
+        now >= self.deadline || (!self.expected.is_empty() && self.is_complete(now))
+    }
+
+    pub fn finish(self) -> ResyncReport {
+        let mut missing = Vec::new();
+        let mut firmware_changes = Vec::new();
+        for (&node, &previous) in &self.expected {
+            let Some(info) = self.registry.node(node).and_then(|entry| entry.info()) else {
+                missing.push(node);
+                continue;
+            };
+            if let Some(previous) = previous
+                && previous != info.firmware_hash()
+            {
+                firmware_changes.push(FirmwareChange {
+                    node,
+                    previous,
+                    current: info.firmware_hash(),
+                });
+            }
+        }
+        let incomplete = self
+            .registry
+            .node_ids()
+            .into_iter()
+            .filter(|&node| {
+                self.registry
+                    .node(node)
+                    .is_some_and(|entry| entry.info().is_some() && !entry.is_complete())
+            })
+            .collect();
+        ResyncReport {
+            registry: self.registry,
+            missing,
+            incomplete,
+            firmware_changes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builders::{FieldRegistration, NodeInfo};
+    use crate::payloads::{CanDataType, TelemetryGroupDefinitionPayload};
+
+    fn from(node: NodeId) -> CanMessageId {
+        CanMessageId::new().with_sender_id(node)
+    }
+
+    fn announcement(firmware_hash: u32, tel_count: u8) -> CanMessage {
+        NodeInfo::builder()
+            .name("node")
+            .firmware_hash(firmware_hash)
+            .tel_count(tel_count)
+            .build()
+            .unwrap()
+            .to_message()
+    }
+
+    fn telemetry(field_id: u8) -> CanMessage {
+        FieldRegistration::telemetry(field_id, CanDataType::UInt8, format!("t{field_id}"))
+            .unwrap()
+            .to_message()
+    }
+
+    #[test]
+    fn test_requests() {
+        let now = Instant::now();
+        let resync = Resync::broadcast(0, now, Duration::from_secs(1));
+        let requests: Vec<_> = resync.requests().collect();
+        assert_eq!(requests.len(), 31);
+        assert!(requests.iter().all(|(id, msg)| id.sender_id() == 0
+            && id.receiver_id() != 0
+            && *msg == CanMessage::NodeInfoReq));
+
+        let resync = Resync::targeted(0, [3, 5], now, Duration::from_secs(1)).expect_firmware(7, 1);
+        let receivers: Vec<_> = resync.requests().map(|(id, _)| id.receiver_id()).collect();
+        assert_eq!(receivers, vec![3, 5, 7]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Node ID 32 is out of range")]
+    fn test_rejects_node_32() {
+        Resync::targeted(0, [3, 32], Instant::now(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_completes_when_all_registered() {
+        let now = Instant::now();
+        let mut resync = Resync::targeted(0, [3], now, Duration::from_secs(1));
+        assert!(!resync.is_done(now));
+        resync.handle(from(3), &announcement(1, 2), now).unwrap();
+        resync.handle(from(3), &telemetry(0x81), now).unwrap();
+        assert!(!resync.is_done(now));
+        resync.handle(from(3), &telemetry(0x82), now).unwrap();
+        assert!(!resync.is_done(now));
+        assert!(resync.is_done(now + DEFAULT_QUIET_PERIOD));
+
+        let report = resync.finish();
+        assert!(report.missing.is_empty());
+        assert!(report.incomplete.is_empty());
+        assert_eq!(report.registry.node(3).unwrap().fields().count(), 2);
+    }
+
+    #[test]
+    fn test_waits_for_late_groups() {
+        let start = Instant::now();
+        let quiet = Duration::from_millis(50);
+        let mut resync =
+            Resync::targeted(0, [3], start, Duration::from_secs(1)).quiet_period(quiet);
+        resync.handle(from(3), &announcement(1, 2), start).unwrap();
+        resync.handle(from(3), &telemetry(0x81), start).unwrap();
+        resync.handle(from(3), &telemetry(0x82), start).unwrap();
+
+        // The group is defined after the last registration, but within the quiet period.
+        let defined = start + Duration::from_millis(40);
+        assert!(!resync.is_done(defined));
+        let payload = TelemetryGroupDefinitionPayload::new(1, &[0x81, 0x82]).unwrap();
+        let msg = CanMessage::TelemetryGroupDefinition { payload };
+        resync.handle(from(3), &msg, defined).unwrap();
+        assert!(!resync.is_done(start + quiet));
+        assert!(resync.is_done(defined + quiet));
+
+        let report = resync.finish();
+        assert_eq!(
+            report.registry.node(3).unwrap().group(1),
+            Some(&[0x81, 0x82][..])
+        );
+    }
+
+    #[test]
+    fn test_report_after_timeout() {
+        let now = Instant::now();
+        let timeout = Duration::from_secs(1);
+        let mut resync = Resync::broadcast(0, now, timeout)
+            .expect_firmware(3, 0xAAAA)
+            .expect_firmware(4, 0xBBBB)
+            .expect_firmware(5, 0xCCCC);
+        resync
+            .handle(from(3), &announcement(0xAAAA, 0), now)
+            .unwrap();
+        resync
+            .handle(from(4), &announcement(0xBBBC, 1), now)
+            .unwrap();
+        // Node 9 wasn't known before, but answered the broadcast.
+        resync
+            .handle(from(9), &announcement(0x1234, 0), now)
+            .unwrap();
+        assert!(!resync.is_done(now));
+        assert!(resync.is_done(now + timeout));
+
+        let report = resync.finish();
+        assert_eq!(report.missing, vec![5]);
+        assert_eq!(report.incomplete, vec![4]);
+        assert_eq!(
+            report.firmware_changes,
+            vec![FirmwareChange {
+                node: 4,
+                previous: 0xBBBB,
+                current: 0xBBBC
+            }]
+        );
+        assert_eq!(report.registry.node_ids(), vec![3, 4, 9]);
+    }
+}
@@ -0,0 +1,366 @@
+//! Checks the Rust definitions against the LaTeX sources of the spec.
+//!
+//! The message type table in `sections/08_message_types.tex` and the struct listings and enum
+//! tables in `sections/09_data_structures.tex` are parsed and compared with the [`CType`]
+//! metadata of [`CanMessage`] and the payloads, and with the directions in
+//! [`CanMessageType::metadata`], so neither side can change without the other.
+
+use crate::c_header::{CType, CTypeInfo, CTypeKind};
+use crate::payloads::{CanDataType, ParameterSetStatus};
+use crate::{CanMessage, CanMessageType};
+use std::collections::BTreeMap;
+
+const MESSAGE_TYPES: &str = include_str!("../../sections/08_message_types.tex");
+const DATA_STRUCTURES: &str = include_str!("../../sections/09_data_structures.tex");
+
+/// Differences between the spec and this crate that are known and accepted. The test fails if
+/// one of them is fixed, so it can be removed from here.
+const KNOWN_DEVIATIONS: &[&str] = &[
+    // The spec leaves the last byte of the frame unused, this crate uses it for the value.
+    "field_get_res_t.value: spec has 61 bytes, Rust has 62",
+    // The listings name these fields differently than the tables next to them, which say
+    // `tel_cnt`, `field_id` and `parameter_lock`.
+    "node_info_res_t: spec has fields [\"var_count\", \"par_count\", \"firmware_hash\", \"liquid_hash\", \"device_name\"], Rust has [\"tel_count\", \"par_count\", \"firmware_hash\", \"liquid_hash\", \"device_name\"]",
+    "field_id_lookup_res_t: spec has fields [\"fieldID\", \"field_type\"], Rust has [\"field_id\", \"field_type\"]",
+    "parameter_set_lock_t: spec has fields [\"parameter_id\", \"lock_status\"], Rust has [\"parameter_id\", \"parameter_lock\"]",
+];
+
+/// A row of the message type table.
+#[derive(Debug)]
+struct SpecMessage {
+    discriminant: u8,
+    name: String,
+    /// The label of the payload's subsection, e.g. `NodeInfoRes`.
+    payload: Option<String>,
+    /// E.g. `Server -> Node`.
+    direction: String,
+}
+
+#[derive(Debug, PartialEq)]
+struct SpecField {
+    name: String,
+    size: usize,
+}
+
+#[derive(Debug)]
+struct SpecStruct {
+    c_name: String,
+    fields: Vec<SpecField>,
+}
+
+/// Replaces the LaTeX markup used in table cells by plain text.
+fn plain_text(cell: &str) -> String {
+    let mut text = cell.replace("$\\rightarrow$", "->").replace("\\_", "_");
+    // Drop `\label{...}` and `\ref{...}`, unwrap `\texttt{...}`.
+    while let Some(start) = text.find("\\label{") {
+        let end = start + text[start..].find('}').expect("closed label");
+        text.replace_range(start..=end, "");
+    }
+    text = text.replace("\\texttt{", "").replace('}', "");
+    text.trim().to_string()
+}
+
+/// Returns the argument of the first `\<command>{...}` in `text`.
+fn command_arg<'a>(text: &'a str, command: &str) -> Option<&'a str> {
+    let start = text.find(&format!("\\{command}{{"))? + command.len() + 2;
+    let len = text[start..].find('}')?;
+    Some(&text[start..start + len])
+}
+
+/// Returns the cells of the table rows in `tex` that start with a number.
+fn numbered_rows(tex: &str) -> impl Iterator<Item = Vec<&str>> {
+    tex.lines()
+        .map(|line| {
+            line.trim()
+                .trim_end_matches("\\\\")
+                .split('&')
+                .collect::<Vec<_>>()
+        })
+        .filter(|cells| cells.len() > 1 && cells[0].trim().parse::<u8>().is_ok())
+}
+
+fn parse_message_table(tex: &str) -> Vec<SpecMessage> {
+    numbered_rows(tex)
+        .map(|cells| {
+            assert!(cells.len() >= 4, "Unexpected message type row: {cells:?}");
+            let payload = command_arg(cells[2], "ref")
+                .map(|label| label.strip_prefix("struct:").unwrap_or(label).to_string());
+            SpecMessage {
+                discriminant: cells[0].trim().parse().unwrap(),
+                name: plain_text(cells[1]),
+                payload,
+                direction: plain_text(cells[3]),
+            }
+        })
+        .collect()
+}
+
+/// Expands a direction like `Server/Node -> Node` into `["Node -> Node", "Server -> Node"]`.
+fn parse_directions(direction: &str) -> Vec<String> {
+    let (senders, receivers) = direction
+        .split_once("->")
+        .unwrap_or_else(|| panic!("Unexpected direction `{direction}`"));
+    let mut directions = Vec::new();
+    for sender in senders.split('/') {
+        for receiver in receivers.split('/') {
+            directions.push(format!("{} -> {}", sender.trim(), receiver.trim()));
+        }
+    }
+    directions.sort();
+    directions
+}
+
+/// Splits `tex` into its subsections, keyed by their label.
+fn subsections(tex: &str) -> BTreeMap<&str, &str> {
+    tex.split("\\subsection{")
+        .skip(1)
+        .filter_map(|section| Some((command_arg(section, "label")?, section)))
+        .collect()
+}
+
+fn c_type_size(c_type: &str) -> usize {
+    match c_type {
+        "uint8_t" | "int8_t" | "char" => 1,
+        "uint16_t" | "int16_t" => 2,
+        "uint32_t" | "int32_t" | "float" => 4,
+        other => panic!("Unknown C type `{other}` in the spec"),
+    }
+}
+
+/// Parses the `typedef struct` listing of a subsection.
+fn parse_struct_listing(section: &str) -> Option<SpecStruct> {
+    let start = section.find("\\begin{lstlisting}")?;
+    let end = section.find("\\end{lstlisting}")?;
+    let mut fields = Vec::new();
+    let mut c_name = None;
+    for line in section[start..end].lines().skip(1) {
+        let line = line.split("//").next().unwrap().trim();
+        if line.starts_with("typedef") || line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('}') {
+            c_name = Some(name.trim().trim_end_matches(';').to_string());
+            continue;
+        }
+        let declaration = line.trim_end_matches(';');
+        let (c_type, declarator) = declaration
+            .split_once(char::is_whitespace)
+            .unwrap_or_else(|| panic!("Unexpected struct field `{line}`"));
+        let (name, len) = match declarator.trim().split_once('[') {
+            Some((name, len)) => (name, len.trim_end_matches(']').parse::<usize>().unwrap()),
+            None => (declarator.trim(), 1),
+        };
+        fields.push(SpecField {
+            name: name.to_string(),
+            size: c_type_size(c_type) * len,
+        });
+    }
+    Some(SpecStruct {
+        c_name: c_name?,
+        fields,
+    })
+}
+
+fn rust_fields(info: &CTypeInfo) -> Vec<SpecField> {
+    let CTypeKind::Struct(fields) = info.kind else {
+        panic!("{} isn't a struct", info.rust_name);
+    };
+    fields
+        .iter()
+        .map(|field| SpecField {
+            name: field.name.to_string(),
+            size: field.ty.size * field.array_len.unwrap_or(1),
+        })
+        .collect()
+}
+
+/// Message and type names without case and underscores, `telemetryValue_registration` and
+/// `TelemetryValueRegistration` are the same.
+fn normalize(name: &str) -> String {
+    name.replace('_', "").to_ascii_lowercase()
+}
+
+fn check_struct(spec: &SpecStruct, info: &CTypeInfo, mismatches: &mut Vec<String>) {
+    let c_name = &spec.c_name;
+    if info.c_name() != *c_name {
+        mismatches.push(format!("{c_name}: spec struct, Rust has {}", info.c_name()));
+    }
+    let rust = rust_fields(info);
+    let names = |fields: &[SpecField]| fields.iter().map(|f| f.name.clone()).collect::<Vec<_>>();
+    if names(&spec.fields) != names(&rust) {
+        mismatches.push(format!(
+            "{c_name}: spec has fields {:?}, Rust has {:?}",
+            names(&spec.fields),
+            names(&rust)
+        ));
+        return;
+    }
+    for (spec_field, rust_field) in spec.fields.iter().zip(&rust) {
+        if spec_field.size != rust_field.size {
+            mismatches.push(format!(
+                "{c_name}.{}: spec has {} bytes, Rust has {}",
+                spec_field.name, spec_field.size, rust_field.size
+            ));
+        }
+    }
+}
+
+fn check_enum(
+    sections: &BTreeMap<&str, &str>,
+    label: &str,
+    info: &CTypeInfo,
+    mismatches: &mut Vec<String>,
+) {
+    let CTypeKind::Enum(variants) = info.kind else {
+        panic!("{} isn't an enum", info.rust_name);
+    };
+    let spec: Vec<_> = numbered_rows(sections[label])
+        .map(|cells| (cells[0].trim().parse::<u8>().unwrap(), plain_text(cells[1])))
+        .collect();
+    let rust: Vec<_> = variants
+        .iter()
+        .map(|variant| (variant.value, variant.name.to_string()))
+        .collect();
+    if spec != rust {
+        mismatches.push(format!(
+            "{}: spec has {spec:?}, Rust has {rust:?}",
+            info.rust_name
+        ));
+    }
+}
+
+/// Returns a description of every difference between the spec and the Rust definitions.
+fn check() -> Vec<String> {
+    let mut mismatches = Vec::new();
+    let sections = subsections(DATA_STRUCTURES);
+    let CTypeKind::Message(variants) = CanMessage::C_TYPE.kind else {
+        unreachable!("CanMessage is a message");
+    };
+
+    let spec_messages = parse_message_table(MESSAGE_TYPES);
+    let mut checked_payloads = Vec::new();
+    for message in &spec_messages {
+        let Some(variant) = variants
+            .iter()
+            .find(|v| v.discriminant == message.discriminant)
+        else {
+            mismatches.push(format!(
+                "{}: no variant with discriminant {}",
+                message.name, message.discriminant
+            ));
+            continue;
+        };
+        if normalize(variant.name) != normalize(&message.name) {
+            mismatches.push(format!(
+                "{}: spec name for discriminant {}, Rust has {}",
+                message.name, message.discriminant, variant.name
+            ));
+        }
+        let metadata = CanMessageType::try_from(message.discriminant)
+            .expect("variant exists")
+            .metadata();
+        let mut rust_directions: Vec<_> =
+            metadata.directions.iter().map(|d| d.to_string()).collect();
+        rust_directions.sort();
+        let spec_directions = parse_directions(&message.direction);
+        if spec_directions != rust_directions {
+            mismatches.push(format!(
+                "{}: spec directions {spec_directions:?}, Rust has {rust_directions:?}",
+                message.name
+            ));
+        }
+        match (&message.payload, variant.payload) {
+            (None, None) => {}
+            (Some(label), Some(payload)) => {
+                // Several messages share a payload, compare it only once.
+                if checked_payloads.contains(label) {
+                    continue;
+                }
+                checked_payloads.push(label.clone());
+                let spec = sections
+                    .get(format!("struct:{label}").as_str())
+                    .and_then(|section| parse_struct_listing(section));
+                match spec {
+                    Some(spec) => check_struct(&spec, payload, &mut mismatches),
+                    None => mismatches.push(format!("{label}: no struct listing in the spec")),
+                }
+            }
+            (spec, rust) => mismatches.push(format!(
+                "{}: spec payload {spec:?}, Rust payload {:?}",
+                message.name,
+                rust.map(|p| p.rust_name)
+            )),
+        }
+    }
+    for variant in variants {
+        if !spec_messages
+            .iter()
+            .any(|m| m.discriminant == variant.discriminant)
+        {
+            mismatches.push(format!("{}: missing from the spec", variant.name));
+        }
+    }
+
+    check_enum(
+        &sections,
+        "subsec:DataType",
+        &CanDataType::C_TYPE,
+        &mut mismatches,
+    );
+    check_enum(
+        &sections,
+        "subsec:ParameterSetStatus",
+        &ParameterSetStatus::C_TYPE,
+        &mut mismatches,
+    );
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_message_table() {
+        let messages = parse_message_table(MESSAGE_TYPES);
+        assert_eq!(messages.len(), 19);
+        let lookup = messages.iter().find(|m| m.discriminant == 62).unwrap();
+        assert_eq!(lookup.name, "field_id_lookup_req");
+        assert_eq!(lookup.payload.as_deref(), Some("FieldIDLookupReq"));
+        assert_eq!(lookup.direction, "Node -> Node");
+        let set_req = messages.iter().find(|m| m.discriminant == 50).unwrap();
+        assert_eq!(
+            parse_directions(&set_req.direction),
+            vec!["Node -> Node", "Server -> Node"]
+        );
+        assert_eq!(messages[0].payload, None);
+    }
+
+    #[test]
+    fn test_parse_struct_listing() {
+        let sections = subsections(DATA_STRUCTURES);
+        let spec = parse_struct_listing(sections["struct:NodeInfoRes"]).unwrap();
+        assert_eq!(spec.c_name, "node_info_res_t");
+        let sizes: Vec<_> = spec.fields.iter().map(|f| f.size).collect();
+        assert_eq!(sizes, vec![1, 1, 4, 4, 53]);
+    }
+
+    #[test]
+    fn test_spec_matches_rust() {
+        let mismatches = check();
+        let unexpected: Vec<_> = mismatches
+            .iter()
+            .filter(|m| !KNOWN_DEVIATIONS.contains(&m.as_str()))
+            .collect();
+        assert!(
+            unexpected.is_empty(),
+            "The spec and the Rust definitions differ:\n{unexpected:#?}"
+        );
+        for deviation in KNOWN_DEVIATIONS {
+            assert!(
+                mismatches.iter().any(|m| m == deviation),
+                "`{deviation}` is fixed, remove it from KNOWN_DEVIATIONS"
+            );
+        }
+    }
+}
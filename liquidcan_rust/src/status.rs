@@ -0,0 +1,259 @@
+//! Status messages of all severities as a single kind of event.
+//!
+//! `InfoStatus`, `WarningStatus` and `ErrorStatus` only differ in their message type. A
+//! [`StatusEvent`] carries the severity alongside the text, and a [`StatusAggregator`] collapses
+//! nodes repeating the same status over and over.
+
+use crate::CanMessage;
+use crate::payloads::StatusPayload;
+use crate::registry::NodeId;
+use crate::strings;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// Creates the status message of this severity, cutting `text` off at 62 characters.
+    pub fn message(self, text: &str) -> CanMessage {
+        let payload = StatusPayload::truncated(text);
+        match self {
+            Severity::Info => CanMessage::InfoStatus { payload },
+            Severity::Warning => CanMessage::WarningStatus { payload },
+            Severity::Error => CanMessage::ErrorStatus { payload },
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl StatusPayload {
+    /// Holds as much of `text` as fits, see [`strings::encode_truncated`].
+    pub fn truncated(text: &str) -> Self {
+        StatusPayload {
+            msg: strings::encode_truncated(text),
+        }
+    }
+
+    /// Returns the text, with bytes outside of ASCII replaced by `?`.
+    pub fn text(&self) -> String {
+        strings::decode_lossy(&self.msg)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusEvent {
+    pub node: NodeId,
+    pub severity: Severity,
+    pub text: String,
+    pub timestamp: Instant,
+}
+
+impl StatusEvent {
+    /// Returns the event for a status message, or `None` for other messages.
+    pub fn from_message(node: NodeId, msg: &CanMessage, timestamp: Instant) -> Option<Self> {
+        let (severity, payload) = match msg {
+            CanMessage::InfoStatus { payload } => (Severity::Info, payload),
+            CanMessage::WarningStatus { payload } => (Severity::Warning, payload),
+            CanMessage::ErrorStatus { payload } => (Severity::Error, payload),
+            _ => return None,
+        };
+        Some(StatusEvent {
+            node,
+            severity,
+            text: payload.text(),
+            timestamp,
+        })
+    }
+
+    pub fn to_message(&self) -> CanMessage {
+        self.severity.message(&self.text)
+    }
+}
+
+impl fmt::Display for StatusEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[node {} {}] {}", self.node, self.severity, self.text)
+    }
+}
+
+/// An event passed on by a [`StatusAggregator`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AggregatedStatus {
+    /// The latest of the collapsed events.
+    pub event: StatusEvent,
+    /// How many identical events this stands for, at least 1.
+    pub count: u32,
+}
+
+#[derive(Debug, Clone)]
+struct Repeats {
+    reported_at: Instant,
+    suppressed: Option<(StatusEvent, u32)>,
+}
+
+/// Passes on each distinct status of a node at most once per window.
+///
+/// Identical events, with the same node, severity and text, that arrive within the window after
+/// one was passed on are held back and counted. They are reported together with the next
+/// identical event after the window, or by [`flush`](Self::flush).
+#[derive(Debug, Clone)]
+pub struct StatusAggregator {
+    window: Duration,
+    seen: HashMap<(NodeId, Severity, String), Repeats>,
+}
+
+impl StatusAggregator {
+    pub fn new(window: Duration) -> Self {
+        StatusAggregator {
+            window,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Returns the event to pass on, or `None` if it is a repeat within the window.
+    pub fn push(&mut self, event: StatusEvent) -> Option<AggregatedStatus> {
+        let key = (event.node, event.severity, event.text.clone());
+        let now = event.timestamp;
+        match self.seen.get_mut(&key) {
+            Some(repeats) if now.saturating_duration_since(repeats.reported_at) < self.window => {
+                let count = repeats.suppressed.as_ref().map_or(0, |(_, count)| *count);
+                repeats.suppressed = Some((event, count + 1));
+                None
+            }
+            repeats => {
+                let count = repeats
+                    .and_then(|repeats| repeats.suppressed.take())
+                    .map_or(0, |(_, count)| count);
+                self.seen.insert(
+                    key,
+                    Repeats {
+                        reported_at: now,
+                        suppressed: None,
+                    },
+                );
+                Some(AggregatedStatus {
+                    event,
+                    count: count + 1,
+                })
+            }
+        }
+    }
+
+    /// Reports the events held back in windows that ended before `now`, and forgets statuses
+    /// that weren't repeated.
+    pub fn flush(&mut self, now: Instant) -> Vec<AggregatedStatus> {
+        let mut flushed = Vec::new();
+        self.seen.retain(|_, repeats| {
+            if now.saturating_duration_since(repeats.reported_at) < self.window {
+                return true;
+            }
+            match repeats.suppressed.take() {
+                Some((event, count)) => {
+                    repeats.reported_at = event.timestamp;
+                    flushed.push(AggregatedStatus { event, count });
+                    true
+                }
+                None => false,
+            }
+        });
+        flushed.sort_by_key(|status| status.event.timestamp);
+        flushed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(node: NodeId, text: &str, at: Instant) -> StatusEvent {
+        StatusEvent {
+            node,
+            severity: Severity::Warning,
+            text: text.to_string(),
+            timestamp: at,
+        }
+    }
+
+    #[test]
+    fn test_message_conversion() {
+        let now = Instant::now();
+        let msg = Severity::Error.message("valve stuck");
+        assert!(matches!(msg, CanMessage::ErrorStatus { .. }));
+        let event = StatusEvent::from_message(3, &msg, now).unwrap();
+        assert_eq!(event.severity, Severity::Error);
+        assert_eq!(event.text, "valve stuck");
+        assert_eq!(event.to_string(), "[node 3 error] valve stuck");
+        assert_eq!(event.to_message(), msg);
+        assert_eq!(
+            StatusEvent::from_message(3, &CanMessage::NodeInfoReq, now),
+            None
+        );
+    }
+
+    #[test]
+    fn test_long_text_is_truncated() {
+        let text = "x".repeat(100);
+        let payload = StatusPayload::truncated(&text);
+        assert_eq!(payload.msg[62], 0);
+        assert_eq!(payload.text(), "x".repeat(62));
+    }
+
+    #[test]
+    fn test_aggregator_collapses_repeats() {
+        let start = Instant::now();
+        let ms = |ms| start + Duration::from_millis(ms);
+        let mut aggregator = StatusAggregator::new(Duration::from_millis(100));
+
+        let first = aggregator.push(event(3, "low pressure", ms(0))).unwrap();
+        assert_eq!(first.count, 1);
+        assert_eq!(aggregator.push(event(3, "low pressure", ms(10))), None);
+        assert_eq!(aggregator.push(event(3, "low pressure", ms(20))), None);
+        // Other nodes and texts aren't affected.
+        assert!(aggregator.push(event(4, "low pressure", ms(30))).is_some());
+        assert!(aggregator.push(event(3, "high pressure", ms(30))).is_some());
+
+        let next = aggregator.push(event(3, "low pressure", ms(150))).unwrap();
+        assert_eq!(next.count, 3);
+        assert_eq!(next.event.timestamp, ms(150));
+    }
+
+    #[test]
+    fn test_aggregator_flush() {
+        let start = Instant::now();
+        let ms = |ms| start + Duration::from_millis(ms);
+        let mut aggregator = StatusAggregator::new(Duration::from_millis(100));
+
+        aggregator.push(event(3, "low pressure", ms(0))).unwrap();
+        aggregator.push(event(4, "sensor lost", ms(0))).unwrap();
+        assert_eq!(aggregator.push(event(3, "low pressure", ms(50))), None);
+        assert_eq!(aggregator.flush(ms(60)), vec![]);
+
+        let flushed = aggregator.flush(ms(100));
+        assert_eq!(
+            flushed,
+            vec![AggregatedStatus {
+                event: event(3, "low pressure", ms(50)),
+                count: 1
+            }]
+        );
+        // The flushed event starts a new window.
+        assert_eq!(aggregator.push(event(3, "low pressure", ms(120))), None);
+        // "sensor lost" wasn't repeated and is forgotten, so it's passed on again.
+        assert!(aggregator.push(event(4, "sensor lost", ms(110))).is_some());
+    }
+}
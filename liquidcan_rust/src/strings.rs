@@ -0,0 +1,117 @@
+//! ASCII strings in fixed-size payload fields.
+//!
+//! All strings on the bus are ASCII encoded and null terminated; unused bytes after the terminator
+//! are zero.
+
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringError {
+    /// The string contains characters outside of ASCII.
+    NotAscii,
+    /// The string contains a null byte, which would terminate it early.
+    ContainsNul,
+    /// The string and its null terminator need more than `max + 1` bytes.
+    TooLong { len: usize, max: usize },
+}
+
+impl fmt::Display for StringError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StringError::NotAscii => write!(f, "string is not ASCII"),
+            StringError::ContainsNul => write!(f, "string contains a null byte"),
+            StringError::TooLong { len, max } => {
+                write!(f, "string has {len} characters, at most {max} fit")
+            }
+        }
+    }
+}
+
+impl core::error::Error for StringError {}
+
+/// Encodes `s` into a field of `N` bytes, leaving room for the null terminator.
+pub fn encode<const N: usize>(s: &str) -> Result<[u8; N], StringError> {
+    if !s.is_ascii() {
+        return Err(StringError::NotAscii);
+    }
+    if s.contains('\0') {
+        return Err(StringError::ContainsNul);
+    }
+    if s.len() >= N {
+        return Err(StringError::TooLong {
+            len: s.len(),
+            max: N - 1,
+        });
+    }
+    let mut buf = [0u8; N];
+    buf[..s.len()].copy_from_slice(s.as_bytes());
+    Ok(buf)
+}
+
+/// Encodes as much of `s` as fits into a field of `N` bytes, for text where losing the end is
+/// better than failing, like status messages. Non-ASCII characters become `?` and the text ends
+/// at a null character.
+pub fn encode_truncated<const N: usize>(s: &str) -> [u8; N] {
+    let mut buf = [0u8; N];
+    let chars = s
+        .chars()
+        .take_while(|&c| c != '\0')
+        .map(|c| if c.is_ascii() { c as u8 } else { b'?' });
+    for (byte, c) in buf[..N.saturating_sub(1)].iter_mut().zip(chars) {
+        *byte = c;
+    }
+    buf
+}
+
+/// Decodes the string up to the first null byte. A field without terminator is taken as a whole.
+pub fn decode(bytes: &[u8]) -> Result<&str, StringError> {
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    let s = &bytes[..len];
+    if !s.is_ascii() {
+        return Err(StringError::NotAscii);
+    }
+    Ok(core::str::from_utf8(s).expect("ASCII is valid UTF-8"))
+}
+
+/// Decodes the string up to the first null byte, replacing bytes outside of ASCII with `?`.
+#[cfg(feature = "alloc")]
+pub fn decode_lossy(bytes: &[u8]) -> alloc::string::String {
+    bytes
+        .iter()
+        .take_while(|&&b| b != 0)
+        .map(|&b| if b.is_ascii() { b as char } else { '?' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode::<6>("abc"), Ok(*b"abc\0\0\0"));
+        assert_eq!(encode::<4>("abc"), Ok(*b"abc\0"));
+        assert_eq!(
+            encode::<3>("abc"),
+            Err(StringError::TooLong { len: 3, max: 2 })
+        );
+        assert_eq!(encode::<8>("träge"), Err(StringError::NotAscii));
+        assert_eq!(encode::<8>("a\0b"), Err(StringError::ContainsNul));
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode(b"abc\0\0\0"), Ok("abc"));
+        assert_eq!(decode(b"abc\0def"), Ok("abc"));
+        assert_eq!(decode(b"abc"), Ok("abc"));
+        assert_eq!(decode(b"\xFF\0"), Err(StringError::NotAscii));
+    }
+
+    #[test]
+    fn test_lossy() {
+        assert_eq!(encode_truncated::<4>("abcdef"), *b"abc\0");
+        assert_eq!(encode_truncated::<6>("tr\u{e4}ge"), *b"tr?ge\0");
+        assert_eq!(encode_truncated::<6>("ab\0cd"), *b"ab\0\0\0\0");
+        assert_eq!(decode_lossy(b"tr\xC3\xA4ge\0x"), "tr??ge");
+    }
+}
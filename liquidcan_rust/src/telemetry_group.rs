@@ -0,0 +1,181 @@
+//! Telemetry groups decoded into structs.
+//!
+//! When the layout of a node's group is known ahead of time, `#[derive(TelemetryGroup)]` maps a
+//! struct to the group's values, one field per value in declaration order:
+//!
+//! ```
+//! use liquidcan_rust::telemetry_group::TelemetryGroup;
+//!
+//! #[derive(TelemetryGroup, Debug, PartialEq)]
+//! struct Tank {
+//!     pressure: f32,
+//!     temperature: i16,
+//!     vent_open: bool,
+//! }
+//!
+//! let tank = Tank { pressure: 2.5, temperature: -3, vent_open: true };
+//! let payload = tank.to_payload(1);
+//! assert_eq!(Tank::from_payload(&payload), tank);
+//! ```
+//!
+//! Nothing on the bus says which types a group has, so check the node's group definition with
+//! [`TelemetryGroup::check_definition`] before trusting the decoded values.
+
+use crate::field_value::FieldValue;
+use crate::payloads::{CanDataType, TelemetryGroupUpdatePayload};
+use crate::registry::{FieldId, NodeEntry};
+use alloc::vec::Vec;
+use core::fmt;
+
+pub use liquidcan_rust_macros_derive::TelemetryGroup;
+
+/// A group definition that doesn't match a [`TelemetryGroup`] struct.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupMismatch {
+    /// The node hasn't defined the group.
+    UnknownGroup(u8),
+    /// The group contains a field the node hasn't registered.
+    UnknownField(FieldId),
+    WrongLength {
+        expected: usize,
+        actual: usize,
+    },
+    WrongType {
+        field_id: FieldId,
+        expected: CanDataType,
+        actual: CanDataType,
+    },
+}
+
+impl fmt::Display for GroupMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GroupMismatch::UnknownGroup(group_id) => {
+                write!(f, "Unknown telemetry group {group_id}")
+            }
+            GroupMismatch::UnknownField(field_id) => {
+                write!(f, "Field {field_id:#04x} isn't registered")
+            }
+            GroupMismatch::WrongLength { expected, actual } => {
+                write!(f, "Expected {expected} fields in the group, got {actual}")
+            }
+            GroupMismatch::WrongType {
+                field_id,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Field {field_id:#04x} has type {actual:?}, expected {expected:?}"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for GroupMismatch {}
+
+/// The values of a telemetry group. Implement it with `#[derive(TelemetryGroup)]`.
+pub trait TelemetryGroup: Sized {
+    /// The types of the values, in the order of the group definition.
+    const DATA_TYPES: &'static [CanDataType];
+
+    fn to_values(&self) -> Vec<FieldValue>;
+
+    /// Returns `None` if the number or types of the values don't match.
+    fn from_values(values: &[FieldValue]) -> Option<Self>;
+
+    fn to_payload(&self, group_id: u8) -> TelemetryGroupUpdatePayload {
+        TelemetryGroupUpdatePayload::pack(group_id, &self.to_values())
+            .expect("checked by the derive")
+    }
+
+    fn from_payload(payload: &TelemetryGroupUpdatePayload) -> Self {
+        let values = payload
+            .unpack(Self::DATA_TYPES)
+            .expect("checked by the derive");
+        Self::from_values(&values).expect("values have the right types")
+    }
+
+    /// Checks that a node's definition of the group has the fields of this struct, in the same
+    /// order and with the same types.
+    fn check_definition(node: &NodeEntry, group_id: u8) -> Result<(), GroupMismatch> {
+        let field_ids = node
+            .group(group_id)
+            .ok_or(GroupMismatch::UnknownGroup(group_id))?;
+        if field_ids.len() != Self::DATA_TYPES.len() {
+            return Err(GroupMismatch::WrongLength {
+                expected: Self::DATA_TYPES.len(),
+                actual: field_ids.len(),
+            });
+        }
+        for (&field_id, &expected) in field_ids.iter().zip(Self::DATA_TYPES) {
+            let actual = node
+                .field(field_id)
+                .ok_or(GroupMismatch::UnknownField(field_id))?
+                .data_type();
+            if actual != expected {
+                return Err(GroupMismatch::WrongType {
+                    field_id,
+                    expected,
+                    actual,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::tests::example_registry;
+
+    #[derive(TelemetryGroup, Debug, PartialEq)]
+    struct Tank {
+        pressure: f32,
+        vent_open: bool,
+    }
+
+    #[derive(TelemetryGroup, Debug, PartialEq)]
+    struct Swapped(bool, f32);
+
+    #[test]
+    fn test_round_trip() {
+        let tank = Tank {
+            pressure: 1.5,
+            vent_open: true,
+        };
+        let payload = tank.to_payload(1);
+        assert_eq!(payload.group_id, 1);
+        assert_eq!(&payload.values[..5], &[0x00, 0x00, 0xC0, 0x3F, 1]);
+        assert_eq!(Tank::from_payload(&payload), tank);
+
+        assert_eq!(
+            Swapped::from_values(&[FieldValue::Boolean(false), FieldValue::Float32(2.0)]),
+            Some(Swapped(false, 2.0))
+        );
+        assert_eq!(Swapped::from_values(&[FieldValue::Boolean(false)]), None);
+        assert_eq!(
+            Swapped::from_values(&[FieldValue::Float32(2.0), FieldValue::Boolean(false)]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_check_definition() {
+        let registry = example_registry();
+        let node = registry.node(3).unwrap();
+        assert_eq!(Tank::check_definition(node, 1), Ok(()));
+        assert_eq!(
+            Swapped::check_definition(node, 1),
+            Err(GroupMismatch::WrongType {
+                field_id: 0x81,
+                expected: CanDataType::Boolean,
+                actual: CanDataType::Float32
+            })
+        );
+        assert_eq!(
+            Tank::check_definition(node, 2),
+            Err(GroupMismatch::UnknownGroup(2))
+        );
+    }
+}
@@ -0,0 +1,645 @@
+//! Canonical corpus of encoded frames shared with other LiquidCAN implementations.
+//!
+//! The corpus is checked in as `test-vectors/golden.json` at the repository root. Other
+//! implementations decode and re-encode every entry to prove that they agree with this crate
+//! byte-for-byte, and the unit test below verifies the checked-in file against the current
+//! encoders, so any change of the wire layout shows up as a test failure. Regenerate it with
+//! `cargo run --features test-vectors --bin liquidcan_codegen -- test-vectors
+//! ../test-vectors/golden.json` after an intentional protocol change.
+
+use crate::payloads::*;
+use crate::raw_can_message::CanMessagePriority;
+use crate::{CanMessage, CanMessageFrame, CanMessageId, DecodeError};
+use std::fmt::Write;
+use zerocopy::IntoBytes;
+
+/// Bumped whenever the structure of the JSON file changes.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// A valid message and its expected encoding.
+pub struct FrameVector {
+    pub name: String,
+    pub message: CanMessage,
+}
+
+/// A CAN identifier and its expected 11-bit encoding.
+pub struct IdVector {
+    pub name: String,
+    pub receiver_id: u8,
+    pub sender_id: u8,
+    pub priority: CanMessagePriority,
+}
+
+/// Bytes that every implementation must reject with the given error kind.
+pub struct InvalidVector {
+    pub name: String,
+    pub data: Vec<u8>,
+    pub error: DecodeError,
+}
+
+fn name_bytes<const N: usize>(name: &str) -> [u8; N] {
+    let mut buf = [0u8; N];
+    buf[..name.len()].copy_from_slice(name.as_bytes());
+    buf
+}
+
+fn value_bytes<const N: usize>(value: &[u8]) -> [u8; N] {
+    let mut buf = [0u8; N];
+    buf[..value.len()].copy_from_slice(value);
+    buf
+}
+
+/// Little-endian encodings of the edge values of every [`CanDataType`].
+pub fn data_type_edge_values() -> Vec<(CanDataType, &'static str, Vec<u8>)> {
+    use CanDataType::*;
+    vec![
+        (Float32, "zero", 0.0f32.to_le_bytes().to_vec()),
+        (Float32, "negative_zero", (-0.0f32).to_le_bytes().to_vec()),
+        (Float32, "one", 1.0f32.to_le_bytes().to_vec()),
+        (Float32, "min", f32::MIN.to_le_bytes().to_vec()),
+        (Float32, "max", f32::MAX.to_le_bytes().to_vec()),
+        (
+            Float32,
+            "min_positive",
+            f32::MIN_POSITIVE.to_le_bytes().to_vec(),
+        ),
+        (
+            Float32,
+            "smallest_subnormal",
+            f32::from_bits(1).to_le_bytes().to_vec(),
+        ),
+        (Float32, "infinity", f32::INFINITY.to_le_bytes().to_vec()),
+        (
+            Float32,
+            "negative_infinity",
+            f32::NEG_INFINITY.to_le_bytes().to_vec(),
+        ),
+        (Float32, "nan", f32::NAN.to_le_bytes().to_vec()),
+        (Int32, "min", i32::MIN.to_le_bytes().to_vec()),
+        (Int32, "minus_one", (-1i32).to_le_bytes().to_vec()),
+        (Int32, "max", i32::MAX.to_le_bytes().to_vec()),
+        (Int16, "min", i16::MIN.to_le_bytes().to_vec()),
+        (Int16, "minus_one", (-1i16).to_le_bytes().to_vec()),
+        (Int16, "max", i16::MAX.to_le_bytes().to_vec()),
+        (Int8, "min", i8::MIN.to_le_bytes().to_vec()),
+        (Int8, "minus_one", (-1i8).to_le_bytes().to_vec()),
+        (Int8, "max", i8::MAX.to_le_bytes().to_vec()),
+        (UInt32, "zero", 0u32.to_le_bytes().to_vec()),
+        (UInt32, "max", u32::MAX.to_le_bytes().to_vec()),
+        (UInt16, "zero", 0u16.to_le_bytes().to_vec()),
+        (UInt16, "max", u16::MAX.to_le_bytes().to_vec()),
+        (UInt8, "zero", vec![0]),
+        (UInt8, "max", vec![u8::MAX]),
+        (Boolean, "false", vec![0]),
+        (Boolean, "true", vec![1]),
+    ]
+}
+
+/// One or more vectors for every [`CanMessage`] variant, covering zero, typical and extreme values.
+pub fn frame_vectors() -> Vec<FrameVector> {
+    let mut vectors = Vec::new();
+    let mut push = |name: &str, message: CanMessage| {
+        vectors.push(FrameVector {
+            name: name.to_string(),
+            message,
+        })
+    };
+
+    push("node_info_req", CanMessage::NodeInfoReq);
+    push(
+        "node_info_announcement",
+        CanMessage::NodeInfoAnnouncement {
+            payload: NodeInfoResPayload {
+                tel_count: 12,
+                par_count: 3,
+                firmware_hash: 0xDEADBEEF.into(),
+                liquid_hash: 0x01020304.into(),
+                device_name: name_bytes("ECU-Main"),
+            },
+        },
+    );
+    push(
+        "node_info_announcement_max",
+        CanMessage::NodeInfoAnnouncement {
+            payload: NodeInfoResPayload {
+                tel_count: u8::MAX,
+                par_count: u8::MAX,
+                firmware_hash: u32::MAX.into(),
+                liquid_hash: u32::MAX.into(),
+                device_name: [b'x'; 53],
+            },
+        },
+    );
+
+    let status = StatusPayload {
+        msg: name_bytes("Tank pressure nominal"),
+    };
+    push(
+        "info_status",
+        CanMessage::InfoStatus {
+            payload: status.clone(),
+        },
+    );
+    push(
+        "warning_status",
+        CanMessage::WarningStatus {
+            payload: status.clone(),
+        },
+    );
+    push("error_status", CanMessage::ErrorStatus { payload: status });
+    push(
+        "error_status_full_length",
+        CanMessage::ErrorStatus {
+            payload: StatusPayload { msg: [b'!'; 63] },
+        },
+    );
+
+    for data_type in [
+        CanDataType::Float32,
+        CanDataType::Int32,
+        CanDataType::Int16,
+        CanDataType::Int8,
+        CanDataType::UInt32,
+        CanDataType::UInt16,
+        CanDataType::UInt8,
+        CanDataType::Boolean,
+    ] {
+        push(
+            &format!("telemetry_value_registration_{data_type:?}").to_lowercase(),
+            CanMessage::TelemetryValueRegistration {
+                payload: FieldRegistrationPayload {
+                    field_id: 0x80 | data_type as u8,
+                    field_type: data_type,
+                    field_name: name_bytes("tank_pressure"),
+                },
+            },
+        );
+    }
+    push(
+        "parameter_registration",
+        CanMessage::ParameterRegistration {
+            payload: FieldRegistrationPayload {
+                field_id: 0x01,
+                field_type: CanDataType::Boolean,
+                field_name: name_bytes("valve_open"),
+            },
+        },
+    );
+
+    push(
+        "telemetry_group_definition",
+        CanMessage::TelemetryGroupDefinition {
+            payload: TelemetryGroupDefinitionPayload {
+                group_id: 1,
+                field_ids: value_bytes(&[0x80, 0x81, 0x82]),
+            },
+        },
+    );
+    let mut group_values = Vec::new();
+    group_values.extend_from_slice(&1.5f32.to_le_bytes());
+    group_values.extend_from_slice(&(-2i16).to_le_bytes());
+    group_values.push(1);
+    push(
+        "telemetry_group_update",
+        CanMessage::TelemetryGroupUpdate {
+            payload: TelemetryGroupUpdatePayload {
+                group_id: 1,
+                values: value_bytes(&group_values),
+            },
+        },
+    );
+
+    for (name, counter) in [("zero", 0), ("one", 1), ("max", u32::MAX)] {
+        push(
+            &format!("heartbeat_req_{name}"),
+            CanMessage::HeartbeatReq {
+                payload: HeartbeatPayload::new(counter),
+            },
+        );
+        push(
+            &format!("heartbeat_res_{name}"),
+            CanMessage::HeartbeatRes {
+                payload: HeartbeatPayload::new(counter),
+            },
+        );
+    }
+
+    // Every edge value of every data type, both as a set request and as a get response.
+    for (data_type, edge, value) in data_type_edge_values() {
+        let type_name = format!("{data_type:?}").to_lowercase();
+        push(
+            &format!("parameter_set_req_{type_name}_{edge}"),
+            CanMessage::ParameterSetReq {
+                payload: ParameterSetReqPayload {
+                    parameter_id: 0x01,
+                    value: value_bytes(&value),
+                },
+            },
+        );
+        push(
+            &format!("field_get_res_{type_name}_{edge}"),
+            CanMessage::FieldGetRes {
+                payload: FieldGetResPayload {
+                    field_id: 0x80,
+                    value: value_bytes(&value),
+                },
+            },
+        );
+    }
+
+    for status in [
+        ParameterSetStatus::Success,
+        ParameterSetStatus::InvalidParameterID,
+        ParameterSetStatus::ParameterLocked,
+        ParameterSetStatus::NodeToNodeModification,
+    ] {
+        push(
+            &format!("parameter_set_confirmation_{status:?}").to_lowercase(),
+            CanMessage::ParameterSetConfirmation {
+                payload: ParameterSetConfirmationPayload {
+                    parameter_id: 0x01,
+                    status,
+                    value: value_bytes(&42u16.to_le_bytes()),
+                },
+            },
+        );
+    }
+
+    for lock in [ParameterLockStatus::Unlocked, ParameterLockStatus::Locked] {
+        let lock_name = format!("{lock:?}").to_lowercase();
+        push(
+            &format!("parameter_set_lock_req_{lock_name}"),
+            CanMessage::ParameterSetLockReq {
+                payload: ParameterSetLockPayload {
+                    parameter_id: 0x01,
+                    parameter_lock: lock,
+                },
+            },
+        );
+        push(
+            &format!("parameter_set_lock_confirmation_{lock_name}"),
+            CanMessage::ParameterSetLockConfirmation {
+                payload: ParameterSetLockPayload {
+                    parameter_id: 0x01,
+                    parameter_lock: lock,
+                },
+            },
+        );
+    }
+
+    push(
+        "field_get_req",
+        CanMessage::FieldGetReq {
+            payload: FieldGetReqPayload { field_id: 0x80 },
+        },
+    );
+    push(
+        "field_id_lookup_req",
+        CanMessage::FieldIDLookupReq {
+            payload: FieldIDLookupReqPayload {
+                field_name: name_bytes("tank_pressure"),
+            },
+        },
+    );
+    push(
+        "field_id_lookup_res",
+        CanMessage::FieldIDLookupRes {
+            payload: FieldIDLookupResPayload {
+                field_id: 0x80,
+                field_type: CanDataType::Float32,
+            },
+        },
+    );
+    push(
+        "field_id_lookup_res_not_found",
+        CanMessage::FieldIDLookupRes {
+            payload: FieldIDLookupResPayload {
+                field_id: 0,
+                field_type: CanDataType::Float32,
+            },
+        },
+    );
+
+    vectors
+}
+
+/// Boundary values of every field of [`CanMessageId`].
+pub fn id_vectors() -> Vec<IdVector> {
+    let id = |name: &str, receiver_id, sender_id, priority| IdVector {
+        name: name.to_string(),
+        receiver_id,
+        sender_id,
+        priority,
+    };
+    vec![
+        id("all_zero", 0, 0, CanMessagePriority::Low),
+        id("server_to_node_1", 1, 0, CanMessagePriority::Low),
+        id("node_1_to_server", 0, 1, CanMessagePriority::Low),
+        id("max_receiver", 31, 0, CanMessagePriority::Low),
+        id("max_sender", 0, 31, CanMessagePriority::Low),
+        id("high_priority", 0, 0, CanMessagePriority::High),
+        id("all_ones", 31, 31, CanMessagePriority::High),
+        id("alternating", 0b10101, 0b01010, CanMessagePriority::High),
+    ]
+}
+
+/// Frames that every implementation must reject.
+pub fn invalid_vectors() -> Vec<InvalidVector> {
+    let frame = |message_type: u8, payload: &[u8]| {
+        let mut data = vec![message_type];
+        data.extend_from_slice(payload);
+        data.resize(size_of::<CanMessageFrame>(), 0);
+        data
+    };
+    let invalid = |name: &str, data: Vec<u8>, error| InvalidVector {
+        name: name.to_string(),
+        data,
+        error,
+    };
+    vec![
+        invalid("empty", Vec::new(), DecodeError::Empty),
+        invalid(
+            "too_long",
+            vec![0; size_of::<CanMessageFrame>() + 1],
+            DecodeError::TooLong {
+                len: size_of::<CanMessageFrame>() + 1,
+            },
+        ),
+        invalid(
+            "unknown_message_type_2",
+            frame(2, &[]),
+            DecodeError::UnknownMessageType(2),
+        ),
+        invalid(
+            "unknown_message_type_64",
+            frame(64, &[]),
+            DecodeError::UnknownMessageType(64),
+        ),
+        invalid(
+            "unknown_message_type_255",
+            frame(255, &[]),
+            DecodeError::UnknownMessageType(255),
+        ),
+        invalid(
+            "telemetry_value_registration_invalid_data_type",
+            frame(20, &[0x80, 8]),
+            DecodeError::InvalidPayload { message_type: 20 },
+        ),
+        invalid(
+            "parameter_registration_invalid_data_type",
+            frame(21, &[0x01, 255]),
+            DecodeError::InvalidPayload { message_type: 21 },
+        ),
+        invalid(
+            "parameter_set_confirmation_invalid_status",
+            frame(51, &[0x01, 4]),
+            DecodeError::InvalidPayload { message_type: 51 },
+        ),
+        invalid(
+            "parameter_set_lock_req_invalid_lock",
+            frame(52, &[0x01, 2]),
+            DecodeError::InvalidPayload { message_type: 52 },
+        ),
+        invalid(
+            "parameter_set_lock_confirmation_invalid_lock",
+            frame(53, &[0x01, 255]),
+            DecodeError::InvalidPayload { message_type: 53 },
+        ),
+        invalid(
+            "field_id_lookup_res_invalid_data_type",
+            frame(63, &[0x80, 8]),
+            DecodeError::InvalidPayload { message_type: 63 },
+        ),
+    ]
+}
+
+/// Name of the error kind as written to the corpus.
+pub fn error_kind(error: &DecodeError) -> &'static str {
+    match error {
+        DecodeError::Empty => "Empty",
+        DecodeError::TooLong { .. } => "TooLong",
+        DecodeError::UnknownMessageType(_) => "UnknownMessageType",
+        DecodeError::InvalidPayload { .. } => "InvalidPayload",
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn id_raw(vector: &IdVector) -> u16 {
+    CanMessageId::new()
+        .with_receiver_id(vector.receiver_id)
+        .with_sender_id(vector.sender_id)
+        .with_priority(vector.priority)
+        .into()
+}
+
+/// Serializes the corpus as JSON. Written by hand to keep the file stable and diff-friendly.
+pub fn generate() -> String {
+    let mut out = String::new();
+    writeln!(out, "{{\n  \"version\": {FORMAT_VERSION},").unwrap();
+
+    let frames: Vec<String> = frame_vectors()
+        .into_iter()
+        .map(|vector| {
+            let frame: CanMessageFrame = vector.message.into();
+            format!(
+                "    {{ \"name\": \"{}\", \"message_type\": {}, \"data\": \"{}\" }}",
+                vector.name,
+                u8::from(frame.message_type),
+                hex(frame.as_bytes())
+            )
+        })
+        .collect();
+    writeln!(out, "  \"frames\": [\n{}\n  ],", frames.join(",\n")).unwrap();
+
+    let ids: Vec<String> = id_vectors()
+        .iter()
+        .map(|vector| {
+            format!(
+                "    {{ \"name\": \"{}\", \"receiver_id\": {}, \"sender_id\": {}, \"priority\": {}, \"raw\": {} }}",
+                vector.name,
+                vector.receiver_id,
+                vector.sender_id,
+                vector.priority as u8,
+                id_raw(vector)
+            )
+        })
+        .collect();
+    writeln!(out, "  \"can_ids\": [\n{}\n  ],", ids.join(",\n")).unwrap();
+
+    let invalid: Vec<String> = invalid_vectors()
+        .iter()
+        .map(|vector| {
+            format!(
+                "    {{ \"name\": \"{}\", \"data\": \"{}\", \"error\": \"{}\" }}",
+                vector.name,
+                hex(&vector.data),
+                error_kind(&vector.error)
+            )
+        })
+        .collect();
+    writeln!(
+        out,
+        "  \"invalid_frames\": [\n{}\n  ]\n}}",
+        invalid.join(",\n")
+    )
+    .unwrap();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CanMessageType;
+    use serde_json::Value;
+    use std::collections::BTreeSet;
+
+    const CHECKED_IN_CORPUS: &str = include_str!("../../test-vectors/golden.json");
+
+    fn parse_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn corpus() -> Value {
+        serde_json::from_str(CHECKED_IN_CORPUS).expect("golden.json is not valid JSON")
+    }
+
+    fn entries<'a>(corpus: &'a Value, key: &str) -> &'a Vec<Value> {
+        corpus[key].as_array().unwrap()
+    }
+
+    fn assert_same_names<'a>(
+        key: &str,
+        checked_in: &[Value],
+        expected: impl Iterator<Item = &'a str>,
+    ) {
+        let checked_in: BTreeSet<&str> = checked_in
+            .iter()
+            .map(|entry| entry["name"].as_str().unwrap())
+            .collect();
+        let expected: BTreeSet<&str> = expected.collect();
+        assert_eq!(
+            checked_in, expected,
+            "{key} in golden.json doesn't match the corpus, regenerate it"
+        );
+    }
+
+    #[test]
+    fn test_version() {
+        assert_eq!(corpus()["version"], FORMAT_VERSION);
+    }
+
+    #[test]
+    fn test_frames_match_encoder() {
+        let corpus = corpus();
+        let checked_in = entries(&corpus, "frames");
+        let vectors = frame_vectors();
+        assert_same_names(
+            "frames",
+            checked_in,
+            vectors.iter().map(|v| v.name.as_str()),
+        );
+
+        for vector in vectors {
+            let entry = checked_in
+                .iter()
+                .find(|entry| entry["name"] == vector.name.as_str())
+                .unwrap();
+            let data = parse_hex(entry["data"].as_str().unwrap());
+
+            let encoded: CanMessageFrame = vector.message.clone().into();
+            assert_eq!(
+                encoded.as_bytes(),
+                data.as_slice(),
+                "encoding of {} changed",
+                vector.name
+            );
+            assert_eq!(entry["message_type"], u8::from(encoded.message_type));
+            assert_eq!(
+                CanMessage::decode(&data).as_ref(),
+                Ok(&vector.message),
+                "decoding of {} changed",
+                vector.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_can_ids_match_encoder() {
+        let corpus = corpus();
+        let checked_in = entries(&corpus, "can_ids");
+        let vectors = id_vectors();
+        assert_same_names(
+            "can_ids",
+            checked_in,
+            vectors.iter().map(|v| v.name.as_str()),
+        );
+
+        for vector in vectors {
+            let entry = checked_in
+                .iter()
+                .find(|entry| entry["name"] == vector.name.as_str())
+                .unwrap();
+            let raw = entry["raw"].as_u64().unwrap() as u16;
+            assert_eq!(id_raw(&vector), raw, "encoding of {} changed", vector.name);
+
+            let id = CanMessageId::from(raw);
+            assert_eq!(entry["receiver_id"], id.receiver_id());
+            assert_eq!(entry["sender_id"], id.sender_id());
+            assert_eq!(entry["priority"], id.priority() as u8);
+        }
+    }
+
+    #[test]
+    fn test_invalid_frames_are_rejected() {
+        let corpus = corpus();
+        let checked_in = entries(&corpus, "invalid_frames");
+        let vectors = invalid_vectors();
+        assert_same_names(
+            "invalid_frames",
+            checked_in,
+            vectors.iter().map(|v| v.name.as_str()),
+        );
+
+        for vector in vectors {
+            let entry = checked_in
+                .iter()
+                .find(|entry| entry["name"] == vector.name.as_str())
+                .unwrap();
+            let data = parse_hex(entry["data"].as_str().unwrap());
+            assert_eq!(data, vector.data, "bytes of {} changed", vector.name);
+
+            let error = CanMessage::decode(&data).expect_err(&vector.name);
+            assert_eq!(
+                error, vector.error,
+                "{} was rejected differently",
+                vector.name
+            );
+            assert_eq!(entry["error"], error_kind(&error));
+        }
+    }
+
+    #[test]
+    fn test_every_message_type_is_covered() {
+        let covered: BTreeSet<CanMessageType> = frame_vectors()
+            .iter()
+            .map(|vector| vector.message.message_type())
+            .collect();
+        let all: BTreeSet<CanMessageType> = CanMessageType::iter().collect();
+        assert_eq!(covered, all);
+    }
+
+    #[test]
+    fn test_checked_in_corpus_is_up_to_date() {
+        assert!(
+            CHECKED_IN_CORPUS == generate(),
+            "test-vectors/golden.json is out of date, regenerate it with \
+             `cargo run --features test-vectors --bin liquidcan_codegen -- test-vectors ../test-vectors/golden.json`"
+        );
+    }
+}
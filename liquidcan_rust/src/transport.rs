@@ -0,0 +1,163 @@
+//! Sending and receiving messages over a CAN FD bus.
+//!
+//! A [`Transport`] moves [`BusFrame`]s, the provided methods take care of encoding and decoding
+//! [`CanMessage`]s. Implementations exist for the in-process [`VirtualBus`](virtual_bus::VirtualBus)
+//! and, with the `socketcan` feature, for Linux SocketCAN interfaces. The `embedded-can` feature
+//! adapts any driver implementing the `embedded-can` traits, also without `std`. The `tokio`
+//! feature adds [`AsyncTransport`] together with [`MessageStream`] and [`MessageSink`] adapters.
+
+#[cfg(feature = "embedded-can")]
+pub mod embedded;
+#[cfg(feature = "socketcan")]
+pub mod socketcan;
+#[cfg(feature = "tokio")]
+mod stream;
+#[cfg(feature = "std")]
+pub mod virtual_bus;
+
+#[cfg(feature = "tokio")]
+pub use stream::{AsyncTransport, MessageSink, MessageStream};
+
+use crate::{CanMessage, CanMessageFrame, CanMessageId, DecodeError};
+use core::fmt;
+
+/// Data lengths a CAN FD frame can have, see [`can_fd_len`].
+const CAN_FD_LENGTHS: [usize; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 12, 16, 20, 24, 32, 48, 64];
+
+/// Rounds `len` up to the next data length a CAN FD frame can carry.
+pub const fn can_fd_len(len: usize) -> usize {
+    let mut i = 0;
+    while i < CAN_FD_LENGTHS.len() {
+        if CAN_FD_LENGTHS[i] >= len {
+            return CAN_FD_LENGTHS[i];
+        }
+        i += 1;
+    }
+    CAN_FD_LENGTHS[CAN_FD_LENGTHS.len() - 1]
+}
+
+/// A frame as it appears on the bus: an identifier and up to 64 data bytes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BusFrame {
+    pub id: CanMessageId,
+    len: u8,
+    data: [u8; 64],
+}
+
+impl BusFrame {
+    /// Returns `None` if `data` doesn't fit into a CAN FD frame.
+    pub fn new(id: CanMessageId, data: &[u8]) -> Option<Self> {
+        let mut frame = BusFrame {
+            id,
+            len: data.len().try_into().ok()?,
+            data: [0; 64],
+        };
+        frame.data.get_mut(..data.len())?.copy_from_slice(data);
+        Some(frame)
+    }
+
+    /// Encodes a message, omitting as much trailing padding as the CAN FD frame lengths allow.
+    pub fn encode(id: CanMessageId, msg: CanMessage) -> Self {
+        let mut buf = [0u8; size_of::<CanMessageFrame>()];
+        let len = can_fd_len(msg.to_bytes(&mut buf).len());
+        BusFrame {
+            id,
+            len: len as u8,
+            data: buf,
+        }
+    }
+
+    pub fn decode(&self) -> Result<CanMessage, DecodeError> {
+        CanMessage::decode(self.data())
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data[..self.len as usize]
+    }
+}
+
+/// Errors returned when receiving messages.
+#[derive(Debug)]
+pub enum TransportError<E> {
+    /// The underlying bus failed.
+    Bus(E),
+    /// A frame was received, but it doesn't contain a valid message.
+    Decode {
+        id: CanMessageId,
+        error: DecodeError,
+    },
+}
+
+impl<E: fmt::Display> fmt::Display for TransportError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransportError::Bus(e) => write!(f, "Bus error: {e}"),
+            TransportError::Decode { id, error } => {
+                write!(f, "Invalid frame from node {}: {error}", id.sender_id())
+            }
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> core::error::Error for TransportError<E> {}
+
+/// A blocking connection to a CAN FD bus.
+pub trait Transport {
+    type Error;
+
+    fn send_frame(&mut self, frame: &BusFrame) -> Result<(), Self::Error>;
+
+    /// Blocks until the next frame arrives.
+    fn receive_frame(&mut self) -> Result<BusFrame, Self::Error>;
+
+    fn send(&mut self, id: CanMessageId, msg: CanMessage) -> Result<(), Self::Error> {
+        self.send_frame(&BusFrame::encode(id, msg))
+    }
+
+    /// Blocks until the next frame arrives and decodes it.
+    fn receive(&mut self) -> Result<(CanMessageId, CanMessage), TransportError<Self::Error>> {
+        let frame = self.receive_frame().map_err(TransportError::Bus)?;
+        let msg = frame.decode().map_err(|error| TransportError::Decode {
+            id: frame.id,
+            error,
+        })?;
+        Ok((frame.id, msg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payloads::HeartbeatPayload;
+
+    #[test]
+    fn test_can_fd_len() {
+        assert_eq!(can_fd_len(0), 0);
+        assert_eq!(can_fd_len(5), 5);
+        assert_eq!(can_fd_len(8), 8);
+        assert_eq!(can_fd_len(9), 12);
+        assert_eq!(can_fd_len(33), 48);
+        assert_eq!(can_fd_len(64), 64);
+    }
+
+    #[test]
+    fn test_encode_omits_padding() {
+        let id = CanMessageId::new().with_receiver_id(3);
+        let msg = CanMessage::HeartbeatReq {
+            payload: HeartbeatPayload::new(7),
+        };
+        let frame = BusFrame::encode(id, msg.clone());
+        assert_eq!(frame.data(), &[40, 7, 0, 0, 0]);
+        assert_eq!(frame.decode(), Ok(msg));
+
+        let frame = BusFrame::encode(id, CanMessage::NodeInfoReq);
+        assert_eq!(frame.data(), &[0]);
+    }
+
+    #[test]
+    fn test_new_rejects_oversized_data() {
+        let id = CanMessageId::new();
+        assert!(BusFrame::new(id, &[0; 64]).is_some());
+        assert!(BusFrame::new(id, &[0; 65]).is_none());
+    }
+}
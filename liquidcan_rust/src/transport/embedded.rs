@@ -0,0 +1,242 @@
+//! Transport over any CAN driver implementing the `embedded-can` traits, e.g. the FDCAN
+//! peripheral of an MCU or a USB CAN adapter driven from a host.
+//!
+//! Neither this adapter nor the messages need `std` or an allocator, so it runs in `no_std`
+//! firmware. Build the crate with `default-features = false` there.
+//!
+//! LiquidCAN frames are sent as standard-ID frames built with [`Frame::new`]. Drivers support
+//! CAN FD by accepting up to 64 data bytes there; classic-only drivers reject longer messages
+//! with [`EmbeddedCanError::FrameTooLong`].
+
+use super::{BusFrame, Transport};
+use crate::CanMessageId;
+use core::fmt;
+use embedded_can::{Frame, Id, StandardId, blocking::Can};
+
+/// Converts a received frame, skipping remote and extended-ID frames, which LiquidCAN doesn't use.
+pub fn from_embedded_frame<F: Frame>(frame: &F) -> Option<BusFrame> {
+    if frame.is_remote_frame() {
+        return None;
+    }
+    let Id::Standard(id) = frame.id() else {
+        return None;
+    };
+    BusFrame::new(CanMessageId::from(id.as_raw()), frame.data())
+}
+
+/// Converts a frame for sending. Returns `None` if the driver's frame type can't hold the data.
+pub fn to_embedded_frame<F: Frame>(frame: &BusFrame) -> Option<F> {
+    let raw_id: u16 = frame.id.into();
+    let id = StandardId::new(raw_id).expect("CanMessageId has 11 bits");
+    F::new(id, frame.data())
+}
+
+#[derive(Debug)]
+pub enum EmbeddedCanError<E> {
+    /// The driver failed.
+    Can(E),
+    /// The driver's frame type can't carry this many bytes, e.g. because it doesn't support CAN FD.
+    FrameTooLong { len: usize },
+}
+
+impl<E: fmt::Debug> fmt::Display for EmbeddedCanError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmbeddedCanError::Can(e) => write!(f, "CAN driver error: {e:?}"),
+            EmbeddedCanError::FrameTooLong { len } => {
+                write!(f, "CAN driver can't send frames with {len} data bytes")
+            }
+        }
+    }
+}
+
+impl<E: fmt::Debug> core::error::Error for EmbeddedCanError<E> {}
+
+/// Wraps a blocking `embedded-can` driver.
+pub struct EmbeddedCanTransport<C> {
+    can: C,
+}
+
+impl<C: Can> EmbeddedCanTransport<C> {
+    pub fn new(can: C) -> Self {
+        EmbeddedCanTransport { can }
+    }
+
+    pub fn into_inner(self) -> C {
+        self.can
+    }
+}
+
+impl<C: Can> Transport for EmbeddedCanTransport<C> {
+    type Error = EmbeddedCanError<C::Error>;
+
+    fn send_frame(&mut self, frame: &BusFrame) -> Result<(), Self::Error> {
+        let frame = to_embedded_frame(frame).ok_or(EmbeddedCanError::FrameTooLong {
+            len: frame.data().len(),
+        })?;
+        self.can.transmit(&frame).map_err(EmbeddedCanError::Can)
+    }
+
+    fn receive_frame(&mut self) -> Result<BusFrame, Self::Error> {
+        loop {
+            let frame = self.can.receive().map_err(EmbeddedCanError::Can)?;
+            if let Some(frame) = from_embedded_frame(&frame) {
+                return Ok(frame);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payloads::{FieldGetResPayload, HeartbeatPayload};
+    use crate::{CanMessage, raw_can_message::CanMessagePriority};
+    use embedded_can::{ErrorKind, ExtendedId};
+    use std::collections::VecDeque;
+
+    /// A frame type with a configurable maximum length, like an FDCAN driver's frame.
+    #[derive(Debug, Clone, PartialEq)]
+    struct MockFrame<const MAX_LEN: usize> {
+        id: Id,
+        remote: bool,
+        data: Vec<u8>,
+    }
+
+    impl<const MAX_LEN: usize> Frame for MockFrame<MAX_LEN> {
+        fn new(id: impl Into<Id>, data: &[u8]) -> Option<Self> {
+            (data.len() <= MAX_LEN).then(|| MockFrame {
+                id: id.into(),
+                remote: false,
+                data: data.to_vec(),
+            })
+        }
+
+        fn new_remote(id: impl Into<Id>, dlc: usize) -> Option<Self> {
+            (dlc <= MAX_LEN).then(|| MockFrame {
+                id: id.into(),
+                remote: true,
+                data: Vec::new(),
+            })
+        }
+
+        fn is_extended(&self) -> bool {
+            matches!(self.id, Id::Extended(_))
+        }
+
+        fn is_remote_frame(&self) -> bool {
+            self.remote
+        }
+
+        fn id(&self) -> Id {
+            self.id
+        }
+
+        fn dlc(&self) -> usize {
+            self.data.len()
+        }
+
+        fn data(&self) -> &[u8] {
+            &self.data
+        }
+    }
+
+    /// A driver that records transmitted frames and replays queued received frames.
+    #[derive(Default)]
+    struct MockCan<const MAX_LEN: usize> {
+        transmitted: Vec<MockFrame<MAX_LEN>>,
+        to_receive: VecDeque<MockFrame<MAX_LEN>>,
+    }
+
+    impl<const MAX_LEN: usize> Can for MockCan<MAX_LEN> {
+        type Frame = MockFrame<MAX_LEN>;
+        type Error = ErrorKind;
+
+        fn transmit(&mut self, frame: &Self::Frame) -> Result<(), Self::Error> {
+            self.transmitted.push(frame.clone());
+            Ok(())
+        }
+
+        fn receive(&mut self) -> Result<Self::Frame, Self::Error> {
+            self.to_receive.pop_front().ok_or(ErrorKind::Other)
+        }
+    }
+
+    type FdCan = MockCan<64>;
+    type ClassicCan = MockCan<8>;
+
+    fn id() -> CanMessageId {
+        CanMessageId::new()
+            .with_receiver_id(0)
+            .with_sender_id(7)
+            .with_priority(CanMessagePriority::High)
+    }
+
+    #[test]
+    fn test_send_fd_message() {
+        let mut transport = EmbeddedCanTransport::new(FdCan::default());
+        let msg = CanMessage::FieldGetRes {
+            payload: FieldGetResPayload {
+                field_id: 0x81,
+                value: [0x55; 62],
+            },
+        };
+        transport.send(id(), msg).unwrap();
+
+        let can = transport.into_inner();
+        let frame = &can.transmitted[0];
+        let raw_id: u16 = id().into();
+        assert_eq!(frame.id, Id::Standard(StandardId::new(raw_id).unwrap()));
+        assert_eq!(frame.data.len(), 64);
+        assert_eq!(&frame.data[..2], &[61, 0x81]);
+    }
+
+    #[test]
+    fn test_receive_skips_unrelated_frames() {
+        let mut can = FdCan::default();
+        can.to_receive
+            .push_back(MockFrame::new_remote(StandardId::new(1).unwrap(), 0).unwrap());
+        can.to_receive
+            .push_back(MockFrame::new(ExtendedId::new(0x12345).unwrap(), &[0]).unwrap());
+        let msg = CanMessage::HeartbeatRes {
+            payload: HeartbeatPayload::new(3),
+        };
+        let frame = BusFrame::encode(id(), msg.clone());
+        can.to_receive.push_back(to_embedded_frame(&frame).unwrap());
+
+        let mut transport = EmbeddedCanTransport::new(can);
+        assert_eq!(transport.receive().unwrap(), (id(), msg));
+    }
+
+    #[test]
+    fn test_classic_can_driver() {
+        let mut transport = EmbeddedCanTransport::new(ClassicCan::default());
+        // Short messages fit into classic CAN frames...
+        let heartbeat = CanMessage::HeartbeatReq {
+            payload: HeartbeatPayload::new(1),
+        };
+        transport.send(id(), heartbeat).unwrap();
+
+        // ...long ones don't.
+        let msg = CanMessage::FieldGetRes {
+            payload: FieldGetResPayload {
+                field_id: 0x81,
+                value: [0x55; 62],
+            },
+        };
+        assert!(matches!(
+            transport.send(id(), msg),
+            Err(EmbeddedCanError::FrameTooLong { len: 64 })
+        ));
+        assert_eq!(transport.into_inner().transmitted.len(), 1);
+    }
+
+    #[test]
+    fn test_receive_error() {
+        let mut transport = EmbeddedCanTransport::new(FdCan::default());
+        assert!(matches!(
+            transport.receive_frame(),
+            Err(EmbeddedCanError::Can(ErrorKind::Other))
+        ));
+    }
+}
@@ -0,0 +1,182 @@
+//! Transport over Linux SocketCAN interfaces with CAN FD enabled, e.g. `can0` or `vcan0`.
+
+use super::{BusFrame, Transport};
+use crate::CanMessageId;
+use socketcan::id::FdFlags;
+use socketcan::{CanAnyFrame, EmbeddedFrame, Id, Socket, StandardId};
+
+/// Converts a received frame, skipping frames LiquidCAN doesn't use (remote, error and
+/// extended-ID frames).
+pub fn from_socketcan(frame: &CanAnyFrame) -> Option<BusFrame> {
+    let (id, data) = match frame {
+        CanAnyFrame::Normal(frame) => (frame.id(), frame.data()),
+        CanAnyFrame::Fd(frame) => (frame.id(), frame.data()),
+        CanAnyFrame::Remote(_) | CanAnyFrame::Error(_) => return None,
+    };
+    let Id::Standard(id) = id else {
+        return None;
+    };
+    BusFrame::new(CanMessageId::from(id.as_raw()), data)
+}
+
+/// Converts a frame for sending. LiquidCAN always uses CAN FD frames with bit rate switching,
+/// so frames received as classic CAN frames are sent as CAN FD frames as well.
+pub fn to_socketcan(frame: &BusFrame) -> socketcan::CanFdFrame {
+    let raw_id: u16 = frame.id.into();
+    let id = StandardId::new(raw_id).expect("CanMessageId has 11 bits");
+    socketcan::CanFdFrame::with_flags(id, frame.data(), FdFlags::BRS)
+        .expect("BusFrame data fits into a CAN FD frame")
+}
+
+/// A blocking SocketCAN FD socket.
+pub struct SocketCanTransport {
+    socket: socketcan::CanFdSocket,
+}
+
+impl SocketCanTransport {
+    /// Opens the interface with the given name, e.g. `vcan0`.
+    pub fn open(interface: &str) -> std::io::Result<Self> {
+        Ok(SocketCanTransport {
+            socket: socketcan::CanFdSocket::open(interface)?,
+        })
+    }
+}
+
+impl Transport for SocketCanTransport {
+    type Error = std::io::Error;
+
+    fn send_frame(&mut self, frame: &BusFrame) -> Result<(), Self::Error> {
+        self.socket.write_frame(&to_socketcan(frame))
+    }
+
+    fn receive_frame(&mut self) -> Result<BusFrame, Self::Error> {
+        loop {
+            if let Some(frame) = from_socketcan(&self.socket.read_frame()?) {
+                return Ok(frame);
+            }
+        }
+    }
+}
+
+/// An async SocketCAN FD socket.
+///
+/// It is a `Stream` of received frames and a `Sink` for sending frames; wrap it in
+/// [`MessageStream`](super::MessageStream) or [`MessageSink`](super::MessageSink) to work with
+/// messages instead.
+#[cfg(feature = "tokio")]
+pub struct AsyncSocketCanTransport {
+    socket: socketcan::tokio::CanFdSocket,
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncSocketCanTransport {
+    /// Opens the interface with the given name, e.g. `vcan0`. Must be called within a tokio runtime.
+    pub fn open(interface: &str) -> std::io::Result<Self> {
+        Ok(AsyncSocketCanTransport {
+            socket: socketcan::tokio::CanFdSocket::open(interface)?,
+        })
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl super::AsyncTransport for AsyncSocketCanTransport {
+    type Error = std::io::Error;
+
+    async fn send_frame(&mut self, frame: BusFrame) -> Result<(), Self::Error> {
+        self.socket.write_frame(&to_socketcan(&frame)).await
+    }
+
+    async fn receive_frame(&mut self) -> Result<BusFrame, Self::Error> {
+        loop {
+            if let Some(frame) = from_socketcan(&self.socket.read_frame().await?) {
+                return Ok(frame);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl futures_core::Stream for AsyncSocketCanTransport {
+    type Item = Result<BusFrame, socketcan::Error>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        loop {
+            let frame = std::task::ready!(std::pin::Pin::new(&mut self.socket).poll_next(cx));
+            match frame {
+                Some(Ok(frame)) => {
+                    if let Some(frame) = from_socketcan(&frame) {
+                        return std::task::Poll::Ready(Some(Ok(frame)));
+                    }
+                }
+                Some(Err(e)) => return std::task::Poll::Ready(Some(Err(e))),
+                None => return std::task::Poll::Ready(None),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl futures_sink::Sink<BusFrame> for AsyncSocketCanTransport {
+    type Error = socketcan::Error;
+
+    fn poll_ready(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        futures_sink::Sink::<CanAnyFrame>::poll_ready(std::pin::Pin::new(&mut self.socket), cx)
+    }
+
+    fn start_send(mut self: std::pin::Pin<&mut Self>, frame: BusFrame) -> Result<(), Self::Error> {
+        std::pin::Pin::new(&mut self.socket).start_send(CanAnyFrame::Fd(to_socketcan(&frame)))
+    }
+
+    fn poll_flush(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        futures_sink::Sink::<CanAnyFrame>::poll_flush(std::pin::Pin::new(&mut self.socket), cx)
+    }
+
+    fn poll_close(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        futures_sink::Sink::<CanAnyFrame>::poll_close(std::pin::Pin::new(&mut self.socket), cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CanMessage;
+    use crate::payloads::HeartbeatPayload;
+
+    #[test]
+    fn test_frame_conversion_round_trip() {
+        let id = CanMessageId::new()
+            .with_receiver_id(31)
+            .with_sender_id(5)
+            .with_priority(crate::raw_can_message::CanMessagePriority::High);
+        let msg = CanMessage::HeartbeatRes {
+            payload: HeartbeatPayload::new(0xABCD),
+        };
+        let frame = BusFrame::encode(id, msg);
+
+        let socketcan_frame = to_socketcan(&frame);
+        assert!(socketcan_frame.is_brs());
+        assert_eq!(
+            from_socketcan(&CanAnyFrame::Fd(socketcan_frame)),
+            Some(frame)
+        );
+    }
+
+    #[test]
+    fn test_extended_ids_are_skipped() {
+        let id = socketcan::ExtendedId::new(0x1234).unwrap();
+        let frame = socketcan::CanFdFrame::new(id, &[0]).unwrap();
+        assert_eq!(from_socketcan(&CanAnyFrame::Fd(frame)), None);
+    }
+}
@@ -0,0 +1,215 @@
+//! Async counterparts of [`Transport`](super::Transport) for use with tokio.
+
+use super::{BusFrame, TransportError};
+use crate::{CanMessage, CanMessageId, DecodeError};
+use futures_core::Stream;
+use futures_sink::Sink;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+
+/// An async connection to a CAN FD bus.
+pub trait AsyncTransport: Send {
+    type Error;
+
+    fn send_frame(
+        &mut self,
+        frame: BusFrame,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Waits for the next frame.
+    fn receive_frame(&mut self) -> impl Future<Output = Result<BusFrame, Self::Error>> + Send;
+
+    fn send(
+        &mut self,
+        id: CanMessageId,
+        msg: CanMessage,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        self.send_frame(BusFrame::encode(id, msg))
+    }
+
+    /// Waits for the next frame and decodes it.
+    fn receive(
+        &mut self,
+    ) -> impl Future<Output = Result<(CanMessageId, CanMessage), TransportError<Self::Error>>> + Send
+    {
+        async {
+            let frame = self.receive_frame().await.map_err(TransportError::Bus)?;
+            let msg = frame.decode().map_err(|error| TransportError::Decode {
+                id: frame.id,
+                error,
+            })?;
+            Ok((frame.id, msg))
+        }
+    }
+}
+
+/// Decodes a stream of [`BusFrame`]s into messages.
+///
+/// Frames that fail to decode are yielded as errors and the stream continues. An error of the
+/// underlying bus ends the stream; it can then be retrieved with [`MessageStream::take_error`].
+pub struct MessageStream<S, E> {
+    frames: S,
+    error: Option<E>,
+}
+
+impl<S, E> MessageStream<S, E>
+where
+    S: Stream<Item = Result<BusFrame, E>> + Unpin,
+{
+    pub fn new(frames: S) -> Self {
+        MessageStream {
+            frames,
+            error: None,
+        }
+    }
+
+    /// Returns the bus error that ended the stream, if any.
+    pub fn take_error(&mut self) -> Option<E> {
+        self.error.take()
+    }
+
+    pub fn into_inner(self) -> S {
+        self.frames
+    }
+}
+
+impl<S, E> Stream for MessageStream<S, E>
+where
+    S: Stream<Item = Result<BusFrame, E>> + Unpin,
+    E: Unpin,
+{
+    type Item = Result<(CanMessageId, CanMessage), DecodeError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.error.is_some() {
+            return Poll::Ready(None);
+        }
+        match ready!(Pin::new(&mut self.frames).poll_next(cx)) {
+            Some(Ok(frame)) => Poll::Ready(Some(frame.decode().map(|msg| (frame.id, msg)))),
+            Some(Err(error)) => {
+                self.error = Some(error);
+                Poll::Ready(None)
+            }
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+/// Encodes messages into a sink of [`BusFrame`]s.
+pub struct MessageSink<K> {
+    frames: K,
+}
+
+impl<K> MessageSink<K>
+where
+    K: Sink<BusFrame> + Unpin,
+{
+    pub fn new(frames: K) -> Self {
+        MessageSink { frames }
+    }
+
+    pub fn into_inner(self) -> K {
+        self.frames
+    }
+}
+
+impl<K> Sink<(CanMessageId, CanMessage)> for MessageSink<K>
+where
+    K: Sink<BusFrame> + Unpin,
+{
+    type Error = K::Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.frames).poll_ready(cx)
+    }
+
+    fn start_send(
+        mut self: Pin<&mut Self>,
+        (id, msg): (CanMessageId, CanMessage),
+    ) -> Result<(), Self::Error> {
+        Pin::new(&mut self.frames).start_send(BusFrame::encode(id, msg))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.frames).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.frames).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payloads::HeartbeatPayload;
+    use crate::transport::virtual_bus::VirtualBus;
+    use std::future::poll_fn;
+
+    fn heartbeat(counter: u32) -> CanMessage {
+        CanMessage::HeartbeatReq {
+            payload: HeartbeatPayload::new(counter),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_async_transport() {
+        let bus = VirtualBus::new();
+        let mut server = bus.connect_async();
+        let mut node = bus.connect_async();
+
+        let id = CanMessageId::new().with_receiver_id(1);
+        server.send(id, heartbeat(1)).await.unwrap();
+        assert_eq!(node.receive().await.unwrap(), (id, heartbeat(1)));
+    }
+
+    #[tokio::test]
+    async fn test_stream_and_sink() {
+        let bus = VirtualBus::new();
+        let mut sink = MessageSink::new(bus.connect_async());
+        let mut stream = MessageStream::new(bus.connect_async());
+        // A blocking node on the same bus sees the frames as well.
+        let mut blocking_node = bus.connect();
+
+        let id = CanMessageId::new().with_receiver_id(2);
+        for counter in 0..3 {
+            poll_fn(|cx| Pin::new(&mut sink).poll_ready(cx))
+                .await
+                .unwrap();
+            Pin::new(&mut sink)
+                .start_send((id, heartbeat(counter)))
+                .unwrap();
+        }
+
+        for counter in 0..3 {
+            let received = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await;
+            assert_eq!(received, Some(Ok((id, heartbeat(counter)))));
+            assert_eq!(
+                super::super::Transport::receive(&mut blocking_node).unwrap(),
+                (id, heartbeat(counter))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_yields_decode_errors() {
+        let bus = VirtualBus::new();
+        let sender = bus.connect_async();
+        let mut stream = MessageStream::new(bus.connect_async());
+
+        let id = CanMessageId::new();
+        let mut sender = MessageSink::new(sender).into_inner();
+        AsyncTransport::send_frame(&mut sender, BusFrame::new(id, &[255]).unwrap())
+            .await
+            .unwrap();
+        AsyncTransport::send(&mut sender, id, CanMessage::NodeInfoReq)
+            .await
+            .unwrap();
+
+        let received = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await;
+        assert_eq!(received, Some(Err(DecodeError::UnknownMessageType(255))));
+        let received = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await;
+        assert_eq!(received, Some(Ok((id, CanMessage::NodeInfoReq))));
+    }
+}
@@ -0,0 +1,230 @@
+//! An in-process bus for tests and simulations.
+//!
+//! Like on a real CAN bus, every frame sent by an endpoint is delivered to all other endpoints,
+//! but not back to the sender. Blocking and async endpoints can be mixed on the same bus.
+
+use super::{BusFrame, Transport};
+use std::convert::Infallible;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+type Deliver = Box<dyn FnMut(&BusFrame) -> bool + Send>;
+
+struct Subscriber {
+    endpoint: usize,
+    /// Returns `false` once the endpoint is gone.
+    deliver: Deliver,
+}
+
+#[derive(Default)]
+struct BusState {
+    next_endpoint: usize,
+    subscribers: Vec<Subscriber>,
+}
+
+/// Handle to a virtual bus. Clones refer to the same bus.
+#[derive(Clone, Default)]
+pub struct VirtualBus {
+    state: Arc<Mutex<BusState>>,
+}
+
+impl VirtualBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Connects a new blocking endpoint.
+    pub fn connect(&self) -> VirtualBusEndpoint {
+        let (tx, rx) = mpsc::channel();
+        let endpoint = self.subscribe(Box::new(move |frame| tx.send(frame.clone()).is_ok()));
+        VirtualBusEndpoint {
+            bus: self.clone(),
+            endpoint,
+            rx,
+        }
+    }
+
+    /// Connects a new endpoint for use with tokio.
+    #[cfg(feature = "tokio")]
+    pub fn connect_async(&self) -> AsyncVirtualBusEndpoint {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let endpoint = self.subscribe(Box::new(move |frame| tx.send(frame.clone()).is_ok()));
+        AsyncVirtualBusEndpoint {
+            bus: self.clone(),
+            endpoint,
+            rx,
+        }
+    }
+
+    fn subscribe(&self, deliver: Deliver) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let endpoint = state.next_endpoint;
+        state.next_endpoint += 1;
+        state.subscribers.push(Subscriber { endpoint, deliver });
+        endpoint
+    }
+
+    fn broadcast(&self, sender: usize, frame: &BusFrame) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .subscribers
+            .retain_mut(|subscriber| subscriber.endpoint == sender || (subscriber.deliver)(frame));
+    }
+}
+
+/// A blocking endpoint of a [`VirtualBus`].
+pub struct VirtualBusEndpoint {
+    bus: VirtualBus,
+    endpoint: usize,
+    rx: Receiver<BusFrame>,
+}
+
+impl VirtualBusEndpoint {
+    /// Returns the next frame if one has already arrived.
+    pub fn try_receive_frame(&mut self) -> Option<BusFrame> {
+        self.rx.try_recv().ok()
+    }
+
+    /// Waits at most `timeout` for the next frame.
+    pub fn receive_frame_timeout(&mut self, timeout: Duration) -> Option<BusFrame> {
+        self.rx.recv_timeout(timeout).ok()
+    }
+}
+
+impl Transport for VirtualBusEndpoint {
+    type Error = Infallible;
+
+    fn send_frame(&mut self, frame: &BusFrame) -> Result<(), Self::Error> {
+        self.bus.broadcast(self.endpoint, frame);
+        Ok(())
+    }
+
+    fn receive_frame(&mut self) -> Result<BusFrame, Self::Error> {
+        // The bus keeps a sender for this endpoint alive as long as the endpoint exists.
+        Ok(self.rx.recv().expect("virtual bus endpoint disconnected"))
+    }
+}
+
+/// An async endpoint of a [`VirtualBus`].
+///
+/// It is a `Stream` of received frames and a `Sink` for sending frames; wrap it in
+/// [`MessageStream`](super::MessageStream) or [`MessageSink`](super::MessageSink) to work with
+/// messages instead.
+#[cfg(feature = "tokio")]
+pub struct AsyncVirtualBusEndpoint {
+    bus: VirtualBus,
+    endpoint: usize,
+    rx: tokio::sync::mpsc::UnboundedReceiver<BusFrame>,
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncVirtualBusEndpoint {
+    fn broadcast(&self, frame: &BusFrame) {
+        self.bus.broadcast(self.endpoint, frame);
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl super::AsyncTransport for AsyncVirtualBusEndpoint {
+    type Error = Infallible;
+
+    async fn send_frame(&mut self, frame: BusFrame) -> Result<(), Self::Error> {
+        self.broadcast(&frame);
+        Ok(())
+    }
+
+    async fn receive_frame(&mut self) -> Result<BusFrame, Self::Error> {
+        Ok(self
+            .rx
+            .recv()
+            .await
+            .expect("virtual bus endpoint disconnected"))
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl futures_core::Stream for AsyncVirtualBusEndpoint {
+    type Item = Result<BusFrame, Infallible>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx).map(|frame| frame.map(Ok))
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl futures_sink::Sink<BusFrame> for AsyncVirtualBusEndpoint {
+    type Error = Infallible;
+
+    fn poll_ready(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: std::pin::Pin<&mut Self>, frame: BusFrame) -> Result<(), Self::Error> {
+        self.broadcast(&frame);
+        Ok(())
+    }
+
+    fn poll_flush(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CanMessage, CanMessageId};
+
+    #[test]
+    fn test_frames_reach_all_other_endpoints() {
+        let bus = VirtualBus::new();
+        let mut server = bus.connect();
+        let mut node_a = bus.connect();
+        let mut node_b = bus.connect();
+
+        let id = CanMessageId::new().with_receiver_id(0).with_sender_id(1);
+        node_a.send(id, CanMessage::NodeInfoReq).unwrap();
+
+        assert_eq!(server.receive().unwrap(), (id, CanMessage::NodeInfoReq));
+        assert_eq!(node_b.receive().unwrap(), (id, CanMessage::NodeInfoReq));
+        assert_eq!(node_a.try_receive_frame(), None);
+    }
+
+    #[test]
+    fn test_dropped_endpoints_are_removed() {
+        let bus = VirtualBus::new();
+        let mut sender = bus.connect();
+        drop(bus.connect());
+
+        sender
+            .send(CanMessageId::new(), CanMessage::NodeInfoReq)
+            .unwrap();
+        assert_eq!(bus.state.lock().unwrap().subscribers.len(), 1);
+    }
+
+    #[test]
+    fn test_receive_timeout() {
+        let bus = VirtualBus::new();
+        let mut endpoint = bus.connect();
+        assert_eq!(
+            endpoint.receive_frame_timeout(Duration::from_millis(1)),
+            None
+        );
+    }
+}
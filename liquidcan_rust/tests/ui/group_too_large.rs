@@ -0,0 +1,39 @@
+use liquidcan_rust::liquid_fields::LiquidFields;
+
+#[derive(LiquidFields)]
+struct Ecu {
+    #[telemetry(group = 1)]
+    a: u32,
+    #[telemetry(group = 1)]
+    b: u32,
+    #[telemetry(group = 1)]
+    c: u32,
+    #[telemetry(group = 1)]
+    d: u32,
+    #[telemetry(group = 1)]
+    e: u32,
+    #[telemetry(group = 1)]
+    f: u32,
+    #[telemetry(group = 1)]
+    g: u32,
+    #[telemetry(group = 1)]
+    h: u32,
+    #[telemetry(group = 1)]
+    i: u32,
+    #[telemetry(group = 1)]
+    j: u32,
+    #[telemetry(group = 1)]
+    k: u32,
+    #[telemetry(group = 1)]
+    l: u32,
+    #[telemetry(group = 1)]
+    m: u32,
+    #[telemetry(group = 1)]
+    n: u32,
+    #[telemetry(group = 1)]
+    o: u32,
+    #[telemetry(group = 1)]
+    p: u32,
+}
+
+fn main() {}
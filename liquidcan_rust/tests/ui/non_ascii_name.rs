@@ -0,0 +1,9 @@
+use liquidcan_rust::liquid_fields::LiquidFields;
+
+#[derive(LiquidFields)]
+struct Ecu {
+    #[telemetry]
+    temperatur_außen: f32,
+}
+
+fn main() {}
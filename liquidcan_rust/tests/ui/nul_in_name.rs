@@ -0,0 +1,9 @@
+use liquidcan_rust::liquid_fields::LiquidFields;
+
+#[derive(LiquidFields)]
+struct Ecu {
+    #[parameter(name = "fill\0target")]
+    fill_target: u16,
+}
+
+fn main() {}